@@ -406,31 +406,103 @@ pub fn context_codegen(data: ContextData) -> Result<TokenStream, EmbeddedAssetsE
 
   #[cfg(feature = "shell-scope")]
   let shell_scope_config = {
-    use regex::Regex;
-    use tauri_utils::config::ShellAllowlistOpen;
+    use tauri_utils::config::{ShellAllowlistOpen, ShellOpenValidation};
 
     let shell_scopes = get_allowed_clis(&root, &config.tauri.allowlist.shell.scope);
 
+    let some_regex = |regex: &str| {
+      let regex = shell_open_regex(&root, regex);
+      quote!(::std::option::Option::Some(#regex))
+    };
+    let none = quote!(::std::option::Option::None);
+    let mut shell_scope_open_rules = Vec::new();
+    let mut shell_scope_open_schemes = Vec::new();
     let shell_scope_open = match &config.tauri.allowlist.shell.open {
-      ShellAllowlistOpen::Flag(false) => quote!(::std::option::Option::None),
+      ShellAllowlistOpen::Flag(false) => none,
       ShellAllowlistOpen::Flag(true) => {
-        quote!(::std::option::Option::Some(#root::regex::Regex::new("^https?://").unwrap()))
+        shell_scope_open_schemes = shell_open_schemes(&[]);
+        none
       }
-      ShellAllowlistOpen::Validate(regex) => match Regex::new(regex) {
-        Ok(_) => quote!(::std::option::Option::Some(#root::regex::Regex::new(#regex).unwrap())),
-        Err(error) => {
-          let error = error.to_string();
-          quote!({
-            compile_error!(#error);
-            ::std::option::Option::Some(#root::regex::Regex::new(#regex).unwrap())
-          })
+      ShellAllowlistOpen::Validate(regex) => some_regex(regex),
+      ShellAllowlistOpen::Config(open) => match &open.validate {
+        None => {
+          shell_scope_open_schemes = shell_open_schemes(&open.schemes);
+          none
+        }
+        Some(ShellOpenValidation::Regex(regex)) => some_regex(regex),
+        Some(ShellOpenValidation::Rules(rules)) => {
+          for (program, regex) in rules {
+            let regex = shell_open_regex(&root, regex);
+            shell_scope_open_rules.push(quote!((#program.into(), #regex)));
+          }
+          none
         }
+        _ => panic!("unknown shell open validation format, unable to prepare"),
       },
       _ => panic!("unknown shell open format, unable to prepare"),
     };
 
+    let custom_programs: Vec<TokenStream> = match &config.tauri.allowlist.shell.open {
+      ShellAllowlistOpen::Config(open) => open
+        .custom_programs
+        .iter()
+        .map(|program| {
+          if program.contains(&['/', '\\'][..]) {
+            let error = format!(
+              "shell open custom program `{}` must be a program name, not a path",
+              program
+            );
+            quote!({
+              compile_error!(#error);
+              #program.into()
+            })
+          } else {
+            quote!(#program.into())
+          }
+        })
+        .collect(),
+      _ => Vec::new(),
+    };
+
+    let open_allowed_programs = match &config.tauri.allowlist.shell.open {
+      ShellAllowlistOpen::Config(open) => match &open.allowed_programs {
+        Some(programs) => quote!(::std::option::Option::Some(
+          ::std::vec![#(#programs.into()),*]
+        )),
+        None => quote!(::std::option::Option::None),
+      },
+      _ => quote!(::std::option::Option::None),
+    };
+
+    let mut open_allowed_args = Vec::new();
+    if let ShellAllowlistOpen::Config(open) = &config.tauri.allowlist.shell.open {
+      for (program, patterns) in &open.allowed_args {
+        let patterns = patterns
+          .iter()
+          .map(|pattern| shell_open_regex(&root, &format!("^(?:{})$", pattern)));
+        open_allowed_args.push(quote!((#program.into(), ::std::vec![#(#patterns),*])));
+      }
+    }
+
+    let open_sanitize_env = match &config.tauri.allowlist.shell.open {
+      ShellAllowlistOpen::Config(open) => open.sanitize_env.clone(),
+      _ => Vec::new(),
+    };
+
+    let open_local_scope = match &config.tauri.allowlist.shell.open {
+      ShellAllowlistOpen::Config(open) => open.local_scope,
+      _ => Default::default(),
+    };
+
     quote!(#root::ShellScopeConfig {
       open: #shell_scope_open,
+      open_rules: ::std::collections::HashMap::from([#(#shell_scope_open_rules),*]),
+      open_schemes: ::std::vec![#(#shell_scope_open_schemes),*],
+      open_custom_programs: ::std::vec![#(#custom_programs),*],
+      open_allowed_programs: #open_allowed_programs,
+      open_allowed_args: ::std::collections::HashMap::from([#(#open_allowed_args),*]),
+      open_local_scope: #open_local_scope,
+      open_sanitize_env: ::std::vec![#(#open_sanitize_env.into()),*],
       scopes: #shell_scopes
     })
   };
@@ -564,6 +636,48 @@ fn find_icon<F: Fn(&&String) -> bool>(
   config_parent.join(icon_path)
 }
 
+/// The URL schemes the `shell > open` API allows without a custom validation.
+#[cfg(feature = "shell-scope")]
+const DEFAULT_SHELL_OPEN_SCHEMES: &[&str] = &["https", "http", "mailto", "tel"];
+
+#[cfg(feature = "shell-scope")]
+fn shell_open_schemes(extra: &[String]) -> Vec<TokenStream> {
+  DEFAULT_SHELL_OPEN_SCHEMES
+    .iter()
+    .map(|scheme| scheme.to_string())
+    .chain(extra.iter().map(|scheme| scheme.to_lowercase()))
+    .map(|scheme| {
+      let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+      if valid {
+        quote!(#scheme.into())
+      } else {
+        let error = format!("`{}` is not a valid shell open URL scheme", scheme);
+        quote!({
+          compile_error!(#error);
+          #scheme.into()
+        })
+      }
+    })
+    .collect()
+}
+
+#[cfg(feature = "shell-scope")]
+fn shell_open_regex(root: &TokenStream, regex: &str) -> TokenStream {
+  match regex::Regex::new(regex) {
+    Ok(_) => quote!(#root::regex::Regex::new(#regex).unwrap()),
+    Err(error) => {
+      let error = error.to_string();
+      quote!({
+        compile_error!(#error);
+        #root::regex::Regex::new(#regex).unwrap()
+      })
+    }
+  }
+}
+
 #[cfg(feature = "shell-scope")]
 fn get_allowed_clis(root: &TokenStream, scope: &ShellAllowlistScope) -> TokenStream {
   let commands = scope
@@ -78,6 +78,16 @@ impl ConfigFormat {
       }
     }
   }
+
+  /// Maps the config format to the file name of the overlay for the given bundle target,
+  /// e.g. `tauri.deb.conf.json` for the `deb` target.
+  pub fn into_target_file_name(self, target: &str) -> String {
+    match self {
+      Self::Json => format!("tauri.{}.conf.json", target),
+      Self::Json5 => format!("tauri.{}.conf.json5", target),
+      Self::Toml => format!("Tauri.{}.toml", target),
+    }
+  }
 }
 
 /// Represents all the errors that can happen while reading the config.
@@ -179,10 +189,24 @@ pub fn is_configuration_file(path: &Path) -> bool {
 ///
 /// [JSON Merge Patch (RFC 7396)]: https://datatracker.ietf.org/doc/html/rfc7396.
 pub fn read_from(root_dir: PathBuf) -> Result<Value, ConfigError> {
+  read_from_with_targets(root_dir, &[])
+}
+
+/// Reads the configuration from the given root directory like [`read_from`],
+/// then merges the overlay of each of the given bundle targets (see [`read_target`]) in order.
+pub fn read_from_with_targets<S: AsRef<str>>(
+  root_dir: PathBuf,
+  targets: &[S],
+) -> Result<Value, ConfigError> {
   let mut config: Value = parse_value(root_dir.join("tauri.conf.json"))?.0;
-  if let Some((platform_config, _)) = read_platform(root_dir)? {
+  if let Some((platform_config, _)) = read_platform(root_dir.clone())? {
     merge(&mut config, &platform_config);
   }
+  for target in targets {
+    if let Some((target_config, _)) = read_target(root_dir.clone(), target.as_ref())? {
+      merge(&mut config, &target_config);
+    }
+  }
   Ok(config)
 }
 
@@ -199,6 +223,34 @@ pub fn read_platform(root_dir: PathBuf) -> Result<Option<(Value, PathBuf)>, Conf
   }
 }
 
+/// Reads the configuration overlay for the given bundle target from the given root directory if it exists.
+///
+/// The overlay is named after the bundle target, e.g. `tauri.deb.conf.json[5]` or `Tauri.deb.toml`,
+/// and is meant to be merged after the platform-specific configuration file.
+pub fn read_target(
+  root_dir: PathBuf,
+  target: &str,
+) -> Result<Option<(Value, PathBuf)>, ConfigError> {
+  let target_config_path = root_dir.join(ConfigFormat::Json.into_target_file_name(target));
+  let exists = ENABLED_FORMATS
+    .iter()
+    .any(|format| root_dir.join(format.into_target_file_name(target)).exists());
+  if exists {
+    let (target_config, path): (Value, PathBuf) = parse_value(target_config_path)?;
+    Ok(Some((target_config, path)))
+  } else {
+    Ok(None)
+  }
+}
+
+/// Gets the bundle target of a `tauri.<target>.conf.json` file name, if it is not a platform-specific one.
+fn target_of_file_name(file_name: &str) -> Option<&str> {
+  file_name
+    .strip_prefix("tauri.")
+    .and_then(|name| name.strip_suffix(".conf.json"))
+    .filter(|target| !target.is_empty() && !target.contains('.'))
+}
+
 /// Check if a supported config file exists at path.
 ///
 /// The passed path is expected to be the path to the "default" configuration format, in this case
@@ -252,15 +304,25 @@ fn do_parse<D: DeserializeOwned>(path: PathBuf) -> Result<(D, PathBuf), ConfigEr
     .iter()
     .any(|format| file_name == format.into_platform_file_name());
 
+  let target = if lookup_platform_config {
+    None
+  } else {
+    target_of_file_name(&file_name)
+  };
+
   let json5 = path.with_file_name(if lookup_platform_config {
-    ConfigFormat::Json5.into_platform_file_name()
+    ConfigFormat::Json5.into_platform_file_name().to_string()
+  } else if let Some(target) = target {
+    ConfigFormat::Json5.into_target_file_name(target)
   } else {
-    ConfigFormat::Json5.into_file_name()
+    ConfigFormat::Json5.into_file_name().to_string()
   });
   let toml = path.with_file_name(if lookup_platform_config {
-    ConfigFormat::Toml.into_platform_file_name()
+    ConfigFormat::Toml.into_platform_file_name().to_string()
+  } else if let Some(target) = target {
+    ConfigFormat::Toml.into_target_file_name(target)
   } else {
-    ConfigFormat::Toml.into_file_name()
+    ConfigFormat::Toml.into_file_name().to_string()
   });
 
   let path_ext = path
@@ -386,3 +448,69 @@ fn read_to_string(path: &Path) -> Result<String, ConfigError> {
     error,
   })
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn config_dir(files: &[(&str, Value)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (file_name, value) in files {
+      std::fs::write(dir.path().join(file_name), value.to_string()).unwrap();
+    }
+    dir
+  }
+
+  #[test]
+  fn target_file_names() {
+    assert_eq!(
+      ConfigFormat::Json.into_target_file_name("deb"),
+      "tauri.deb.conf.json"
+    );
+    assert_eq!(target_of_file_name("tauri.deb.conf.json"), Some("deb"));
+    assert_eq!(target_of_file_name("tauri.conf.json"), None);
+    assert_eq!(target_of_file_name("tauri.deb.conf.json5"), None);
+  }
+
+  #[test]
+  fn target_overlay_merge_order() {
+    let platform_file_name = ConfigFormat::Json.into_platform_file_name();
+    let temp_dir = config_dir(&[
+      (
+        "tauri.conf.json",
+        json!({ "package": { "productName": "base", "version": "1.0.0" }, "build": { "distDir": "base" } }),
+      ),
+      (
+        platform_file_name,
+        json!({ "package": { "productName": "platform" }, "build": { "devPath": "platform" } }),
+      ),
+      (
+        "tauri.deb.conf.json",
+        json!({ "package": { "productName": "deb" } }),
+      ),
+      (
+        "tauri.appimage.conf.json",
+        json!({ "package": { "productName": "appimage" }, "build": { "distDir": null } }),
+      ),
+    ]);
+
+    let dir = temp_dir.path().to_path_buf();
+    let config = read_from(dir.clone()).unwrap();
+    assert_eq!(config["package"]["productName"], "platform");
+    assert_eq!(config["package"]["version"], "1.0.0");
+
+    let config = read_from_with_targets(dir.clone(), &["deb"]).unwrap();
+    assert_eq!(config["package"]["productName"], "deb");
+    assert_eq!(config["build"]["distDir"], "base");
+    assert_eq!(config["build"]["devPath"], "platform");
+
+    let config = read_from_with_targets(dir.clone(), &["deb", "appimage"]).unwrap();
+    assert_eq!(config["package"]["productName"], "appimage");
+    assert!(config["build"].get("distDir").is_none());
+
+    // targets without an overlay file are ignored
+    let config = read_from_with_targets(dir.clone(), &["dmg"]).unwrap();
+    assert_eq!(config["package"]["productName"], "platform");
+  }
+}
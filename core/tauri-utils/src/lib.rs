@@ -158,16 +158,204 @@ impl Display for Theme {
   }
 }
 
+/// Information about the Flatpak sandbox the app runs in, read from `/.flatpak-info`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FlatpakInfo {
+  /// The application id, e.g. `com.tauri.dev`.
+  pub id: String,
+  /// The runtime ref, e.g. `runtime/org.gnome.Platform/x86_64/43`.
+  pub runtime: Option<String>,
+  /// The branch of the application, e.g. `stable`.
+  pub branch: Option<String>,
+  /// The architecture of the application, e.g. `x86_64`.
+  pub arch: Option<String>,
+  /// The commit of the deployment the app runs from.
+  pub app_commit: Option<String>,
+  /// Where the host keeps the app deployment mounted at `/app`.
+  pub app_path: Option<std::path::PathBuf>,
+  /// Where the host keeps the runtime mounted at `/usr`.
+  pub runtime_path: Option<std::path::PathBuf>,
+}
+
+#[cfg(target_os = "linux")]
+impl FlatpakInfo {
+  /// Reads the sandbox information from `/.flatpak-info`, returning `None` outside a Flatpak sandbox.
+  pub fn detect() -> Option<Self> {
+    std::fs::read_to_string("/.flatpak-info")
+      .ok()
+      .and_then(|contents| Self::parse(&contents))
+  }
+
+  /// Parses the contents of a `.flatpak-info` file, returning `None` if it has no application id.
+  pub fn parse(contents: &str) -> Option<Self> {
+    let mut section = "";
+    let mut id = None;
+    let mut runtime = None;
+    let mut branch = None;
+    let mut arch = None;
+    let mut app_commit = None;
+    let mut app_path = None;
+    let mut runtime_path = None;
+    for line in contents.lines().map(str::trim) {
+      if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+        section = name;
+      } else if let Some((key, value)) = line.split_once('=') {
+        let value = Some(value.trim().to_string());
+        match (section, key.trim()) {
+          ("Application", "name") => id = value,
+          ("Application", "runtime") => runtime = value,
+          ("Instance", "branch") => branch = value,
+          ("Instance", "arch") => arch = value,
+          ("Instance", "app-commit") => app_commit = value,
+          ("Instance", "app-path") => app_path = value.map(Into::into),
+          ("Instance", "runtime-path") => runtime_path = value.map(Into::into),
+          _ => {}
+        }
+      }
+    }
+    Some(Self {
+      id: id?,
+      runtime,
+      branch,
+      arch,
+      app_commit,
+      app_path,
+      runtime_path,
+    })
+  }
+
+  /// The directory Flatpak keeps the app's config, data and cache in, `~/.var/app/<id>`, as the
+  /// host sees it.
+  pub fn host_app_dir(&self, home: &std::path::Path) -> std::path::PathBuf {
+    home.join(".var/app").join(&self.id)
+  }
+
+  /// Whether the path only exists inside the sandbox, like the app deployment at `/app` or the
+  /// runtime at `/usr`, so it must not be shown to the user or exported to the host.
+  pub fn is_sandboxed_path(&self, path: &std::path::Path) -> bool {
+    ["/app", "/usr", "/run/flatpak", "/.flatpak-info"]
+      .iter()
+      .any(|prefix| path.starts_with(prefix))
+  }
+
+  /// The ref of the app, e.g. `app/com.tauri.dev/x86_64/stable`, as the host `flatpak` command takes it.
+  pub fn app_ref(&self) -> Option<String> {
+    Some(format!(
+      "app/{}/{}/{}",
+      self.id,
+      self.arch.as_ref()?,
+      self.branch.as_ref()?
+    ))
+  }
+
+  /// The `active` link Flatpak points at the deployed commit of the app, next to the
+  /// [`Self::app_path`] deployment, as the host sees it.
+  pub fn active_deployment_link(&self) -> Option<std::path::PathBuf> {
+    // `<installation>/app/<id>/<arch>/<branch>/<commit>/files`
+    Some(self.app_path.as_ref()?.parent()?.parent()?.join("active"))
+  }
+}
+
+/// Information about the AppImage the app runs from, read from the `APPIMAGE` and `APPDIR`
+/// variables set by the AppImage runtime.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppImageInfo {
+  /// The path to the `.AppImage` file, which the updater replaces.
+  pub path: std::path::PathBuf,
+  /// The directory the AppImage is mounted to, e.g. `/tmp/.mount_appXXXXXX`.
+  pub mount_dir: std::path::PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+impl AppImageInfo {
+  /// Reads the AppImage information from the environment, returning `Ok(None)` outside an AppImage.
+  ///
+  /// # Errors
+  ///
+  /// Fails if the variables don't describe the AppImage the current executable runs from.
+  pub fn try_detect() -> crate::Result<Option<Self>> {
+    let lookup = |name: &str| std::env::var_os(name);
+    if lookup("APPIMAGE").is_none() && lookup("APPDIR").is_none() {
+      return Ok(None);
+    }
+    // note that it is safe to use `std::env::current_exe` here since we just loaded an AppImage.
+    Self::from_vars(lookup, &std::env::temp_dir(), &std::env::current_exe()?)
+  }
+
+  /// Validates the `APPIMAGE` and `APPDIR` values found by `lookup`.
+  ///
+  /// The `.AppImage` file must exist, and `APPDIR` must be the `${temp_dir}/.mount_*` squashfs mount
+  /// the executable runs from.
+  /// See <https://github.com/AppImage/AppImageKit/blob/1681fd84dbe09c7d9b22e13cdb16ea601aa0ec47/src/runtime.c#L501>.
+  pub fn from_vars<F: Fn(&str) -> Option<std::ffi::OsString>>(
+    lookup: F,
+    temp_dir: &std::path::Path,
+    current_exe: &std::path::Path,
+  ) -> crate::Result<Option<Self>> {
+    let (path, mount_dir) = match (lookup("APPIMAGE"), lookup("APPDIR")) {
+      (None, None) => return Ok(None),
+      (Some(path), Some(mount_dir)) => (
+        std::path::PathBuf::from(path),
+        std::path::PathBuf::from(mount_dir),
+      ),
+      (Some(_), None) => {
+        return Err(Error::InvalidAppImage(
+          "`APPIMAGE` is set without `APPDIR`".into(),
+        ))
+      }
+      (None, Some(_)) => {
+        return Err(Error::InvalidAppImage(
+          "`APPDIR` is set without `APPIMAGE`".into(),
+        ))
+      }
+    };
+    if !path.is_file() {
+      return Err(Error::InvalidAppImage(format!(
+        "`APPIMAGE` file {} does not exist",
+        path.display()
+      )));
+    }
+    let is_mount = mount_dir.parent() == Some(temp_dir)
+      && mount_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| name.starts_with(".mount_"));
+    if !is_mount {
+      return Err(Error::InvalidAppImage(format!(
+        "`APPDIR` {} is not an AppImage mount point",
+        mount_dir.display()
+      )));
+    }
+    if !current_exe.starts_with(&mount_dir) {
+      return Err(Error::InvalidAppImage(format!(
+        "the executable {} does not run from `APPDIR`",
+        current_exe.display()
+      )));
+    }
+    Ok(Some(Self { path, mount_dir }))
+  }
+}
+
 /// Information about environment variables.
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct Env {
-  /// The APPIMAGE environment variable.
+  /// The APPIMAGE environment variable. Prefer [`Self::appimage_info`], which is validated.
   #[cfg(target_os = "linux")]
   pub appimage: Option<std::ffi::OsString>,
-  /// The APPDIR environment variable.
+  /// The APPDIR environment variable. Prefer [`Self::appimage_info`], which is validated.
   #[cfg(target_os = "linux")]
   pub appdir: Option<std::ffi::OsString>,
+  /// The Flatpak sandbox the app runs in, detected once when the environment is created.
+  #[cfg(target_os = "linux")]
+  pub flatpak: Option<std::sync::Arc<FlatpakInfo>>,
+  /// The AppImage the app runs from, detected once when the environment is created.
+  #[cfg(target_os = "linux")]
+  pub appimage_info: Option<std::sync::Arc<AppImageInfo>>,
   /// The command line arguments of the current process.
   pub args: Vec<String>,
 }
@@ -178,31 +366,23 @@ impl Default for Env {
     let args = std::env::args().skip(1).collect();
     #[cfg(target_os = "linux")]
     {
-      let env = Self {
-        #[cfg(target_os = "linux")]
-        appimage: std::env::var_os("APPIMAGE"),
-        #[cfg(target_os = "linux")]
-        appdir: std::env::var_os("APPDIR"),
+      let appimage_info = AppImageInfo::try_detect().unwrap_or_else(|e| {
+        panic!(
+          "this application was not detected as an AppImage: {}; this might be a security issue.",
+          e
+        )
+      });
+      Self {
+        appimage: appimage_info
+          .as_ref()
+          .map(|info| info.path.clone().into_os_string()),
+        appdir: appimage_info
+          .as_ref()
+          .map(|info| info.mount_dir.clone().into_os_string()),
+        flatpak: FlatpakInfo::detect().map(std::sync::Arc::new),
+        appimage_info: appimage_info.map(std::sync::Arc::new),
         args,
-      };
-      if env.appimage.is_some() || env.appdir.is_some() {
-        // validate that we're actually running on an AppImage
-        // an AppImage is mounted to `/$TEMPDIR/.mount_${appPrefix}${hash}`
-        // see https://github.com/AppImage/AppImageKit/blob/1681fd84dbe09c7d9b22e13cdb16ea601aa0ec47/src/runtime.c#L501
-        // note that it is safe to use `std::env::current_exe` here since we just loaded an AppImage.
-        let is_temp = std::env::current_exe()
-          .map(|p| {
-            p.display()
-              .to_string()
-              .starts_with(&format!("{}/.mount_", std::env::temp_dir().display()))
-          })
-          .unwrap_or(true);
-
-        if !is_temp {
-          panic!("`APPDIR` or `APPIMAGE` environment variable found but this application was not detected as an AppImage; this might be a security issue.");
-        }
       }
-      env
     }
     #[cfg(not(target_os = "linux"))]
     {
@@ -265,6 +445,10 @@ pub enum Error {
   #[cfg(feature = "resources")]
   #[error("could not walk directory `{0}`, try changing `allow_walk` to true on the `ResourcePaths` constructor.")]
   NotAllowedToWalkDir(std::path::PathBuf),
+  /// The AppImage environment variables are inconsistent.
+  #[cfg(target_os = "linux")]
+  #[error("invalid AppImage environment: {0}")]
+  InvalidAppImage(String),
 }
 
 /// Suppresses the unused-variable warnings of the given inputs.
@@ -294,3 +478,139 @@ macro_rules! debug_eprintln {
     $crate::consume_unused_variable!($($arg)*);
   };
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+  use super::{AppImageInfo, FlatpakInfo};
+
+  #[test]
+  fn parse_flatpak_info() {
+    let info = FlatpakInfo::parse(
+      "[Application]
+name=com.tauri.dev
+runtime=runtime/org.gnome.Platform/x86_64/43
+
+[Instance]
+instance-id=1234
+branch=stable
+arch=x86_64
+app-commit=abc
+app-path=/var/lib/flatpak/app/com.tauri.dev/x86_64/stable/abc/files
+",
+    )
+    .unwrap();
+    assert_eq!(info.id, "com.tauri.dev");
+    assert_eq!(
+      info.runtime.as_deref(),
+      Some("runtime/org.gnome.Platform/x86_64/43")
+    );
+    assert_eq!(info.branch.as_deref(), Some("stable"));
+    assert_eq!(info.arch.as_deref(), Some("x86_64"));
+    assert_eq!(info.app_commit.as_deref(), Some("abc"));
+    assert_eq!(
+      info.app_path.as_deref(),
+      Some(std::path::Path::new(
+        "/var/lib/flatpak/app/com.tauri.dev/x86_64/stable/abc/files"
+      ))
+    );
+    assert_eq!(info.runtime_path, None);
+    assert_eq!(
+      info.app_ref().as_deref(),
+      Some("app/com.tauri.dev/x86_64/stable")
+    );
+    assert_eq!(
+      info.active_deployment_link().as_deref(),
+      Some(std::path::Path::new(
+        "/var/lib/flatpak/app/com.tauri.dev/x86_64/stable/active"
+      ))
+    );
+
+    assert!(FlatpakInfo::parse("[Instance]\nbranch=stable\n").is_none());
+    assert!(FlatpakInfo::parse("").is_none());
+  }
+
+  #[test]
+  fn flatpak_paths() {
+    use std::path::Path;
+
+    let info = FlatpakInfo {
+      id: "com.tauri.dev".into(),
+      runtime: None,
+      branch: None,
+      arch: None,
+      app_commit: None,
+      app_path: None,
+      runtime_path: None,
+    };
+    assert_eq!(
+      info.host_app_dir(Path::new("/home/tauri")),
+      Path::new("/home/tauri/.var/app/com.tauri.dev")
+    );
+    for path in [
+      "/app/lib/app/icon.png",
+      "/usr/share/fonts",
+      "/.flatpak-info",
+    ] {
+      assert!(info.is_sandboxed_path(Path::new(path)), "{}", path);
+    }
+    for path in [
+      "/home/tauri/.var/app/com.tauri.dev/config",
+      "/home/tauri/Documents",
+      "/application",
+    ] {
+      assert!(!info.is_sandboxed_path(Path::new(path)), "{}", path);
+    }
+  }
+
+  #[test]
+  fn appimage_info() {
+    use std::{ffi::OsString, path::Path};
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_dir = temp_dir.path();
+    let appimage = temp_dir.join("app.AppImage");
+    std::fs::write(&appimage, "").unwrap();
+    let mount_dir = temp_dir.join(".mount_appXXXXXX");
+    let current_exe = mount_dir.join("usr/bin/app");
+
+    let detect = |appimage: Option<&Path>, appdir: Option<&Path>, current_exe: &Path| {
+      AppImageInfo::from_vars(
+        |name| match name {
+          "APPIMAGE" => appimage.map(OsString::from),
+          "APPDIR" => appdir.map(OsString::from),
+          _ => None,
+        },
+        temp_dir,
+        current_exe,
+      )
+    };
+
+    // absent
+    assert_eq!(detect(None, None, &current_exe).unwrap(), None);
+
+    // present
+    let info = detect(Some(&appimage), Some(&mount_dir), &current_exe)
+      .unwrap()
+      .unwrap();
+    assert_eq!(info.path, appimage);
+    assert_eq!(info.mount_dir, mount_dir);
+
+    // inconsistent
+    assert!(detect(Some(&appimage), None, &current_exe).is_err());
+    assert!(detect(None, Some(&mount_dir), &current_exe).is_err());
+    assert!(detect(
+      Some(&temp_dir.join("missing.AppImage")),
+      Some(&mount_dir),
+      &current_exe
+    )
+    .is_err());
+    assert!(detect(Some(&appimage), Some(&temp_dir.join("app")), &current_exe).is_err());
+    assert!(detect(
+      Some(&appimage),
+      Some(Path::new("/opt/.mount_appXXXXXX")),
+      Path::new("/opt/.mount_appXXXXXX/usr/bin/app")
+    )
+    .is_err());
+    assert!(detect(Some(&appimage), Some(&mount_dir), Path::new("/usr/bin/app")).is_err());
+  }
+}
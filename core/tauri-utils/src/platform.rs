@@ -4,7 +4,7 @@
 
 //! Platform helper functions.
 
-use std::path::{PathBuf, MAIN_SEPARATOR};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
 
 use crate::{Env, PackageInfo};
 
@@ -145,16 +145,25 @@ pub fn target_triple() -> crate::Result<String> {
 ///
 /// On Linux, when running in an AppImage the `APPDIR` variable will be set to
 /// the mounted location of the app, and the resource dir will be
-/// `${APPDIR}/usr/lib/${exe_name}`. If not running in an AppImage, the path is
-/// `/usr/lib/${exe_name}`.  When running the app from
+/// `${APPDIR}/usr/lib/${exe_name}`. Inside a Flatpak sandbox, where the app is installed to `/app`,
+/// it's `/app/lib/${exe_name}`.
+/// Otherwise the path is `/usr/lib/${exe_name}`.  When running the app from
 /// `src-tauri/target/(debug|release)/`, the path is
 /// `${exe_dir}/../lib/${exe_name}`.
 ///
 /// On MacOS, it's `${exe_dir}../Resources` (inside .app).
-#[allow(unused_variables)]
 pub fn resource_dir(package_info: &PackageInfo, env: &Env) -> crate::Result<PathBuf> {
   let exe = current_exe()?;
   let exe_dir = exe.parent().expect("failed to get exe directory");
+  resource_dir_from(exe_dir, package_info, env)
+}
+
+#[allow(unused_variables)]
+fn resource_dir_from(
+  exe_dir: &Path,
+  package_info: &PackageInfo,
+  env: &Env,
+) -> crate::Result<PathBuf> {
   let curr_dir = exe_dir.display().to_string();
 
   if curr_dir.ends_with(format!("{S}target{S}debug", S = MAIN_SEPARATOR).as_str())
@@ -176,11 +185,19 @@ pub fn resource_dir(package_info: &PackageInfo, env: &Env) -> crate::Result<Path
         .join(format!("../lib/{}", package_info.package_name()))
         .canonicalize()
         .map_err(Into::into)
-    } else if let Some(appdir) = &env.appdir {
-      let appdir: &std::path::Path = appdir.as_ref();
+    } else if env.flatpak.is_some() {
+      // running from a Flatpak, where the binaries are installed to `/app/bin`
+      Ok(
+        exe_dir
+          .parent()
+          .unwrap_or_else(|| Path::new("/app"))
+          .join("lib")
+          .join(package_info.package_name()),
+      )
+    } else if let Some(appimage) = &env.appimage_info {
       Ok(PathBuf::from(format!(
         "{}/usr/lib/{}",
-        appdir.display(),
+        appimage.mount_dir.display(),
         package_info.package_name()
       )))
     } else {
@@ -286,3 +303,66 @@ mod windows_platform {
     }
   }
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+  use super::resource_dir_from;
+  use crate::{resources::resource_relpath, Env, FlatpakInfo, PackageInfo};
+  use std::{
+    path::{Component, Path},
+    sync::Arc,
+  };
+
+  #[test]
+  fn resource_dir_layouts() {
+    let root = tempfile::tempdir().unwrap();
+    let root = root.path();
+    let package_info = PackageInfo {
+      name: "My App".into(),
+      version: "1.0.0".parse().unwrap(),
+      authors: "",
+      description: "",
+    };
+    let resource = resource_relpath(Path::new("../assets/logo.svg"));
+    let deb = root.join("deb/data/usr");
+    let flatpak = root.join("flatpak/app");
+    for prefix in [&deb, &flatpak] {
+      let file = prefix.join("lib/my-app").join(&resource);
+      std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+      std::fs::write(file, "").unwrap();
+      std::fs::create_dir_all(prefix.join("bin")).unwrap();
+    }
+
+    let host = Env {
+      appimage: None,
+      appdir: None,
+      flatpak: None,
+      appimage_info: None,
+      args: Vec::new(),
+    };
+    let sandbox = Env {
+      flatpak: Some(Arc::new(FlatpakInfo {
+        id: "com.tauri.dev".into(),
+        runtime: None,
+        branch: None,
+        arch: None,
+        app_commit: None,
+        app_path: None,
+        runtime_path: None,
+      })),
+      ..host.clone()
+    };
+
+    for (exe_dir, env) in [(deb.join("bin"), &host), (flatpak.join("bin"), &sandbox)] {
+      let resource_dir = resource_dir_from(&exe_dir, &package_info, env).unwrap();
+      let path = resource_dir.join(&resource);
+      assert!(path.is_file(), "{}", path.display());
+      // `..` is replaced when resolving, so the resource can't escape the resource directory
+      assert!(!path.components().any(|c| c == Component::ParentDir));
+    }
+    assert_eq!(
+      resource_dir_from(Path::new("/app/bin"), &package_info, &sandbox).unwrap(),
+      Path::new("/app/lib/my-app")
+    );
+  }
+}
@@ -131,7 +131,7 @@ fn main() {
 
   alias_module("clipboard", &["write-text", "read-text"], api_all);
 
-  alias_module("app", &["show", "hide"], api_all);
+  alias_module("app", &["show", "hide", "portal-state"], api_all);
 
   let checked_features_out_path =
     Path::new(&std::env::var("OUT_DIR").unwrap()).join("checked_features");
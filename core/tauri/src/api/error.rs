@@ -85,6 +85,11 @@ pub enum Error {
   /// Shell error.
   #[error("shell error: {0}")]
   Shell(String),
+  /// The shell scope rejected opening the path or the program failed to open it.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("failed to open: {0}")]
+  ShellOpen(#[from] Box<crate::ShellScopeError>),
   /// Unknown program name.
   #[error("unknown program name: {0}")]
   UnknownProgramName(String),
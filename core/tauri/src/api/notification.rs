@@ -72,6 +72,40 @@ impl Notification {
     self
   }
 
+  /// Sets the notification icon from an [`Icon`](crate::Icon).
+  ///
+  /// [`Icon::Named`](crate::Icon::Named) icons are looked up by the notification server in its icon theme,
+  /// [`Icon::File`](crate::Icon::File) icons are shown from their path. Other icons are not supported.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Named icons are unsupported.
+  #[cfg(any(feature = "icon-ico", feature = "icon-png"))]
+  #[cfg_attr(doc_cfg, doc(cfg(any(feature = "icon-ico", feature = "icon-png"))))]
+  pub fn icon_from(mut self, icon: crate::Icon) -> crate::Result<Self> {
+    let icon = match icon {
+      #[cfg(feature = "icon-png")]
+      crate::Icon::Named(name) => {
+        if !crate::icon_theme::is_valid_name(&name) {
+          return Err(crate::Error::InvalidIconName(name));
+        }
+        if !cfg!(target_os = "linux") {
+          return Err(crate::Error::NamedIconUnsupported(name));
+        }
+        name
+      }
+      crate::Icon::File(path) => path.display().to_string(),
+      _ => {
+        return Err(crate::Error::InvalidIcon(std::io::Error::new(
+          std::io::ErrorKind::InvalidInput,
+          "notifications only support named and file icons",
+        )))
+      }
+    };
+    self.icon = Some(icon);
+    Ok(self)
+  }
+
   /// Shows the notification.
   ///
   /// # Examples
@@ -13,6 +13,13 @@ use crate::{Config, Env, PackageInfo};
 
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
+#[cfg(target_os = "linux")]
+mod migrate;
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+pub use migrate::*;
+
 // we have to wrap the BaseDirectory enum in a module for #[allow(deprecated)]
 // to work, because the procedural macros on the enum prevent it from working directly
 // TODO: remove this workaround in v2 along with deprecated variants
@@ -602,3 +609,131 @@ pub fn app_dir(config: &Config) -> Option<PathBuf> {
 pub fn log_dir(config: &Config) -> Option<PathBuf> {
   app_log_dir(config)
 }
+
+/// A path as the host system and the app itself see it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostPath {
+  /// The path on the host system, the one to show to the user or to persist across app builds.
+  pub host_view: PathBuf,
+  /// The path the app accesses the file with.
+  pub sandbox_view: PathBuf,
+}
+
+/// Canonicalizes the path and translates it to the path the host system sees.
+///
+/// Paths that can't be canonicalized, e.g. because they don't exist, are translated as they are.
+///
+/// ## Platform-specific
+///
+/// - **Linux:** Inside a Flatpak sandbox, paths under `/run/host` map to the host root and the `/app` and `/usr`
+///   mounts map to where the host keeps the app deployment and the runtime. The `~/.var/app/<id>` directories the
+///   XDG base directories point to and document portal paths are the same on the host, so they aren't changed.
+///   Outside a sandbox, both views are the same.
+/// - **macOS / Windows:** Both views are the same.
+pub fn canonicalize_host<P: AsRef<Path>>(path: P) -> HostPath {
+  let path = path.as_ref();
+  let sandbox_view = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  #[cfg(target_os = "linux")]
+  let host_view = host_view(&sandbox_view, tauri_utils::FlatpakInfo::detect().as_ref());
+  #[cfg(not(target_os = "linux"))]
+  let host_view = sandbox_view.clone();
+  HostPath {
+    host_view,
+    sandbox_view,
+  }
+}
+
+#[cfg(target_os = "linux")]
+fn host_view(path: &Path, flatpak: Option<&tauri_utils::FlatpakInfo>) -> PathBuf {
+  let flatpak = match flatpak {
+    Some(flatpak) => flatpak,
+    None => return path.to_path_buf(),
+  };
+  if let Ok(relative) = path.strip_prefix("/run/host") {
+    return Path::new("/").join(relative);
+  }
+  for (mount, host_dir) in [("/app", &flatpak.app_path), ("/usr", &flatpak.runtime_path)] {
+    if let (Ok(relative), Some(host_dir)) = (path.strip_prefix(mount), host_dir) {
+      return host_dir.join(relative);
+    }
+  }
+  path.to_path_buf()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+  use super::{canonicalize_host, host_view};
+  use std::path::{Path, PathBuf};
+  use tauri_utils::FlatpakInfo;
+
+  fn flatpak() -> FlatpakInfo {
+    FlatpakInfo::parse(
+      "[Application]
+name=com.tauri.dev
+
+[Instance]
+app-path=/var/lib/flatpak/app/com.tauri.dev/x86_64/stable/abc/files
+runtime-path=/var/lib/flatpak/runtime/org.gnome.Platform/x86_64/43/def/files
+",
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn sandbox_paths() {
+    let flatpak = flatpak();
+    for (sandbox, host) in [
+      ("/run/host/etc/os-release", "/etc/os-release"),
+      (
+        "/app/lib/app/icon.png",
+        "/var/lib/flatpak/app/com.tauri.dev/x86_64/stable/abc/files/lib/app/icon.png",
+      ),
+      (
+        "/usr/share/fonts",
+        "/var/lib/flatpak/runtime/org.gnome.Platform/x86_64/43/def/files/share/fonts",
+      ),
+      (
+        "/home/tauri/.var/app/com.tauri.dev/config/settings.json",
+        "/home/tauri/.var/app/com.tauri.dev/config/settings.json",
+      ),
+      (
+        "/run/user/1000/doc/a1b2c3/notes.txt",
+        "/run/user/1000/doc/a1b2c3/notes.txt",
+      ),
+      ("/home/tauri/Documents", "/home/tauri/Documents"),
+      ("/application/file", "/application/file"),
+    ] {
+      assert_eq!(
+        host_view(Path::new(sandbox), Some(&flatpak)),
+        PathBuf::from(host),
+        "{}",
+        sandbox
+      );
+    }
+  }
+
+  #[test]
+  fn unknown_deployment() {
+    let flatpak = FlatpakInfo::parse("[Application]\nname=com.tauri.dev\n").unwrap();
+    assert_eq!(
+      host_view(Path::new("/app/bin/app"), Some(&flatpak)),
+      Path::new("/app/bin/app")
+    );
+  }
+
+  #[test]
+  fn unsandboxed() {
+    assert_eq!(
+      host_view(Path::new("/app/bin/app"), None),
+      Path::new("/app/bin/app")
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    std::fs::write(&path, "").unwrap();
+    let host_path = canonicalize_host(dir.path().join(".").join("file"));
+    assert_eq!(host_path.sandbox_view, path.canonicalize().unwrap());
+    if !Path::new("/.flatpak-info").exists() {
+      assert_eq!(host_path.host_view, host_path.sandbox_view);
+    }
+  }
+}
@@ -0,0 +1,336 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::Env;
+
+/// The file marking the legacy data as migrated, in the per-app data directory.
+const MIGRATION_MARKER: &str = ".tauri-legacy-data-migrated";
+
+/// The legacy directories to migrate with [`migrate_legacy_data`].
+///
+/// # Examples
+/// ```
+/// use tauri::api::path::LegacyDataOptions;
+///
+/// // `~/.config/com.tauri.dev` and `~/.local/share/com.tauri.dev` of the deb package
+/// let options = LegacyDataOptions::new()
+///   .config_dir("com.tauri.dev")
+///   .data_dir("com.tauri.dev");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LegacyDataOptions {
+  config_dirs: Vec<String>,
+  data_dirs: Vec<String>,
+  dry_run: bool,
+}
+
+impl LegacyDataOptions {
+  /// Creates options migrating nothing.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Migrates the directory of this name in `~/.config` on the host.
+  #[must_use]
+  pub fn config_dir(mut self, name: impl Into<String>) -> Self {
+    self.config_dirs.push(name.into());
+    self
+  }
+
+  /// Migrates the directory of this name in `~/.local/share` on the host.
+  #[must_use]
+  pub fn data_dir(mut self, name: impl Into<String>) -> Self {
+    self.data_dirs.push(name.into());
+    self
+  }
+
+  /// Only reports what would be copied, without writing any file.
+  #[must_use]
+  pub fn dry_run(mut self, dry_run: bool) -> Self {
+    self.dry_run = dry_run;
+    self
+  }
+}
+
+/// The outcome of [`migrate_legacy_data`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum MigrationStatus {
+  /// The app is not running inside a Flatpak sandbox, so the legacy directories are in use as is.
+  NotSandboxed,
+  /// The migration ran on an earlier launch.
+  AlreadyMigrated,
+  /// None of the legacy directories is readable from the sandbox.
+  NoLegacyData,
+  /// The legacy directories were copied, or would be on a dry run.
+  Migrated,
+}
+
+/// What [`migrate_legacy_data`] did, to show to the user.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct MigrationReport {
+  /// The outcome.
+  pub status: MigrationStatus,
+  /// Whether this was a dry run, which doesn't write any file.
+  pub dry_run: bool,
+  /// The number of files copied.
+  pub copied_files: usize,
+  /// The number of bytes copied.
+  pub copied_bytes: u64,
+  /// The legacy directories that were not migrated because their per-app directory already has data.
+  pub skipped_dirs: Vec<PathBuf>,
+  /// The legacy files that were not copied: files already present in the per-app directory,
+  /// unreadable files and anything that isn't a regular file or directory.
+  pub skipped_files: Vec<PathBuf>,
+}
+
+impl MigrationReport {
+  fn new(status: MigrationStatus, dry_run: bool) -> Self {
+    Self {
+      status,
+      dry_run,
+      copied_files: 0,
+      copied_bytes: 0,
+      skipped_dirs: Vec::new(),
+      skipped_files: Vec::new(),
+    }
+  }
+}
+
+/// Copies the app's data from the legacy XDG directories of the host into the per-app directories
+/// of the Flatpak sandbox, `~/.var/app/<id>/config` and `~/.var/app/<id>/data`.
+///
+/// Users switching from another package keep their settings this way. The legacy directories
+/// must be readable from the sandbox, e.g. with the `--filesystem=xdg-config/<name>:ro` permission;
+/// the others are ignored. A legacy directory is only migrated if its per-app directory is empty,
+/// files already in the per-app directory are never overwritten.
+///
+/// Once migrated, a marker file in the per-app data directory keeps the migration from running again.
+///
+/// # Examples
+/// ```no_run
+/// use tauri::{api::path::{migrate_legacy_data, LegacyDataOptions}, Manager};
+///
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     let report = migrate_legacy_data(
+///       &app.env(),
+///       &LegacyDataOptions::new().config_dir(&app.config().tauri.bundle.identifier),
+///     )?;
+///     println!("migrated {} files", report.copied_files);
+///     Ok(())
+///   });
+/// ```
+pub fn migrate_legacy_data(
+  env: &Env,
+  options: &LegacyDataOptions,
+) -> crate::api::Result<MigrationReport> {
+  if env.flatpak.is_none() {
+    return Ok(MigrationReport::new(
+      MigrationStatus::NotSandboxed,
+      options.dry_run,
+    ));
+  }
+  let home = super::home_dir()
+    .ok_or_else(|| crate::api::Error::Path("failed to get the home directory".into()))?;
+  // the sandbox points the XDG variables at `~/.var/app/<id>`
+  let (config_dir, data_dir) = super::config_dir()
+    .zip(super::data_dir())
+    .ok_or_else(|| crate::api::Error::Path("failed to get the XDG directories".into()))?;
+
+  let mut dirs = Vec::new();
+  for name in &options.config_dirs {
+    dirs.push((home.join(".config").join(name), config_dir.join(name)));
+  }
+  for name in &options.data_dirs {
+    dirs.push((home.join(".local/share").join(name), data_dir.join(name)));
+  }
+  migrate(&dirs, &data_dir.join(MIGRATION_MARKER), options.dry_run)
+}
+
+fn migrate(
+  dirs: &[(PathBuf, PathBuf)],
+  marker: &Path,
+  dry_run: bool,
+) -> crate::api::Result<MigrationReport> {
+  if marker.exists() {
+    return Ok(MigrationReport::new(
+      MigrationStatus::AlreadyMigrated,
+      dry_run,
+    ));
+  }
+
+  let mut report = MigrationReport::new(MigrationStatus::NoLegacyData, dry_run);
+  for (legacy, target) in dirs {
+    // not granted, or there was never a legacy install
+    if fs::read_dir(legacy).is_err() {
+      continue;
+    }
+    report.status = MigrationStatus::Migrated;
+    if !is_empty_dir(target) {
+      report.skipped_dirs.push(legacy.clone());
+      continue;
+    }
+    copy_tree(legacy, target, &mut report)?;
+  }
+
+  if !dry_run {
+    if let Some(parent) = marker.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(marker, "")?;
+  }
+  Ok(report)
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+  fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none())
+}
+
+fn copy_tree(source: &Path, target: &Path, report: &mut MigrationReport) -> crate::api::Result<()> {
+  let entries = match fs::read_dir(source) {
+    Ok(entries) => entries,
+    Err(_) => {
+      report.skipped_files.push(source.to_path_buf());
+      return Ok(());
+    }
+  };
+  if !report.dry_run {
+    fs::create_dir_all(target)?;
+  }
+  for entry in entries {
+    let entry = entry?;
+    let source = entry.path();
+    let target = target.join(entry.file_name());
+    // symlinks may point outside of the legacy directory, so they are not followed
+    let file_type = entry.file_type()?;
+    if file_type.is_dir() {
+      copy_tree(&source, &target, report)?;
+    } else if !file_type.is_file() || target.exists() {
+      report.skipped_files.push(source);
+    } else if report.dry_run {
+      match entry.metadata() {
+        Ok(metadata) => {
+          report.copied_files += 1;
+          report.copied_bytes += metadata.len();
+        }
+        Err(_) => report.skipped_files.push(source),
+      }
+    } else {
+      match fs::copy(&source, &target) {
+        Ok(bytes) => {
+          report.copied_files += 1;
+          report.copied_bytes += bytes;
+        }
+        // the legacy file may be unreadable, but the target must be writable
+        Err(_) if fs::File::open(&source).is_err() => report.skipped_files.push(source),
+        Err(e) => return Err(e.into()),
+      }
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{migrate, MigrationStatus};
+  use std::{fs, path::Path};
+
+  fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+  }
+
+  #[test]
+  fn migrates_once() {
+    let root = tempfile::tempdir().unwrap();
+    let legacy = root.path().join("home/.config/com.tauri.dev");
+    let target = root
+      .path()
+      .join("home/.var/app/com.tauri.dev/config/com.tauri.dev");
+    let marker = root
+      .path()
+      .join("home/.var/app/com.tauri.dev/data/.migrated");
+    write(&legacy.join("settings.json"), "{}");
+    write(&legacy.join("themes/dark.css"), "body {}");
+    let dirs = vec![(legacy, target.clone())];
+
+    let report = migrate(&dirs, &marker, true).unwrap();
+    assert_eq!(report.status, MigrationStatus::Migrated);
+    assert_eq!(report.copied_files, 2);
+    assert_eq!(report.copied_bytes, 9);
+    assert!(!target.exists());
+    assert!(!marker.exists());
+
+    let report = migrate(&dirs, &marker, false).unwrap();
+    assert_eq!(report.status, MigrationStatus::Migrated);
+    assert_eq!(report.copied_files, 2);
+    assert_eq!(
+      fs::read_to_string(target.join("themes/dark.css")).unwrap(),
+      "body {}"
+    );
+    assert!(marker.exists());
+
+    fs::remove_dir_all(&target).unwrap();
+    let report = migrate(&dirs, &marker, false).unwrap();
+    assert_eq!(report.status, MigrationStatus::AlreadyMigrated);
+    assert!(!target.exists());
+  }
+
+  #[test]
+  fn keeps_existing_data() {
+    let root = tempfile::tempdir().unwrap();
+    let legacy_config = root.path().join("home/.config/app");
+    let legacy_data = root.path().join("home/.local/share/app");
+    let config = root.path().join("sandbox/config/app");
+    let data = root.path().join("sandbox/data/app");
+    write(&legacy_config.join("settings.json"), "legacy");
+    write(&config.join("settings.json"), "new");
+    write(&legacy_data.join("db.sqlite"), "legacy");
+    std::os::unix::fs::symlink("/etc/passwd", legacy_data.join("link")).unwrap();
+    let dirs = vec![
+      (legacy_config.clone(), config.clone()),
+      (legacy_data.clone(), data.clone()),
+      // not granted to the sandbox
+      (
+        root.path().join("home/.config/other"),
+        root.path().join("sandbox/config/other"),
+      ),
+    ];
+
+    let report = migrate(&dirs, &root.path().join("marker"), false).unwrap();
+    assert_eq!(report.status, MigrationStatus::Migrated);
+    assert_eq!(report.skipped_dirs, vec![legacy_config]);
+    assert_eq!(report.skipped_files, vec![legacy_data.join("link")]);
+    assert_eq!(report.copied_files, 1);
+    assert_eq!(
+      fs::read_to_string(config.join("settings.json")).unwrap(),
+      "new"
+    );
+    assert_eq!(
+      fs::read_to_string(data.join("db.sqlite")).unwrap(),
+      "legacy"
+    );
+    assert!(!data.join("link").exists());
+  }
+
+  #[test]
+  fn no_legacy_data() {
+    let root = tempfile::tempdir().unwrap();
+    let dirs = vec![(root.path().join("missing"), root.path().join("target"))];
+    let report = migrate(&dirs, &root.path().join("marker"), false).unwrap();
+    assert_eq!(report.status, MigrationStatus::NoLegacyData);
+    assert!(!root.path().join("target").exists());
+  }
+}
@@ -55,8 +55,8 @@ pub use command::*;
 pub fn current_binary(_env: &Env) -> std::io::Result<PathBuf> {
   // if we are running from an AppImage, we ONLY want the set AppImage path
   #[cfg(target_os = "linux")]
-  if let Some(app_image_path) = &_env.appimage {
-    return Ok(PathBuf::from(app_image_path));
+  if let Some(appimage) = &_env.appimage_info {
+    return Ok(appimage.path.clone());
   }
 
   tauri_utils::platform::current_exe()
@@ -67,6 +67,17 @@ pub fn current_binary(_env: &Env) -> std::io::Result<PathBuf> {
 /// See [`current_binary`] for platform specific behavior, and
 /// [`tauri_utils::platform::current_exe`] for possible security implications.
 ///
+/// # Platform-specific behavior
+///
+/// ## Linux
+///
+/// Inside a Flatpak sandbox, the app is relaunched in a new sandbox through the `Spawn` method of
+/// the Flatpak portal, starting the latest deployed version so an installed update is picked up.
+/// The portal starts the new sandbox for the app id of the calling one, the id [`Env::flatpak`]
+/// reports, so this does what `flatpak run <app-id>` does on the host, without the
+/// `--talk-name=org.freedesktop.Flatpak` permission running `flatpak` on the host requires.
+/// If the relaunch fails, the error is printed and the process exits with a failure code.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -81,6 +92,15 @@ pub fn current_binary(_env: &Env) -> std::io::Result<PathBuf> {
 pub fn restart(env: &Env) {
   use std::process::{exit, Command};
 
+  #[cfg(target_os = "linux")]
+  if let Some(flatpak) = &env.flatpak {
+    if let Err(e) = relaunch_flatpak(&env.args) {
+      eprintln!("failed to restart the Flatpak {}: {}", flatpak.id, e);
+      exit(1);
+    }
+    exit(0);
+  }
+
   if let Ok(path) = current_binary(env) {
     Command::new(path)
       .args(&env.args)
@@ -90,3 +110,114 @@ pub fn restart(env: &Env) {
 
   exit(0);
 }
+
+/// The `flatpak-spawn` shipped with the Flatpak runtimes.
+#[cfg(target_os = "linux")]
+const FLATPAK_SPAWN: &str = "/usr/bin/flatpak-spawn";
+
+/// How long `flatpak-spawn` is watched for a refused spawn.
+///
+/// It exits with a failure as soon as the portal returns an error. Once the portal returns the pid
+/// of the new instance, it only exits when that instance does, so running past this means the spawn
+/// succeeded.
+#[cfg(target_os = "linux")]
+const SPAWN_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Starts the app in a new Flatpak sandbox through the portal.
+#[cfg(target_os = "linux")]
+fn relaunch_flatpak(args: &[String]) -> std::io::Result<()> {
+  use std::{
+    io::{Error, ErrorKind},
+    process::Command,
+  };
+
+  if !std::path::Path::new(FLATPAK_SPAWN).exists() {
+    return Err(Error::new(
+      ErrorKind::NotFound,
+      format!("{} is not available in the sandbox", FLATPAK_SPAWN),
+    ));
+  }
+  let exe = tauri_utils::platform::current_exe()?;
+  let child = Command::new(FLATPAK_SPAWN)
+    .args(flatpak_spawn_args(&exe, args))
+    .spawn()?;
+  watch_spawn(child, SPAWN_CHECK_TIMEOUT)
+}
+
+/// Fails if `flatpak-spawn` exits with a failure before the timeout, see [`SPAWN_CHECK_TIMEOUT`].
+#[cfg(target_os = "linux")]
+fn watch_spawn(
+  mut child: std::process::Child,
+  timeout: std::time::Duration,
+) -> std::io::Result<()> {
+  use std::{
+    io::{Error, ErrorKind},
+    thread::sleep,
+    time::{Duration, Instant},
+  };
+
+  let deadline = Instant::now() + timeout;
+  loop {
+    match child.try_wait()? {
+      Some(status) if !status.success() => {
+        return Err(Error::new(
+          ErrorKind::Other,
+          format!("flatpak-spawn exited with {}", status),
+        ))
+      }
+      // the new instance exited successfully already
+      Some(_) => return Ok(()),
+      None if Instant::now() >= deadline => return Ok(()),
+      None => sleep(Duration::from_millis(50)),
+    }
+  }
+}
+
+#[cfg(target_os = "linux")]
+fn flatpak_spawn_args(exe: &std::path::Path, args: &[String]) -> Vec<std::ffi::OsString> {
+  // `--latest-version` makes the portal start the most recent deployment instead of the running one
+  let mut spawn_args = vec!["--latest-version".into(), exe.into()];
+  spawn_args.extend(args.iter().map(Into::into));
+  spawn_args
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+  use super::{flatpak_spawn_args, watch_spawn};
+  use std::{path::Path, process::Command, time::Duration};
+
+  #[test]
+  fn flatpak_relaunch() {
+    assert_eq!(
+      flatpak_spawn_args(
+        Path::new("/app/bin/my-app"),
+        &["--flag".into(), "file with spaces".into()]
+      ),
+      vec![
+        "--latest-version",
+        "/app/bin/my-app",
+        "--flag",
+        "file with spaces"
+      ]
+    );
+  }
+
+  #[test]
+  fn flatpak_spawn_watch() {
+    let spawn = |program: &str, args: &[&str]| Command::new(program).args(args).spawn().unwrap();
+    let timeout = Duration::from_millis(500);
+    // the portal refused the spawn
+    let err = watch_spawn(spawn("false", &[]), timeout).unwrap_err();
+    assert!(err.to_string().starts_with("flatpak-spawn exited with"));
+    // the new instance started and is still running, or already exited
+    let mut running = spawn("sleep", &["5"]);
+    let pid = running.id();
+    assert!(watch_spawn(running, timeout).is_ok());
+    assert!(Command::new("kill")
+      .arg(pid.to_string())
+      .status()
+      .unwrap()
+      .success());
+    assert!(watch_spawn(spawn("true", &[]), timeout).is_ok());
+  }
+}
@@ -5,7 +5,7 @@
 use std::{
   collections::HashMap,
   io::{BufReader, Write},
-  path::PathBuf,
+  path::{Path, PathBuf},
   process::{Command as StdCommand, Stdio},
   sync::{Arc, Mutex, RwLock},
   thread::spawn,
@@ -24,7 +24,7 @@ pub use encoding_rs::Encoding;
 use os_pipe::{pipe, PipeReader, PipeWriter};
 use serde::Serialize;
 use shared_child::SharedChild;
-use tauri_utils::platform;
+use tauri_utils::{debug_eprintln, platform};
 
 type ChildStore = Arc<Mutex<HashMap<u32, Arc<SharedChild>>>>;
 
@@ -134,14 +134,79 @@ pub struct Output {
   pub stderr: String,
 }
 
+/// The directory the Flatpak bundle installs the sidecars to.
+#[cfg(target_os = "linux")]
+const FLATPAK_BIN_DIR: &str = "/app/bin";
+
 fn relative_command_path(command: String) -> crate::Result<String> {
-  match platform::current_exe()?.parent() {
-    #[cfg(windows)]
-    Some(exe_dir) => Ok(format!("{}\\{}.exe", exe_dir.display(), command)),
-    #[cfg(not(windows))]
-    Some(exe_dir) => Ok(format!("{}/{}", exe_dir.display(), command)),
-    None => Err(crate::api::Error::Command("Could not evaluate executable dir".to_string()).into()),
+  let exe = platform::current_exe()?;
+  let exe_dir = exe
+    .parent()
+    .ok_or_else(|| crate::api::Error::Command("Could not evaluate executable dir".to_string()))?;
+  let target_triple = platform::target_triple().ok();
+  #[cfg(target_os = "linux")]
+  let flatpak_bin_dir = tauri_utils::FlatpakInfo::detect().map(|_| Path::new(FLATPAK_BIN_DIR));
+  #[cfg(not(target_os = "linux"))]
+  let flatpak_bin_dir = None;
+  let path = find_sidecar(exe_dir, &command, target_triple.as_deref(), flatpak_bin_dir)?;
+  Ok(path.display().to_string())
+}
+
+/// Finds the first of the [`sidecar_candidates`] that exists.
+fn find_sidecar(
+  exe_dir: &Path,
+  command: &str,
+  target_triple: Option<&str>,
+  flatpak_bin_dir: Option<&Path>,
+) -> crate::api::Result<PathBuf> {
+  let candidates = sidecar_candidates(exe_dir, command, target_triple, flatpak_bin_dir);
+  match candidates.iter().find(|path| path.is_file()) {
+    Some(path) => {
+      debug_eprintln!("using sidecar {}", path.display());
+      Ok(path.clone())
+    }
+    None => Err(crate::api::Error::Command(format!(
+      "sidecar `{}` not found, tried {}",
+      command,
+      candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+    ))),
+  }
+}
+
+/// The paths the sidecar may be installed at, in lookup order.
+///
+/// The bundles install sidecars next to the main binary without the target triple suffix, the
+/// Flatpak bundle installs them to `/app/bin`.
+fn sidecar_candidates(
+  exe_dir: &Path,
+  command: &str,
+  target_triple: Option<&str>,
+  flatpak_bin_dir: Option<&Path>,
+) -> Vec<PathBuf> {
+  let file_name = |name: &str| {
+    if cfg!(windows) {
+      format!("{}.exe", name)
+    } else {
+      name.to_string()
+    }
+  };
+  let unsuffixed = target_triple
+    .and_then(|triple| command.strip_suffix(&format!("-{}", triple)))
+    .filter(|name| !name.is_empty());
+
+  let mut candidates = vec![exe_dir.join(file_name(command))];
+  if let Some(unsuffixed) = unsuffixed {
+    candidates.push(exe_dir.join(file_name(unsuffixed)));
+  }
+  if let Some(bin_dir) = flatpak_bin_dir {
+    candidates.push(bin_dir.join(file_name(unsuffixed.unwrap_or(command))));
   }
+  candidates.dedup();
+  candidates
 }
 
 impl From<Command> for StdCommand {
@@ -181,6 +246,9 @@ impl Command {
   ///
   /// A sidecar program is a embedded external binary in order to make your application work
   /// or to prevent users having to install additional dependencies (e.g. Node.js, Python, etc).
+  ///
+  /// The sidecar is looked up next to the current executable, also without the target triple suffix,
+  /// and in `/app/bin` inside a Flatpak sandbox. Returns an error listing the paths tried if none exists.
   pub fn new_sidecar<S: Into<String>>(program: S) -> crate::Result<Self> {
     Ok(Self::new(relative_command_path(program.into())?))
   }
@@ -475,4 +543,56 @@ mod test {
       }
     });
   }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn sidecar_layouts() {
+    let root = tempfile::tempdir().unwrap();
+    let root = root.path();
+    // the deb and AppImage bundles install sidecars next to the main binary
+    let usr_bin = root.join("usr/bin");
+    // the Flatpak bundle installs them to `/app/bin`, the main binary may live elsewhere
+    let app_bin = root.join("app/bin");
+    let app_lib = root.join("app/lib/my-app");
+    for dir in [&usr_bin, &app_bin, &app_lib] {
+      std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(usr_bin.join("server"), "").unwrap();
+    std::fs::write(app_bin.join("server"), "").unwrap();
+    let triple = Some("x86_64-unknown-linux-gnu");
+
+    for command in ["server", "server-x86_64-unknown-linux-gnu"] {
+      assert_eq!(
+        find_sidecar(&usr_bin, command, triple, None).unwrap(),
+        usr_bin.join("server")
+      );
+      assert_eq!(
+        find_sidecar(&app_lib, command, triple, Some(&app_bin)).unwrap(),
+        app_bin.join("server")
+      );
+    }
+    // next to the main binary takes precedence
+    assert_eq!(
+      find_sidecar(&usr_bin, "server", triple, Some(&app_bin)).unwrap(),
+      usr_bin.join("server")
+    );
+    // `/app/bin` is only looked at inside the sandbox
+    assert!(find_sidecar(&app_lib, "server", triple, None).is_err());
+
+    let error = find_sidecar(
+      &app_lib,
+      "missing-x86_64-unknown-linux-gnu",
+      triple,
+      Some(&app_bin),
+    )
+    .unwrap_err()
+    .to_string();
+    for tried in [
+      app_lib.join("missing-x86_64-unknown-linux-gnu"),
+      app_lib.join("missing"),
+      app_bin.join("missing"),
+    ] {
+      assert!(error.contains(&tried.display().to_string()), "{}", error);
+    }
+  }
 }
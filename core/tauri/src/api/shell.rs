@@ -5,9 +5,23 @@
 //! Types and functions related to shell.
 
 use crate::ShellScope;
-use std::str::FromStr;
+use shared_child::SharedChild;
+use std::{
+  borrow::Cow,
+  fmt,
+  future::Future,
+  path::Path,
+  process::{Command, ExitStatus, Stdio},
+  str::FromStr,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+/// How long a launched program is watched for an early exit before [`open()`] returns.
+pub(crate) const EXIT_CHECK_TIMEOUT: Duration = Duration::from_millis(200);
 
 /// Program to use on the [`open()`] call.
+#[derive(Clone)]
 pub enum Program {
   /// Use the `open` program.
   Open,
@@ -31,8 +45,28 @@ pub enum Program {
   Chromium,
   /// Use the `Safari` program.
   Safari,
+  /// Use the `Brave` program.
+  Brave,
+  /// Use the `Microsoft Edge` program.
+  Edge,
+  /// Use the `Opera` program.
+  Opera,
+  /// Use the `Epiphany` (GNOME Web) program.
+  Epiphany,
+  /// Use the `LibreWolf` program.
+  LibreWolf,
+  /// Use a custom program.
+  ///
+  /// The program name must be listed in `tauri > allowlist > shell > open > customPrograms`,
+  /// otherwise [`open()`] rejects it. Names are never parsed into a custom program, so this must
+  /// be constructed explicitly.
+  Custom(String),
 }
 
+/// Parses the name of one of the known programs, case-insensitively.
+///
+/// Unknown names are rejected with [`super::Error::UnknownProgramName`] rather than parsed as a
+/// [`Program::Custom`], so a typo can't launch an arbitrary executable.
 impl FromStr for Program {
   type Err = super::Error;
 
@@ -49,6 +83,11 @@ impl FromStr for Program {
       "chrome" | "google chrome" => Self::Chrome,
       "chromium" => Self::Chromium,
       "safari" => Self::Safari,
+      "brave" | "brave browser" | "brave-browser" => Self::Brave,
+      "edge" | "msedge" | "microsoft edge" | "microsoft-edge" => Self::Edge,
+      "opera" => Self::Opera,
+      "epiphany" | "gnome web" => Self::Epiphany,
+      "librewolf" => Self::LibreWolf,
       _ => return Err(super::Error::UnknownProgramName(s.to_string())),
     };
     Ok(p)
@@ -56,8 +95,8 @@ impl FromStr for Program {
 }
 
 impl Program {
-  pub(crate) fn name(self) -> &'static str {
-    match self {
+  pub(crate) fn name(self) -> Cow<'static, str> {
+    let name = match self {
       Self::Open => "open",
       Self::Start => "start",
       Self::XdgOpen => "xdg-open",
@@ -85,14 +124,541 @@ impl Program {
       Self::Safari => "Safari",
       #[cfg(not(target_os = "macos"))]
       Self::Safari => "safari",
+
+      #[cfg(target_os = "macos")]
+      Self::Brave => "Brave Browser",
+      #[cfg(windows)]
+      Self::Brave => "brave",
+      #[cfg(not(any(windows, target_os = "macos")))]
+      Self::Brave => "brave-browser",
+
+      #[cfg(target_os = "macos")]
+      Self::Edge => "Microsoft Edge",
+      #[cfg(windows)]
+      Self::Edge => "msedge",
+      #[cfg(not(any(windows, target_os = "macos")))]
+      Self::Edge => "microsoft-edge",
+
+      #[cfg(target_os = "macos")]
+      Self::Opera => "Opera",
+      #[cfg(not(target_os = "macos"))]
+      Self::Opera => "opera",
+
+      Self::Epiphany => "epiphany",
+
+      #[cfg(target_os = "macos")]
+      Self::LibreWolf => "LibreWolf",
+      #[cfg(not(target_os = "macos"))]
+      Self::LibreWolf => "librewolf",
+
+      Self::Custom(name) => return Cow::Owned(name),
+    };
+    Cow::Borrowed(name)
+  }
+
+  /// Whether the program can be launched once with multiple paths.
+  pub(crate) fn accepts_multiple_paths(&self) -> bool {
+    match self {
+      Self::Firefox
+      | Self::Chrome
+      | Self::Chromium
+      | Self::Safari
+      | Self::Brave
+      | Self::Edge
+      | Self::Opera
+      | Self::Epiphany
+      | Self::LibreWolf => true,
+      #[cfg(target_os = "macos")]
+      Self::Open => true,
+      Self::Gio => true,
+      _ => false,
+    }
+  }
+}
+
+/// The mechanism an [`open()`] call used to open the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OpenMechanism {
+  /// The path was handed to the system default handler.
+  Default,
+  /// The path was passed to the given program.
+  Launched(String),
+}
+
+/// A validated open request passed to the [`crate::Builder::on_shell_open`] hook.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OpenRequest<'a> {
+  /// The validated path or URL that will be opened.
+  ///
+  /// Local paths are already canonicalized.
+  pub target: &'a str,
+  /// The resolved name of the program the target will be opened with, or `None` for the system
+  /// default handler.
+  pub program: Option<&'a str>,
+  /// The extra arguments passed to the program.
+  pub args: &'a [String],
+  /// The label of the window that requested the open, or `None` when it was requested from Rust.
+  pub window: Option<&'a str>,
+}
+
+/// The decision of the [`crate::Builder::on_shell_open`] hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenDecision {
+  /// Open the target.
+  Allow,
+  /// Deny the request, failing with [`crate::ShellScopeError::OpenDenied`].
+  Deny {
+    /// Why the request was denied.
+    reason: String,
+  },
+}
+
+/// The [`crate::Builder::on_shell_open`] hook.
+pub(crate) type OnShellOpen = dyn Fn(OpenRequest<'_>) -> OpenDecision + Send + Sync;
+
+/// Information about what an [`open()`] call did.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OpenOutcome {
+  /// How the path was opened.
+  pub mechanism: OpenMechanism,
+  /// The launched program, if it was still running after the exit status check.
+  ///
+  /// Always `None` for [`OpenMechanism::Default`] and when [`OpenOptions::wait`] is set.
+  pub program: Option<OpenedProgram>,
+  /// The exit status of the launched program, if [`OpenOptions::wait`] is set.
+  pub status: Option<ExitStatus>,
+}
+
+/// A program launched by [`open()`] that was still running after the exit status check.
+///
+/// The process is reaped in the background once it exits. The handle keeps the child, so
+/// [`Self::kill`] never signals another process that reused its id.
+#[derive(Clone)]
+pub struct OpenedProgram(Arc<SharedChild>);
+
+impl OpenedProgram {
+  /// The process id of the program.
+  pub fn pid(&self) -> u32 {
+    self.0.id()
+  }
+
+  /// Kills the program, doing nothing if it already exited.
+  pub fn kill(&self) -> crate::api::Result<()> {
+    self.0.kill().map_err(Into::into)
+  }
+
+  /// The exit status of the program, `None` while it's running.
+  pub fn try_wait(&self) -> crate::api::Result<Option<ExitStatus>> {
+    self.0.try_wait().map_err(Into::into)
+  }
+}
+
+impl fmt::Debug for OpenedProgram {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OpenedProgram")
+      .field("pid", &self.pid())
+      .finish()
+  }
+}
+
+/// Options for [`open_with_options()`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct OpenOptions {
+  /// Whether to wait for the program to exit, returning its exit status.
+  pub wait: bool,
+  /// Whether to launch the program in its own session or process group, so it survives the app
+  /// exiting.
+  pub detach: bool,
+  /// Extra arguments passed to the program before the path.
+  pub args: Vec<String>,
+}
+
+impl OpenOptions {
+  /// Creates the default options: the program is neither waited for nor detached.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Waits for the program to exit and returns its exit status in [`OpenOutcome::status`].
+  ///
+  /// This is the `$EDITOR` pattern: open a file and continue once the program is closed.
+  /// It requires a program to be specified, since the system default handler can't be waited for.
+  #[must_use]
+  pub fn wait(mut self, wait: bool) -> Self {
+    self.wait = wait;
+    self
+  }
+
+  /// Launches the program detached from the app's session (`setsid` on Unix) or process group
+  /// (`CREATE_NEW_PROCESS_GROUP` on Windows).
+  #[must_use]
+  pub fn detach(mut self, detach: bool) -> Self {
+    self.detach = detach;
+    self
+  }
+
+  /// Passes extra arguments to the program, e.g. `--private-window` for Firefox.
+  ///
+  /// The arguments are inserted before the path, which is always the last argument. On macOS they
+  /// are passed to `open`, so use its `--args` flag to forward arguments to the program. Each
+  /// argument must be allowed by the `tauri > allowlist > shell > open > allowedArgs` patterns of the
+  /// program. Extra arguments require a program, since the system default handler takes none.
+  #[must_use]
+  pub fn args<I, S>(mut self, args: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.args = args.into_iter().map(Into::into).collect();
+    self
+  }
+}
+
+/// The mechanism a [`show_in_folder()`] call used to reveal the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShowInFolderMechanism {
+  /// The item was selected in Windows Explorer with `explorer /select,`.
+  Explorer,
+  /// The item was selected in the Finder with `open -R`.
+  Finder,
+  /// The item was selected in the file manager with the `org.freedesktop.FileManager1.ShowItems`
+  /// DBus call.
+  FileManager1,
+  /// The containing folder was opened with the system default handler, without selecting the item.
+  ParentFolder,
+}
+
+/// Creates the command that opens a path with the given program.
+///
+/// The path must be appended with [`Command::arg`]. With `wait`, the command only exits once the
+/// program does.
+#[cfg(target_os = "macos")]
+pub(crate) fn program_command(program: &str, wait: bool) -> std::io::Result<Command> {
+  let mut command = Command::new("/usr/bin/open");
+  if wait {
+    command.arg("-W");
+  }
+  if program != "open" {
+    command.arg("-a").arg(program);
+  }
+  Ok(command)
+}
+
+/// Creates the command that opens a path with the given program.
+///
+/// The path must be appended with [`Command::arg`]. With `wait`, the command only exits once the
+/// program does. The program name is escaped for cmd.exe like the path.
+#[cfg(windows)]
+pub(crate) fn program_command(program: &str, wait: bool) -> std::io::Result<Command> {
+  let mut command = Command::new("cmd");
+  command.arg("/c").arg("start");
+  if wait {
+    command.arg("/wait");
+  }
+  // the first quoted `start` argument is the window title
+  command.arg("");
+  if program != "start" {
+    command.arg(escape_cmd_arg(program)?.as_ref());
+  }
+  Ok(command)
+}
+
+/// Creates the command that opens a path with the given program.
+///
+/// The path must be appended with [`Command::arg`]. The program is run directly, so `wait` needs
+/// no special handling.
+#[cfg(not(any(windows, target_os = "macos")))]
+pub(crate) fn program_command(program: &str, _wait: bool) -> std::io::Result<Command> {
+  let mut command = Command::new(program);
+  if program == "gio" {
+    command.arg("open");
+  }
+  Ok(command)
+}
+
+/// Returns the argument that passes the path to the program of [`program_command`].
+///
+/// On Windows the program is launched through `cmd /c start`, so the path is escaped for cmd.exe.
+pub(crate) fn program_arg(path: &str) -> std::io::Result<Cow<'_, str>> {
+  #[cfg(windows)]
+  return escape_cmd_arg(path);
+  #[cfg(not(windows))]
+  Ok(Cow::Borrowed(path))
+}
+
+/// Escapes an argument of a `cmd /c` command line so cmd.exe passes it through unchanged.
+///
+/// [`Command`] only quotes arguments that contain spaces or tabs. cmd.exe leaves metacharacters
+/// inside quotes alone, but unquoted ones like the `&` in a URL query string must be escaped with a
+/// caret. Quotes and line breaks can't be escaped, and neither can `%VAR%` expansion inside quotes,
+/// so those arguments are rejected.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn escape_cmd_arg(arg: &str) -> std::io::Result<Cow<'_, str>> {
+  let invalid = || {
+    std::io::Error::new(
+      std::io::ErrorKind::InvalidInput,
+      format!("`{}` can't be passed to cmd.exe safely", arg),
+    )
+  };
+  if arg.contains(&['"', '\r', '\n', '\0'][..]) {
+    return Err(invalid());
+  }
+  if arg.is_empty() || arg.contains(&[' ', '\t'][..]) {
+    return if arg.matches('%').count() > 1 {
+      Err(invalid())
+    } else {
+      Ok(Cow::Borrowed(arg))
+    };
+  }
+  const METACHARACTERS: &[char] = &['^', '&', '|', '<', '>', '(', ')', '%', '!'];
+  if !arg.contains(METACHARACTERS) {
+    return Ok(Cow::Borrowed(arg));
+  }
+  let mut escaped = String::with_capacity(arg.len() * 2);
+  for c in arg.chars() {
+    if METACHARACTERS.contains(&c) {
+      escaped.push('^');
+    }
+    escaped.push(c);
+  }
+  Ok(Cow::Owned(escaped))
+}
+
+/// Spawns the command and waits up to [`EXIT_CHECK_TIMEOUT`] for it to exit.
+///
+/// Returns an error if the program exits with a non-zero status in that time, e.g. `xdg-open`
+/// exiting with `3` when there is no handler for the path. Returns the child if it is still
+/// running, it is then waited for on a background thread so it doesn't linger as a zombie process.
+pub(crate) fn launch(mut command: Command) -> std::io::Result<Option<OpenedProgram>> {
+  let child = Arc::new(SharedChild::spawn(
+    command
+      .stdin(Stdio::null())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null()),
+  )?);
+
+  let started = Instant::now();
+  loop {
+    if let Some(status) = child.try_wait()? {
+      return if status.success() {
+        Ok(None)
+      } else {
+        Err(std::io::Error::new(
+          std::io::ErrorKind::Other,
+          format!(
+            "`{}` exited with {}",
+            command.get_program().to_string_lossy(),
+            status
+          ),
+        ))
+      };
+    }
+    if started.elapsed() >= EXIT_CHECK_TIMEOUT {
+      let reaped = child.clone();
+      std::thread::spawn(move || {
+        let _ = reaped.wait();
+      });
+      return Ok(Some(OpenedProgram(child)));
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+}
+
+/// Makes the command start in a new session, so it isn't tied to the app's terminal or session.
+#[cfg(unix)]
+pub(crate) fn detach(command: &mut Command) {
+  use std::os::unix::process::CommandExt;
+
+  extern "C" {
+    fn setsid() -> i32;
+  }
+
+  // SAFETY: `setsid` is async-signal-safe, so it can be called between `fork` and `exec`.
+  unsafe {
+    command.pre_exec(|| {
+      if setsid() == -1 {
+        Err(std::io::Error::last_os_error())
+      } else {
+        Ok(())
+      }
+    });
+  }
+}
+
+/// Makes the command start in a new process group without the app's console.
+#[cfg(windows)]
+pub(crate) fn detach(command: &mut Command) {
+  use std::os::windows::process::CommandExt;
+
+  const DETACHED_PROCESS: u32 = 0x0000_0008;
+  const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+  command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Environment variables that AppImage runtimes point into the mounted image, which break
+/// programs launched from the app.
+#[cfg(target_os = "linux")]
+pub(crate) const APPIMAGE_ENV_VARS: &[&str] = &[
+  "LD_LIBRARY_PATH",
+  "LD_PRELOAD",
+  "GTK_PATH",
+  "GTK_EXE_PREFIX",
+  "GTK_DATA_PREFIX",
+  "GTK_IM_MODULE_FILE",
+  "GIO_MODULE_DIR",
+  "GDK_PIXBUF_MODULEDIR",
+  "GDK_PIXBUF_MODULE_FILE",
+  "GSETTINGS_SCHEMA_DIR",
+  "GST_PLUGIN_PATH",
+  "GST_PLUGIN_SCANNER",
+  "GST_PLUGIN_SYSTEM_PATH",
+  "GST_PLUGIN_SYSTEM_PATH_1_0",
+  "PYTHONHOME",
+  "PYTHONPATH",
+  "PERLLIB",
+  "QT_PLUGIN_PATH",
+];
+
+/// Clears the environment variables on the command, or restores them to the value saved in
+/// `APPIMAGE_ORIGINAL_<NAME>` if `lookup` finds one.
+///
+/// An empty saved value means the variable was not set originally, so it is cleared.
+pub(crate) fn sanitize_env(
+  command: &mut Command,
+  vars: &[String],
+  lookup: impl Fn(&str) -> Option<std::ffi::OsString>,
+) {
+  for name in vars {
+    match lookup(&format!("APPIMAGE_ORIGINAL_{}", name)) {
+      Some(original) if !original.is_empty() => command.env(name, original),
+      _ => command.env_remove(name),
+    };
+  }
+}
+
+/// Spawns the command and waits for it to exit.
+pub(crate) fn launch_and_wait(mut command: Command) -> std::io::Result<ExitStatus> {
+  command
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status()
+}
+
+/// Reveals the path in the Finder, selecting it.
+#[cfg(target_os = "macos")]
+pub(crate) fn reveal(path: &Path) -> std::io::Result<ShowInFolderMechanism> {
+  let mut command = Command::new("/usr/bin/open");
+  command.arg("-R").arg(path);
+  let status = launch_and_wait(command)?;
+  if status.success() {
+    Ok(ShowInFolderMechanism::Finder)
+  } else {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Other,
+      format!("`open -R` exited with {}", status),
+    ))
+  }
+}
+
+/// Reveals the path in Windows Explorer, selecting it.
+///
+/// Falls back to opening the parent folder if the path can't be passed to `explorer /select,`.
+#[cfg(windows)]
+pub(crate) fn reveal(path: &Path) -> std::io::Result<ShowInFolderMechanism> {
+  let path = path.to_string_lossy();
+  match explorer_select_args(&path) {
+    Some(args) => {
+      // explorer exits with `1` even when it succeeds, so its status can't be checked
+      Command::new("explorer").args(args).spawn()?;
+      Ok(ShowInFolderMechanism::Explorer)
+    }
+    None => {
+      let path = strip_verbatim_prefix(&path);
+      open_parent_folder(Path::new(path.as_ref()))
+    }
+  }
+}
+
+/// Reveals the path in the file manager, selecting it.
+///
+/// Falls back to opening the parent folder if no file manager implements
+/// `org.freedesktop.FileManager1`.
+#[cfg(not(any(windows, target_os = "macos")))]
+pub(crate) fn reveal(path: &Path) -> std::io::Result<ShowInFolderMechanism> {
+  if let Some(uri) = file_manager_uri(path) {
+    let mut command = Command::new("dbus-send");
+    command
+      .arg("--session")
+      .arg("--print-reply")
+      .arg("--dest=org.freedesktop.FileManager1")
+      .arg("/org/freedesktop/FileManager1")
+      .arg("org.freedesktop.FileManager1.ShowItems")
+      .arg(format!("array:string:{}", uri))
+      .arg("string:");
+    // `--print-reply` makes dbus-send fail if there is no file manager service
+    if matches!(launch_and_wait(command), Ok(status) if status.success()) {
+      return Ok(ShowInFolderMechanism::FileManager1);
     }
   }
+  open_parent_folder(path)
+}
+
+/// Opens the folder containing the path with the system default handler.
+#[cfg(not(target_os = "macos"))]
+fn open_parent_folder(path: &Path) -> std::io::Result<ShowInFolderMechanism> {
+  ::open::that(path.parent().unwrap_or(path))?;
+  Ok(ShowInFolderMechanism::ParentFolder)
+}
+
+/// Strips the `\\?\` prefix that [`std::fs::canonicalize`] adds, which Explorer does not accept.
+#[cfg(windows)]
+fn strip_verbatim_prefix(path: &str) -> Cow<'_, str> {
+  if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+    Cow::Owned(format!(r"\\{}", unc))
+  } else {
+    Cow::Borrowed(path.strip_prefix(r"\\?\").unwrap_or(path))
+  }
+}
+
+/// The `explorer` arguments that select the path.
+///
+/// Explorer splits its command line on commas unless they are quoted, but [`Command::arg`] only
+/// quotes arguments containing whitespace. Paths with a comma and no whitespace can't be passed
+/// without `raw_arg`, which is newer than our MSRV, so `None` is returned for them.
+#[cfg(windows)]
+fn explorer_select_args(path: &str) -> Option<[String; 2]> {
+  let path = strip_verbatim_prefix(path);
+  if path.contains(',') && !path.contains(&[' ', '\t'][..]) {
+    None
+  } else {
+    Some(["/select,".into(), path.into_owned()])
+  }
+}
+
+/// The `file://` URI of the path, with commas encoded so `dbus-send` doesn't split the array on
+/// them.
+#[cfg(not(any(windows, target_os = "macos")))]
+fn file_manager_uri(path: &Path) -> Option<String> {
+  url::Url::from_file_path(path)
+    .ok()
+    .map(|url| url.as_str().replace(',', "%2C"))
 }
 
 /// Opens path or URL with the program specified in `with`, or system default if `None`.
 ///
-/// The path will be matched against the shell open validation regex, defaulting to `^https?://`.
-/// A custom validation regex may be supplied in the config in `tauri > allowlist > scope > open`.
+/// The path will be matched against the shell open validation regex, or by default only allowed if
+/// it uses the `https`, `http`, `mailto` or `tel` scheme. A custom validation regex may be supplied
+/// in the config in `tauri > allowlist > scope > open`.
+///
+/// When a program is specified, it is checked for an early non-zero exit status so common failures
+/// (such as a missing handler) are returned as errors. The returned [`OpenOutcome`] describes the
+/// mechanism that was used.
 ///
 /// # Examples
 ///
@@ -109,8 +675,425 @@ pub fn open<P: AsRef<str>>(
   scope: &ShellScope,
   path: P,
   with: Option<Program>,
-) -> crate::api::Result<()> {
+) -> crate::api::Result<OpenOutcome> {
+  open_with_options(scope, path, with, OpenOptions::default())
+}
+
+/// Opens path or URL like [`open()`], with [`OpenOptions`] controlling how the program is launched.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::{api::shell::{open_with_options, OpenOptions, Program}, Manager};
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     // edit the file and continue once the editor is closed
+///     let outcome = open_with_options(
+///       &app.shell_scope(),
+///       "/home/user/notes.txt",
+///       Some(Program::Custom("gedit".into())),
+///       OpenOptions::new().wait(true),
+///     )?;
+///     println!("editor exited with {:?}", outcome.status);
+///     Ok(())
+///   });
+/// ```
+pub fn open_with_options<P: AsRef<str>>(
+  scope: &ShellScope,
+  path: P,
+  with: Option<Program>,
+  options: OpenOptions,
+) -> crate::api::Result<OpenOutcome> {
+  scope
+    .open_with_options(path.as_ref(), with, options)
+    .map_err(|err| crate::api::Error::ShellOpen(Box::new(err)))
+}
+
+/// Opens path or URL like [`open()`], without blocking the async runtime.
+///
+/// The path is validated and opened on the blocking thread pool as soon as this function is called,
+/// so multiple opens run concurrently. The returned future resolves with the outcome.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::{api::shell::open_async, Manager};
+///
+/// #[tauri::command]
+/// async fn open_docs(app: tauri::AppHandle) -> Result<(), String> {
+///   open_async(&app.shell_scope(), "https://tauri.app", None)
+///     .await
+///     .map(|_| ())
+///     .map_err(|e| e.to_string())
+/// }
+/// ```
+pub fn open_async<P: AsRef<str>>(
+  scope: &ShellScope,
+  path: P,
+  with: Option<Program>,
+) -> impl Future<Output = crate::api::Result<OpenOutcome>> {
+  open_async_from_window(scope, path, with, None)
+}
+
+/// Opens path or URL like [`open_async()`], passing the label of the requesting window to the
+/// [`crate::Builder::on_shell_open`] hook.
+pub(crate) fn open_async_from_window<P: AsRef<str>>(
+  scope: &ShellScope,
+  path: P,
+  with: Option<Program>,
+  window: Option<String>,
+) -> impl Future<Output = crate::api::Result<OpenOutcome>> {
+  let scope = scope.clone();
+  let path = path.as_ref().to_string();
+  let task = crate::async_runtime::spawn_blocking(move || {
+    scope
+      .open_from_window(&path, with, OpenOptions::default(), window.as_deref())
+      .map_err(|err| crate::api::Error::ShellOpen(Box::new(err)))
+  });
+  async move {
+    task.await.map_err(|err| {
+      crate::api::Error::ShellOpen(Box::new(crate::ShellScopeError::Io(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        err,
+      ))))
+    })?
+  }
+}
+
+/// Opens multiple paths or URLs with the program specified in `with`, or system default if `None`.
+///
+/// Every path is validated against the shell open validation regex before anything is opened, so
+/// either all paths are opened or none are. Programs that accept multiple paths, such as browsers,
+/// are launched only once.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::{api::shell::{open_all, Program}, Manager};
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     open_all(
+///       &app.shell_scope(),
+///       &["https://tauri.app", "https://github.com/tauri-apps/tauri"],
+///       Some(Program::Firefox),
+///     )?;
+///     Ok(())
+///   });
+/// ```
+pub fn open_all<P: AsRef<str>>(
+  scope: &ShellScope,
+  paths: &[P],
+  with: Option<Program>,
+) -> crate::api::Result<Vec<OpenOutcome>> {
+  let paths: Vec<&str> = paths.iter().map(AsRef::as_ref).collect();
   scope
-    .open(path.as_ref(), with)
-    .map_err(|err| crate::api::Error::Shell(format!("failed to open: {}", err)))
+    .open_all(&paths, with)
+    .map_err(|err| crate::api::Error::ShellOpen(Box::new(err)))
+}
+
+/// Reveals the local path in the platform file manager, selecting it.
+///
+/// The path, an absolute path or a `file://` URL, is validated like a local [`open()`] target and
+/// must exist. Uses `explorer /select,` on Windows, `open -R` on macOS, and the
+/// `org.freedesktop.FileManager1` DBus interface elsewhere. If the item can't be selected, its
+/// parent folder is opened instead. The returned [`ShowInFolderMechanism`] tells which was used.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::{api::shell::show_in_folder, Manager};
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     show_in_folder(&app.shell_scope(), "/home/user/Downloads/report.pdf")?;
+///     Ok(())
+///   });
+/// ```
+pub fn show_in_folder<P: AsRef<str>>(
+  scope: &ShellScope,
+  path: P,
+) -> crate::api::Result<ShowInFolderMechanism> {
+  scope
+    .show_in_folder(path.as_ref())
+    .map_err(|err| crate::api::Error::ShellOpen(Box::new(err)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Program;
+  #[cfg(unix)]
+  use super::{detach, launch, launch_and_wait};
+  use super::{escape_cmd_arg, sanitize_env};
+  use std::str::FromStr;
+  #[cfg(unix)]
+  use std::{
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+  };
+
+  /// Program inputs and the names they resolve to on macOS, Windows and Linux respectively.
+  const PROGRAMS: &[(&str, [&str; 3])] = &[
+    ("open", ["open", "open", "open"]),
+    ("start", ["start", "start", "start"]),
+    ("xdg-open", ["xdg-open", "xdg-open", "xdg-open"]),
+    ("gio", ["gio", "gio", "gio"]),
+    ("gnome-open", ["gnome-open", "gnome-open", "gnome-open"]),
+    ("kde-open", ["kde-open", "kde-open", "kde-open"]),
+    ("wslview", ["wslview", "wslview", "wslview"]),
+    ("firefox", ["Firefox", "firefox", "firefox"]),
+    (
+      "chrome",
+      ["Google Chrome", "google-chrome", "google-chrome"],
+    ),
+    (
+      "Google Chrome",
+      ["Google Chrome", "google-chrome", "google-chrome"],
+    ),
+    ("chromium", ["Chromium", "chromium", "chromium"]),
+    ("safari", ["Safari", "safari", "safari"]),
+    ("brave", ["Brave Browser", "brave", "brave-browser"]),
+    ("Brave Browser", ["Brave Browser", "brave", "brave-browser"]),
+    ("brave-browser", ["Brave Browser", "brave", "brave-browser"]),
+    ("edge", ["Microsoft Edge", "msedge", "microsoft-edge"]),
+    ("msedge", ["Microsoft Edge", "msedge", "microsoft-edge"]),
+    (
+      "Microsoft Edge",
+      ["Microsoft Edge", "msedge", "microsoft-edge"],
+    ),
+    (
+      "microsoft-edge",
+      ["Microsoft Edge", "msedge", "microsoft-edge"],
+    ),
+    ("opera", ["Opera", "opera", "opera"]),
+    ("epiphany", ["epiphany", "epiphany", "epiphany"]),
+    ("GNOME Web", ["epiphany", "epiphany", "epiphany"]),
+    ("librewolf", ["LibreWolf", "librewolf", "librewolf"]),
+    ("LibreWolf", ["LibreWolf", "librewolf", "librewolf"]),
+  ];
+
+  #[test]
+  fn program_names() {
+    #[cfg(target_os = "macos")]
+    let platform = 0;
+    #[cfg(windows)]
+    let platform = 1;
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let platform = 2;
+
+    for (input, names) in PROGRAMS {
+      let program = Program::from_str(input).unwrap();
+      assert!(
+        !matches!(program, Program::Custom(_)),
+        "{} parsed as a custom program",
+        input
+      );
+      assert_eq!(program.name(), names[platform], "resolving {}", input);
+    }
+  }
+
+  #[test]
+  fn unknown_program_names() {
+    for input in ["", "firefx", "code", "/usr/bin/firefox"] {
+      assert!(
+        matches!(Program::from_str(input), Err(crate::api::Error::UnknownProgramName(name)) if name == input),
+        "{} parsed as a program",
+        input
+      );
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn launch_detects_non_zero_exit() {
+    let err = launch(Command::new("false")).unwrap_err();
+    assert!(err.to_string().starts_with("`false` exited with"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn launch_finished_program() {
+    assert!(launch(Command::new("true")).unwrap().is_none());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn launch_running_program() {
+    let mut command = Command::new("sleep");
+    command.arg("5");
+    let program = launch(command)
+      .unwrap()
+      .expect("program exited before the exit status check finished");
+    let pid = program.pid();
+    assert!(program.try_wait().unwrap().is_none());
+    program.kill().unwrap();
+    // reaped once killed
+    let started = Instant::now();
+    while is_process(pid) {
+      assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "the program was not reaped"
+      );
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(program.try_wait().unwrap().is_some());
+    // the handle knows the program exited, so the id isn't signaled again
+    program.kill().unwrap();
+  }
+
+  /// Whether the process exists, zombies included.
+  #[cfg(unix)]
+  fn is_process(pid: u32) -> bool {
+    Command::new("kill")
+      .args(["-0", &pid.to_string()])
+      .stderr(Stdio::null())
+      .status()
+      .unwrap()
+      .success()
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn launch_and_wait_returns_status() {
+    let mut command = Command::new("sh");
+    command.args(["-c", "sleep 0.3; exit 3"]);
+    let status = launch_and_wait(command).unwrap();
+    assert_eq!(status.code(), Some(3));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn detach_starts_new_session() {
+    // the process is a session leader when its session id equals its pid
+    let script = r#"read -r pid _ _ _ _ sid _ < /proc/self/stat; [ "$pid" = "$sid" ]"#;
+
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    assert!(!launch_and_wait(command).unwrap().success());
+
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    detach(&mut command);
+    assert!(launch_and_wait(command).unwrap().success());
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn explorer_select_quoting() {
+    use super::explorer_select_args;
+
+    assert_eq!(
+      explorer_select_args(r"\\?\C:\Users\tauri\file.txt").unwrap()[1],
+      r"C:\Users\tauri\file.txt"
+    );
+    assert_eq!(
+      explorer_select_args(r"\\?\UNC\server\share\file.txt").unwrap()[1],
+      r"\\server\share\file.txt"
+    );
+    // `Command::arg` quotes these, which keeps the commas inside a single explorer argument
+    assert_eq!(
+      explorer_select_args(r"C:\My Files\a, b.txt").unwrap(),
+      ["/select,".to_string(), r"C:\My Files\a, b.txt".to_string()]
+    );
+    assert!(explorer_select_args(r"C:\files\a,b.txt").is_none());
+  }
+
+  #[cfg(not(any(windows, target_os = "macos")))]
+  #[test]
+  fn file_manager_uri_encoding() {
+    use super::file_manager_uri;
+    use std::path::Path;
+
+    assert_eq!(
+      file_manager_uri(Path::new("/home/tauri/my file, v2.txt")).unwrap(),
+      "file:///home/tauri/my%20file%2C%20v2.txt"
+    );
+  }
+
+  #[test]
+  fn sanitize_env_restores_or_clears() {
+    use std::{collections::HashMap, ffi::OsStr, process::Command};
+
+    let mut command = Command::new("firefox");
+    sanitize_env(
+      &mut command,
+      &[
+        "LD_LIBRARY_PATH".into(),
+        "GTK_PATH".into(),
+        "GIO_MODULE_DIR".into(),
+      ],
+      |name| match name {
+        "APPIMAGE_ORIGINAL_LD_LIBRARY_PATH" => Some("/usr/local/lib".into()),
+        "APPIMAGE_ORIGINAL_GIO_MODULE_DIR" => Some("".into()),
+        _ => None,
+      },
+    );
+    let envs: HashMap<&OsStr, Option<&OsStr>> = command.get_envs().collect();
+    assert_eq!(envs.len(), 3);
+    assert_eq!(
+      envs[OsStr::new("LD_LIBRARY_PATH")],
+      Some(OsStr::new("/usr/local/lib"))
+    );
+    assert_eq!(envs[OsStr::new("GTK_PATH")], None);
+    assert_eq!(envs[OsStr::new("GIO_MODULE_DIR")], None);
+  }
+
+  #[test]
+  fn sanitize_env_nothing_to_do() {
+    let mut command = std::process::Command::new("firefox");
+    sanitize_env(&mut command, &[], |_| None);
+    assert_eq!(command.get_envs().count(), 0);
+  }
+
+  #[test]
+  fn cmd_arg_escaping() {
+    for (arg, escaped) in [
+      (
+        "https://tauri.app/?state=a&code=b",
+        "https://tauri.app/?state=a^&code=b",
+      ),
+      (
+        "https://tauri.app/?q=%PATH%^x",
+        "https://tauri.app/?q=^%PATH^%^^x",
+      ),
+      ("https://tauri.app/(a|b)", "https://tauri.app/^(a^|b^)"),
+      ("https://tauri.app/ünïcødé", "https://tauri.app/ünïcødé"),
+      (
+        r"C:\Program Files\Tauri & Co\a.txt",
+        r"C:\Program Files\Tauri & Co\a.txt",
+      ),
+      (
+        r"C:\Users\tauri\100% done.txt",
+        r"C:\Users\tauri\100% done.txt",
+      ),
+      ("", ""),
+    ] {
+      assert_eq!(escape_cmd_arg(arg).unwrap(), escaped);
+    }
+
+    for arg in [
+      "https://tauri.app/\"&calc",
+      "https://tauri.app/\r\ncalc",
+      r"C:\Users\%USERNAME% files\a.txt",
+    ] {
+      assert!(escape_cmd_arg(arg).is_err(), "{} should be rejected", arg);
+    }
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn start_command_line() {
+    let mut command = super::program_command("start", false).unwrap();
+    command.arg(
+      super::program_arg("https://tauri.app/?a=1&b=%20")
+        .unwrap()
+        .as_ref(),
+    );
+    let args: Vec<_> = command.get_args().collect();
+    assert_eq!(command.get_program(), "cmd");
+    assert_eq!(args, ["/c", "start", "", "https://tauri.app/?a=1^&b=^%20"]);
+
+    let command = super::program_command("my&app", true).unwrap();
+    let args: Vec<_> = command.get_args().collect();
+    assert_eq!(args, ["/c", "start", "/wait", "", "my^&app"]);
+    assert!(super::program_command("my\"app", false).is_err());
+  }
 }
@@ -184,6 +184,18 @@ pub enum RunEvent {
   #[cfg(updater)]
   #[cfg_attr(doc_cfg, doc(cfg(feature = "updater")))]
   Updater(crate::UpdaterEvent),
+  /// The installed Flatpak was updated while the app is running, see [`Builder::watch_flatpak_updates`].
+  ///
+  /// The running instance keeps using the files of the old commit, so the app should be restarted.
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  #[non_exhaustive]
+  UpdatedWhileRunning {
+    /// The commit the running instance was started from.
+    old_commit: String,
+    /// The commit that is deployed now.
+    new_commit: String,
+  },
 }
 
 impl From<EventLoopMessage> for RunEvent {
@@ -191,6 +203,14 @@ impl From<EventLoopMessage> for RunEvent {
     match event {
       #[cfg(updater)]
       EventLoopMessage::Updater(event) => RunEvent::Updater(event),
+      #[cfg(target_os = "linux")]
+      EventLoopMessage::UpdatedWhileRunning {
+        old_commit,
+        new_commit,
+      } => RunEvent::UpdatedWhileRunning {
+        old_commit,
+        new_commit,
+      },
     }
   }
 }
@@ -327,6 +347,30 @@ impl PathResolver {
     crate::api::path::app_log_dir(&self.config)
   }
 
+  /// Returns the directory Flatpak keeps the app's files in, `~/.var/app/<id>`, as the host sees it.
+  ///
+  /// Returns `None` when the app is not running inside a Flatpak sandbox.
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  pub fn app_dir_host_view(&self) -> Option<PathBuf> {
+    let flatpak = self.env.flatpak.as_ref()?;
+    crate::api::path::home_dir().map(|home| flatpak.host_app_dir(&home))
+  }
+
+  /// Whether the path only exists inside the Flatpak sandbox, like the resource directory under
+  /// `/app`, so it shouldn't be shown to the user or written to files read on the host.
+  ///
+  /// Always `false` when the app is not running inside a Flatpak sandbox.
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  pub fn is_sandboxed_path<P: AsRef<Path>>(&self, path: P) -> bool {
+    self
+      .env
+      .flatpak
+      .as_ref()
+      .map_or(false, |flatpak| flatpak.is_sandboxed_path(path.as_ref()))
+  }
+
   /// Returns the path to the suggested directory for your app's config files.
   #[deprecated(
     since = "1.2.0",
@@ -551,6 +595,10 @@ impl<R: Runtime> AppHandle<R> {
         let _ = tray.destroy();
       }
     }
+    #[cfg(target_os = "linux")]
+    if let Some(watcher) = self.try_state::<crate::flatpak::DeploymentWatcher>() {
+      watcher.stop();
+    }
   }
 }
 
@@ -569,6 +617,32 @@ impl<R: Runtime> ManagerBase<R> for AppHandle<R> {
   }
 }
 
+/// The bundle identifier configured in `tauri.conf.json` differs from the id of the Flatpak the app runs in.
+///
+/// The desktop entry, D-Bus names and `~/.var/app` directory all follow the Flatpak id,
+/// so features depending on the bundle identifier such as single instance checks and notifications break.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierMismatch {
+  /// The `tauri > bundle > identifier` config value.
+  pub identifier: String,
+  /// The application id read from `/.flatpak-info`.
+  pub flatpak_id: String,
+}
+
+#[cfg(target_os = "linux")]
+fn identifier_mismatch(
+  identifier: &str,
+  flatpak: Option<&tauri_utils::FlatpakInfo>,
+) -> Option<IdentifierMismatch> {
+  flatpak
+    .filter(|info| info.id != identifier)
+    .map(|info| IdentifierMismatch {
+      identifier: identifier.into(),
+      flatpak_id: info.id.clone(),
+    })
+}
+
 /// The instance of the currently running application.
 ///
 /// This type implements [`Manager`] which allows for manipulation of global application items.
@@ -709,6 +783,28 @@ macro_rules! shared_app_impl {
           .get_tray(id)
       }
 
+      /// The sandbox and XDG desktop portals the app runs with, to adapt its desktop integration.
+      ///
+      /// The portals are probed on the session bus the first time this is called, which takes at
+      /// most a few seconds when the bus doesn't answer; the result is cached for the app.
+      /// On other platforms than Linux, [`PortalState::supported`](crate::PortalState::supported) is `false`.
+      ///
+      /// # Examples
+      /// ```
+      /// tauri::Builder::default()
+      ///   .setup(|app| {
+      ///     if !app.portal_state().file_chooser {
+      ///       println!("dialogs fall back to GTK");
+      ///     }
+      ///     Ok(())
+      ///   });
+      /// ```
+      pub fn portal_state(&self) -> crate::PortalState {
+        self
+          .state::<crate::portal::PortalProbe>()
+          .state(&self.state::<Env>())
+      }
+
       /// The path resolver for the application.
       pub fn path_resolver(&self) -> PathResolver {
         PathResolver {
@@ -805,6 +901,18 @@ impl<R: Runtime> App<R> {
       .set_activation_policy(activation_policy);
   }
 
+  /// Checks whether the app runs in a Flatpak whose application id differs from the configured bundle identifier.
+  ///
+  /// Returns `None` outside a Flatpak sandbox or when both values match.
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  pub fn identifier_mismatch(&self) -> Option<IdentifierMismatch> {
+    identifier_mismatch(
+      &self.config().tauri.bundle.identifier,
+      self.env().flatpak.as_deref(),
+    )
+  }
+
   /// Gets the argument matches of the CLI definition configured in `tauri.conf.json`.
   ///
   /// # Examples
@@ -912,7 +1020,13 @@ impl<R: Runtime> App<R> {
         #[cfg(not(target_os = "linux"))]
         let updater_enabled = true;
         #[cfg(target_os = "linux")]
-        let updater_enabled = cfg!(dev) || self.state::<Env>().appimage.is_some();
+        let updater_enabled = {
+          let env = self.state::<Env>();
+          cfg!(dev)
+            || env.appimage_info.is_some()
+            || (updater_config.source == crate::utils::config::UpdaterSource::Flatpak
+              && env.flatpak.is_some())
+        };
         if updater_enabled {
           // if updater dialog is enabled spawn a new task
           self.run_updater_dialog();
@@ -1008,6 +1122,26 @@ pub struct Builder<R: Runtime> {
   /// The updater configuration.
   #[cfg(updater)]
   updater_settings: UpdaterSettings,
+
+  /// The hook that installs updates where the updater can't replace the app.
+  #[cfg(updater)]
+  updater_install_hook: Option<Box<crate::updater::InstallHook<R>>>,
+
+  /// The hook that observes and vetoes `shell > open` requests.
+  #[cfg(feature = "shell-open-api")]
+  on_shell_open: Option<Arc<crate::api::shell::OnShellOpen>>,
+
+  /// How often to check whether the installed Flatpak was updated.
+  #[cfg(target_os = "linux")]
+  flatpak_update_interval: Option<std::time::Duration>,
+
+  /// The legacy directories to migrate into the Flatpak sandbox on the first launch.
+  #[cfg(target_os = "linux")]
+  legacy_data: Option<crate::api::path::LegacyDataOptions>,
+
+  /// The fakes of the desktop session the app uses in tests.
+  #[cfg(any(test, feature = "test"))]
+  mocks: crate::test::Mocks,
 }
 
 impl<R: Runtime> Builder<R> {
@@ -1036,9 +1170,67 @@ impl<R: Runtime> Builder<R> {
       system_tray_event_listeners: Vec::new(),
       #[cfg(updater)]
       updater_settings: Default::default(),
+      #[cfg(updater)]
+      updater_install_hook: None,
+      #[cfg(feature = "shell-open-api")]
+      on_shell_open: None,
+      #[cfg(target_os = "linux")]
+      flatpak_update_interval: None,
+      #[cfg(target_os = "linux")]
+      legacy_data: None,
+      #[cfg(any(test, feature = "test"))]
+      mocks: Default::default(),
     }
   }
 
+  /// Probes the mock portal instead of the session bus for [`App::portal_state`].
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use tauri::test::{mock_context, noop_assets, MockPortal, MockRuntime};
+  ///
+  /// let portal = MockPortal::new().interface("org.freedesktop.portal.FileChooser", 3);
+  /// let app = tauri::Builder::<MockRuntime>::new()
+  ///   .mock_portal(portal)
+  ///   .build(mock_context(noop_assets()))
+  ///   .unwrap();
+  /// # #[cfg(target_os = "linux")]
+  /// assert!(app.portal_state().file_chooser);
+  /// ```
+  #[cfg(any(test, feature = "test"))]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "test")))]
+  pub fn mock_portal(mut self, portal: crate::test::MockPortal) -> Self {
+    self.mocks.portal.replace(portal);
+    self
+  }
+
+  /// Hands the validated `shell > open` targets to the mock shell instead of the system.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use tauri::{
+  ///   api::shell::open,
+  ///   test::{mock_context, noop_assets, MockRuntime, MockShell},
+  ///   Manager,
+  /// };
+  ///
+  /// let shell = MockShell::permissive();
+  /// let app = tauri::Builder::<MockRuntime>::new()
+  ///   .mock_shell(shell.clone())
+  ///   .build(mock_context(noop_assets()))
+  ///   .unwrap();
+  /// open(&app.shell_scope(), "https://tauri.app", None).unwrap();
+  /// assert_eq!(shell.calls()[0].targets, ["https://tauri.app"]);
+  /// ```
+  #[cfg(all(any(test, feature = "test"), feature = "shell-open-api"))]
+  #[cfg_attr(doc_cfg, doc(cfg(all(feature = "test", feature = "shell-open-api"))))]
+  pub fn mock_shell(mut self, shell: crate::test::MockShell) -> Self {
+    self.mocks.shell.replace(shell);
+    self
+  }
+
   /// Builds a new Tauri application running on any thread, bypassing the main thread requirement.
   ///
   /// ## Platform-specific
@@ -1125,6 +1317,95 @@ impl<R: Runtime> Builder<R> {
     self
   }
 
+  /// Defines the hook that observes and vetoes every `shell > open` request.
+  ///
+  /// The hook runs after the scope validated the request and before anything is opened, both for
+  /// the JS `open` API and [`crate::api::shell::open`], where [`OpenRequest::window`] is `None`.
+  /// Denied requests fail with [`crate::ShellScopeError::OpenDenied`].
+  ///
+  /// [`OpenRequest::window`]: crate::api::shell::OpenRequest::window
+  ///
+  /// # Examples
+  /// ```
+  /// use tauri::api::shell::OpenDecision;
+  ///
+  /// tauri::Builder::default()
+  ///   .on_shell_open(|request| {
+  ///     println!("opening {} from {:?}", request.target, request.window);
+  ///     if request.target.starts_with("https://ads.") {
+  ///       OpenDecision::Deny { reason: "ads are blocked".into() }
+  ///     } else {
+  ///       OpenDecision::Allow
+  ///     }
+  ///   });
+  /// ```
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[must_use]
+  pub fn on_shell_open<F>(mut self, on_shell_open: F) -> Self
+  where
+    F: Fn(crate::api::shell::OpenRequest<'_>) -> crate::api::shell::OpenDecision
+      + Send
+      + Sync
+      + 'static,
+  {
+    self.on_shell_open = Some(Arc::new(on_shell_open));
+    self
+  }
+
+  /// Checks whether the installed Flatpak was updated while the app is running, every `interval`.
+  ///
+  /// Flatpak deploys updates next to the running instance, which keeps using the old files, so a
+  /// sidecar spawned later may already be the new version. When another commit gets deployed,
+  /// [`RunEvent::UpdatedWhileRunning`] and the `tauri://updated-while-running` JS event, with the
+  /// `oldCommit` and `newCommit` as payload, are emitted so the app can prompt for a restart.
+  ///
+  /// Does nothing outside a Flatpak sandbox. The deployed commit is read from the Flatpak
+  /// installation when it's visible in the sandbox, otherwise from the host `flatpak` command, which
+  /// requires the `--talk-name=org.freedesktop.Flatpak` permission.
+  ///
+  /// # Examples
+  /// ```
+  /// tauri::Builder::default()
+  ///   .watch_flatpak_updates(std::time::Duration::from_secs(60));
+  /// ```
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  #[must_use]
+  pub fn watch_flatpak_updates(mut self, interval: std::time::Duration) -> Self {
+    self.flatpak_update_interval.replace(interval);
+    self
+  }
+
+  /// Copies the app's data from the legacy XDG directories into the Flatpak sandbox on the first
+  /// launch, before the setup hook runs. See [`crate::api::path::migrate_legacy_data`].
+  ///
+  /// The [`MigrationReport`](crate::api::path::MigrationReport) is available as managed state.
+  /// Does nothing outside a Flatpak sandbox.
+  ///
+  /// # Examples
+  /// ```
+  /// use tauri::{api::path::{LegacyDataOptions, MigrationReport, MigrationStatus}, Manager};
+  ///
+  /// tauri::Builder::default()
+  ///   .migrate_legacy_data(LegacyDataOptions::new().config_dir("com.tauri.dev"))
+  ///   .setup(|app| {
+  ///     if let Some(report) = app.try_state::<MigrationReport>() {
+  ///       if report.status == MigrationStatus::Migrated {
+  ///         println!("imported {} files", report.copied_files);
+  ///       }
+  ///     }
+  ///     Ok(())
+  ///   });
+  /// ```
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  #[must_use]
+  pub fn migrate_legacy_data(mut self, options: crate::api::path::LegacyDataOptions) -> Self {
+    self.legacy_data.replace(options);
+    self
+  }
+
   /// Adds a Tauri application plugin.
   ///
   /// A plugin is created using the [`crate::plugin::Builder`] struct.Check its documentation for more information.
@@ -1486,6 +1767,33 @@ impl<R: Runtime> Builder<R> {
     self
   }
 
+  /// Sets the hook that installs updates where the updater can't replace the app, inside a Flatpak
+  /// sandbox.
+  ///
+  /// The hook only runs when `tauri > updater > customInstall` is enabled, otherwise installing
+  /// the update fails with [`crate::updater::Error::UnsupportedEnvironment`].
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// tauri::Builder::default()
+  ///   .on_updater_install(|_app, update| {
+  ///     println!("run `flatpak update` to install {}", update.version);
+  ///     Ok(())
+  ///   });
+  /// ```
+  #[cfg(updater)]
+  pub fn on_updater_install<F>(mut self, hook: F) -> Self
+  where
+    F: Fn(&AppHandle<R>, crate::updater::UpdateInstall) -> crate::updater::Result<()>
+      + Send
+      + Sync
+      + 'static,
+  {
+    self.updater_install_hook.replace(Box::new(hook));
+    self
+  }
+
   /// Builds the application.
   #[allow(clippy::type_complexity)]
   pub fn build<A: Assets>(mut self, context: Context<A>) -> crate::Result<App<R>> {
@@ -1496,6 +1804,11 @@ impl<R: Runtime> Builder<R> {
 
     #[cfg(shell_scope)]
     let shell_scope = context.shell_scope.clone();
+    #[cfg(all(any(test, feature = "test"), feature = "shell-open-api"))]
+    let shell_scope = match &self.mocks.shell {
+      Some(shell) => shell.scope_config(shell_scope),
+      None => shell_scope,
+    };
 
     let manager = WindowManager::with_handlers(
       context,
@@ -1567,26 +1880,75 @@ impl<R: Runtime> Builder<R> {
     };
 
     let env = Env::default();
+    let fs_scope = FsScope::for_fs_api(
+      &app.manager.config(),
+      app.package_info(),
+      &env,
+      &app.config().tauri.allowlist.fs.scope,
+    )?;
+    #[cfg(shell_scope)]
+    let shell_scope = ShellScope::new(
+      &app.manager.config(),
+      app.package_info(),
+      &env,
+      shell_scope,
+      fs_scope.clone(),
+    );
+    #[cfg(feature = "shell-open-api")]
+    let shell_scope = shell_scope.with_open_hook(self.on_shell_open);
+    #[cfg(all(any(test, feature = "test"), feature = "shell-open-api"))]
+    let shell_scope = match self.mocks.shell {
+      Some(shell) => shell_scope.with_opener(Arc::new(shell)),
+      None => shell_scope,
+    };
+    #[cfg(protocol_asset)]
+    let asset_protocol_scope = FsScope::for_fs_api(
+      &app.manager.config(),
+      app.package_info(),
+      &env,
+      &app.config().tauri.allowlist.protocol.asset_scope,
+    )?;
+    // `convertFileSrc` of a resolved resource points into the `/app` deployment inside a Flatpak,
+    // which is read-only and only holds files bundled with the app
+    #[cfg(all(protocol_asset, target_os = "linux"))]
+    if env.flatpak.is_some() {
+      if let Ok(resource_dir) = crate::utils::platform::resource_dir(app.package_info(), &env) {
+        asset_protocol_scope.allow_directory(resource_dir, true)?;
+      }
+    }
     app.manage(Scopes {
-      fs: FsScope::for_fs_api(
-        &app.manager.config(),
-        app.package_info(),
-        &env,
-        &app.config().tauri.allowlist.fs.scope,
-      )?,
+      #[cfg(shell_scope)]
+      shell: shell_scope,
+      fs: fs_scope,
       #[cfg(protocol_asset)]
-      asset_protocol: FsScope::for_fs_api(
-        &app.manager.config(),
-        app.package_info(),
-        &env,
-        &app.config().tauri.allowlist.protocol.asset_scope,
-      )?,
+      asset_protocol: asset_protocol_scope,
       #[cfg(http_request)]
       http: crate::scope::HttpScope::for_http_api(&app.config().tauri.allowlist.http.scope),
-      #[cfg(shell_scope)]
-      shell: ShellScope::new(&app.manager.config(), app.package_info(), &env, shell_scope),
     });
     app.manage(env);
+    #[cfg(updater)]
+    if let Some(hook) = self.updater_install_hook {
+      app.manage(crate::updater::UpdaterInstallHook(hook));
+    }
+    #[cfg(any(test, feature = "test"))]
+    let portal_bus = self
+      .mocks
+      .portal
+      .map(|portal| {
+        std::sync::Arc::new(portal) as std::sync::Arc<dyn crate::portal::PortalBus>
+      });
+    #[cfg(not(any(test, feature = "test")))]
+    let portal_bus = None;
+    app.manage(crate::portal::PortalProbe::new(portal_bus));
+
+    // printed on release builds too since this is a packaging mistake only visible in the final Flatpak
+    #[cfg(target_os = "linux")]
+    if let Some(mismatch) = app.identifier_mismatch() {
+      eprintln!(
+        "WARNING: the bundle identifier `{}` does not match the Flatpak application id `{}`; single instance checks, notifications and app directories will not work as expected. Set `tauri > bundle > identifier` to `{}`.",
+        mismatch.identifier, mismatch.flatpak_id, mismatch.flatpak_id
+      );
+    }
 
     #[cfg(windows)]
     {
@@ -1653,8 +2015,27 @@ impl<R: Runtime> Builder<R> {
       let _window = app.manager.attach_window(app.handle(), detached);
     }
 
+    #[cfg(target_os = "linux")]
+    if let Some(options) = &self.legacy_data {
+      match crate::api::path::migrate_legacy_data(&app.env(), options) {
+        Ok(report) => {
+          app.manage(report);
+        }
+        // printed on release builds too, the user's data stays in the legacy directories
+        Err(e) => eprintln!(
+          "failed to migrate the legacy data into the Flatpak sandbox: {}",
+          e
+        ),
+      }
+    }
+
     (self.setup)(&mut app).map_err(|e| crate::Error::Setup(e.into()))?;
 
+    #[cfg(target_os = "linux")]
+    if let Some(interval) = self.flatpak_update_interval {
+      crate::flatpak::watch_updates(&app.handle(), interval);
+    }
+
     #[cfg(updater)]
     app.run_updater();
 
@@ -1769,4 +2150,26 @@ mod tests {
     crate::test_utils::assert_send::<super::PathResolver>();
     crate::test_utils::assert_sync::<super::PathResolver>();
   }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn identifier_mismatch() {
+    use super::{identifier_mismatch, IdentifierMismatch};
+    use tauri_utils::FlatpakInfo;
+
+    let info = FlatpakInfo::parse("[Application]\nname=io.github.tauri.App\n").unwrap();
+
+    assert_eq!(identifier_mismatch("com.tauri.dev", None), None);
+    assert_eq!(
+      identifier_mismatch("io.github.tauri.App", Some(&info)),
+      None
+    );
+    assert_eq!(
+      identifier_mismatch("com.tauri.dev", Some(&info)),
+      Some(IdentifierMismatch {
+        identifier: "com.tauri.dev".into(),
+        flatpak_id: "io.github.tauri.App".into(),
+      })
+    );
+  }
 }
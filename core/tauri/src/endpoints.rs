@@ -156,8 +156,9 @@ impl Module {
       Self::Shell(cmd) => resolver.respond_async(async move {
         cmd
           .run(context)
+          .await
           .and_then(|r| r.json)
-          .map_err(InvokeError::from_anyhow)
+          .map_err(shell::into_invoke_error)
       }),
       Self::Event(cmd) => resolver.respond_async(async move {
         cmd
@@ -25,6 +25,9 @@ pub enum Cmd {
   /// Hides the application on macOS.
   #[cmd(app_hide, "app > hide")]
   Hide,
+  /// Gets the sandbox and XDG desktop portals the app runs with.
+  #[cmd(app_portal_state, "app > portalState")]
+  PortalState,
 }
 
 impl Cmd {
@@ -55,4 +58,45 @@ impl Cmd {
     context.window.app_handle.hide()?;
     Ok(())
   }
+
+  #[module_command_handler(app_portal_state)]
+  fn portal_state<R: Runtime>(context: InvokeContext<R>) -> super::Result<crate::PortalState> {
+    Ok(context.window.app_handle.portal_state())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    test::{mock_context, noop_assets, MockPortal, MockRuntime},
+    Builder, Manager,
+  };
+
+  #[test]
+  fn portal_state_allowlist() {
+    let portal = MockPortal::new().interface("org.freedesktop.portal.FileChooser", 3);
+    let app = Builder::<MockRuntime>::new()
+      .mock_portal(portal.clone())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    let context = crate::endpoints::InvokeContext {
+      window: app.get_window("main").unwrap(),
+      config: app.config(),
+      package_info: app.package_info().clone(),
+    };
+
+    let cmd = serde_json::from_str::<super::Cmd>(r#"{ "cmd": "portalState" }"#);
+    #[cfg(app_portal_state)]
+    {
+      assert!(cmd.unwrap().run(context).is_ok());
+      assert_eq!(portal.calls()[0], crate::test::PortalCall::Introspect);
+    }
+    #[cfg(not(app_portal_state))]
+    {
+      let _ = context;
+      assert!(cmd.unwrap_err().to_string().contains("app > portalState"));
+      // rejected before the portal is probed
+      assert!(portal.calls().is_empty());
+    }
+  }
 }
@@ -59,10 +59,27 @@ pub enum Cmd {
 impl Cmd {
   #[module_command_handler(http_request)]
   async fn create_client<R: Runtime>(
-    _context: InvokeContext<R>,
+    context: InvokeContext<R>,
     options: Option<ClientBuilder>,
   ) -> super::Result<ClientId> {
-    let client = options.unwrap_or_default().build()?;
+    let mut builder = options.unwrap_or_default();
+    if let Some(proxy) = &context.config.tauri.allowlist.http.proxy {
+      builder = builder.proxy(proxy.clone());
+    }
+    // the proxy settings of the desktop are only visible to the sandbox through the portal
+    #[cfg(target_os = "linux")]
+    {
+      use crate::Manager;
+      if context.window.state::<crate::Env>().flatpak.is_some() {
+        builder = builder.proxy_resolver(
+          context
+            .window
+            .state::<crate::portal::PortalProbe>()
+            .proxy_resolver(),
+        );
+      }
+    }
+    let client = builder.build()?;
     let mut store = clients().lock().unwrap();
     let id = rand::random::<ClientId>();
     store.insert(id, client);
@@ -5,7 +5,7 @@
 #![allow(unused_imports)]
 
 use super::InvokeContext;
-use crate::{api::ipc::CallbackFn, Runtime};
+use crate::{api::ipc::CallbackFn, InvokeError, Runtime};
 #[cfg(shell_scope)]
 use crate::{Manager, Scopes};
 use serde::Deserialize;
@@ -16,6 +16,11 @@ use crate::ExecuteArgs;
 #[cfg(not(shell_scope))]
 type ExecuteArgs = ();
 
+#[cfg(shell_open)]
+use crate::api::shell::Program;
+#[cfg(not(shell_open))]
+type Program = String;
+
 #[cfg(any(shell_execute, shell_sidecar))]
 use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, path::PathBuf};
@@ -38,6 +43,76 @@ pub enum Buffer {
   Raw(Vec<u8>),
 }
 
+/// The program the frontend requests to open a path with.
+///
+/// Either the name of a known program, e.g. `"firefox"`, or `{ "custom": "<name>" }` for a program
+/// listed in `tauri > allowlist > shell > open > customPrograms`. Unknown program names and custom
+/// names that can't name a program, like flags or paths, are rejected when deserializing the
+/// command, before the `shell > open > with` policy is consulted.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum OpenWith {
+  /// One of the known programs.
+  Program(Program),
+  /// A custom program, by name.
+  Custom(String),
+}
+
+impl<'de> Deserialize<'de> for OpenWith {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(OpenWithVisitor)
+  }
+}
+
+struct OpenWithVisitor;
+
+impl<'de> serde::de::Visitor<'de> for OpenWithVisitor {
+  type Value = OpenWith;
+
+  fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    formatter.write_str(r#"a program name or `{ "custom": "<name>" }`"#)
+  }
+
+  fn visit_str<E: serde::de::Error>(self, with: &str) -> Result<Self::Value, E> {
+    with.parse().map(OpenWith::Program).map_err(|_| {
+      E::custom(format!(
+        r#"invalid `with` value {:?}: unknown program, use `{{ "custom": {:?} }}` for a custom one"#,
+        with, with
+      ))
+    })
+  }
+
+  fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Custom {
+      custom: String,
+    }
+
+    let Custom { custom } = Custom::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+    let reason = if custom.trim().is_empty() {
+      Some("it is empty")
+    } else if custom.trim() != custom {
+      Some("it has leading or trailing whitespace")
+    } else if custom.starts_with('-') {
+      Some("it starts with `-`")
+    } else if custom.contains(&['/', '\\'][..]) {
+      Some("it is a path")
+    } else if custom.chars().any(char::is_control) {
+      Some("it contains control characters")
+    } else {
+      None
+    };
+    match reason {
+      Some(reason) => Err(serde::de::Error::custom(format!(
+        "invalid custom `with` program {:?}: {}",
+        custom, reason
+      ))),
+      None => Ok(OpenWith::Custom(custom)),
+    }
+  }
+}
+
 #[allow(clippy::unnecessary_wraps)]
 fn default_env() -> Option<HashMap<String, String>> {
   Some(HashMap::default())
@@ -61,6 +136,7 @@ pub struct CommandOptions {
 /// The API descriptor.
 #[command_enum]
 #[derive(Deserialize, CommandModule)]
+#[cmd(async)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// The execute script API.
@@ -78,13 +154,21 @@ pub enum Cmd {
   #[cmd(shell_script, "shell > execute or shell > sidecar")]
   KillChild { pid: ChildId },
   #[cmd(shell_open, "shell > open")]
-  Open { path: String, with: Option<String> },
+  Open {
+    path: String,
+    with: Option<OpenWith>,
+  },
+  #[cmd(shell_open, "shell > open")]
+  IsOpenAllowed {
+    path: String,
+    with: Option<OpenWith>,
+  },
 }
 
 impl Cmd {
   #[module_command_handler(shell_script)]
   #[allow(unused_variables)]
-  fn execute<R: Runtime>(
+  async fn execute<R: Runtime>(
     context: InvokeContext<R>,
     program: String,
     args: ExecuteArgs,
@@ -179,7 +263,7 @@ impl Cmd {
   }
 
   #[module_command_handler(shell_script)]
-  fn stdin_write<R: Runtime>(
+  async fn stdin_write<R: Runtime>(
     _context: InvokeContext<R>,
     pid: ChildId,
     buffer: Buffer,
@@ -194,7 +278,7 @@ impl Cmd {
   }
 
   #[module_command_handler(shell_script)]
-  fn kill_child<R: Runtime>(_context: InvokeContext<R>, pid: ChildId) -> super::Result<()> {
+  async fn kill_child<R: Runtime>(_context: InvokeContext<R>, pid: ChildId) -> super::Result<()> {
     if let Some(child) = command_child_store().lock().unwrap().remove(&pid) {
       child.kill()?;
     }
@@ -205,24 +289,91 @@ impl Cmd {
   ///
   /// See [`crate::api::shell::open`] for how it handles security-related measures.
   #[module_command_handler(shell_open)]
-  fn open<R: Runtime>(
+  async fn open<R: Runtime>(
     context: InvokeContext<R>,
     path: String,
-    with: Option<String>,
+    with: Option<OpenWith>,
   ) -> super::Result<()> {
-    use std::str::FromStr;
-
-    with
-      .as_deref()
-      // only allow pre-determined programs to be specified
-      .map(crate::api::shell::Program::from_str)
-      .transpose()
-      .map_err(Into::into)
-      // validate and open path
-      .and_then(|with| {
-        crate::api::shell::open(&context.window.state::<Scopes>().shell, path, with)
-          .map_err(Into::into)
-      })
+    let with = check_open_with(&context.config, with)?;
+    // validate and open path
+    let open = crate::api::shell::open_async_from_window(
+      &context.window.state::<Scopes>().shell,
+      path,
+      with,
+      Some(context.window.label().into()),
+    );
+    let outcome = open.await?;
+    tauri_utils::debug_eprintln!("shell > open: {:?}", outcome);
+    Ok(())
+  }
+
+  /// Checks whether [`Self::open`] would accept the path and program, without opening anything.
+  #[module_command_handler(shell_open)]
+  async fn is_open_allowed<R: Runtime>(
+    context: InvokeContext<R>,
+    path: String,
+    with: Option<OpenWith>,
+  ) -> super::Result<()> {
+    let with = check_open_with(&context.config, with)?;
+    context
+      .window
+      .state::<Scopes>()
+      .shell
+      .is_allowed(&path, with.as_ref())
+      .map_err(|error| crate::api::Error::ShellOpen(Box::new(error)).into())
+  }
+}
+
+/// Converts the error into an [`InvokeError`], serializing `shell > open` scope errors with their
+/// `kind` so the frontend can tell them apart.
+pub(crate) fn into_invoke_error(error: anyhow::Error) -> InvokeError {
+  #[cfg(shell_open)]
+  if let Some(crate::api::Error::ShellOpen(error)) = error.downcast_ref::<crate::api::Error>() {
+    return InvokeError::from(&**error);
+  }
+  InvokeError::from_anyhow(error)
+}
+
+/// Ensures the program requested by the frontend is allowed by the `shell > open > with` policy
+/// and resolves it.
+#[cfg(shell_open)]
+fn check_open_with(
+  config: &crate::Config,
+  with: Option<OpenWith>,
+) -> crate::api::Result<Option<crate::api::shell::Program>> {
+  use crate::utils::config::{ShellAllowlistOpen, ShellOpenWith};
+  use std::str::FromStr;
+
+  let program = match with {
+    Some(OpenWith::Program(program)) => program,
+    // checked against `customPrograms` by the scope
+    Some(OpenWith::Custom(name)) => Program::Custom(name),
+    None => return Ok(None),
+  };
+  let policy = match &config.tauri.allowlist.shell.open {
+    ShellAllowlistOpen::Config(open) => &open.with,
+    _ => return Ok(Some(program)),
+  };
+  let allowed = match policy {
+    ShellOpenWith::Any => return Ok(Some(program)),
+    ShellOpenWith::Programs(programs) => programs.clone(),
+    _ => Vec::new(),
+  };
+  let requested = program.clone().name();
+  // the policy names custom programs like the known ones
+  if allowed
+    .iter()
+    .map(|program| Program::from_str(program).unwrap_or_else(|_| Program::Custom(program.clone())))
+    .any(|program| program.name() == requested)
+  {
+    Ok(Some(program))
+  } else {
+    Err(crate::api::Error::ShellOpen(Box::new(
+      crate::ShellScopeError::OpenWithNotAllowed {
+        program: requested.into_owned(),
+        allowed,
+      },
+    )))
   }
 }
 
@@ -277,4 +428,174 @@ mod tests {
   #[tauri_macros::module_command_test(shell_open, "shell > open")]
   #[quickcheck_macros::quickcheck]
   fn open(_path: String, _with: Option<String>) {}
+
+  #[tauri_macros::module_command_test(shell_open, "shell > open")]
+  #[quickcheck_macros::quickcheck]
+  fn is_open_allowed(_path: String, _with: Option<String>) {}
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_with_policy() {
+    use crate::utils::config::{ShellAllowlistOpen, ShellAllowlistOpenConfig, ShellOpenWith};
+
+    let open_with = |policy: ShellOpenWith, with: &str| {
+      let mut context = crate::test::mock_invoke_context();
+      let mut config = (*context.config).clone();
+      config.tauri.allowlist.shell.open = ShellAllowlistOpen::Config(ShellAllowlistOpenConfig {
+        with: policy,
+        ..Default::default()
+      });
+      context.config = std::sync::Arc::new(config);
+      crate::async_runtime::block_on(super::Cmd::open(
+        context,
+        "https://tauri.app".into(),
+        Some(super::OpenWith::Program(with.parse().unwrap())),
+      ))
+    };
+
+    let err = open_with(ShellOpenWith::Default, "firefox").unwrap_err();
+    assert!(err.to_string().contains("shell > open > with"), "{}", err);
+
+    let err = open_with(ShellOpenWith::Programs(vec!["chrome".into()]), "firefox").unwrap_err();
+    let firefox = format!("`{}`", crate::api::shell::Program::Firefox.name());
+    assert!(err.to_string().contains(&firefox), "{}", err);
+  }
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_with_policy_resolves_names() {
+    use crate::utils::config::{ShellAllowlistOpen, ShellAllowlistOpenConfig, ShellOpenWith};
+
+    let mut config = crate::Config::default();
+    config.tauri.allowlist.shell.open = ShellAllowlistOpen::Config(ShellAllowlistOpenConfig {
+      with: ShellOpenWith::Programs(vec!["Google Chrome".into(), "code".into()]),
+      ..Default::default()
+    });
+    let with = |with: &str| Some(super::OpenWith::Program(with.parse().unwrap()));
+    let custom = |name: &str| Some(super::OpenWith::Custom(name.into()));
+    assert!(super::check_open_with(&config, with("chrome")).is_ok());
+    assert!(super::check_open_with(&config, None).is_ok());
+    assert!(super::check_open_with(&config, with("firefox")).is_err());
+    assert!(super::check_open_with(&config, custom("code")).is_ok());
+    assert!(super::check_open_with(&config, custom("vim")).is_err());
+  }
+
+  #[cfg(shell_open)]
+  fn is_open_allowed_with(
+    policy: crate::utils::config::ShellOpenWith,
+    with: &str,
+  ) -> super::super::Result<()> {
+    use crate::utils::config::{ShellAllowlistOpen, ShellAllowlistOpenConfig};
+
+    let mut context = crate::test::mock_invoke_context();
+    let mut config = (*context.config).clone();
+    config.tauri.allowlist.shell.open = ShellAllowlistOpen::Config(ShellAllowlistOpenConfig {
+      with: policy,
+      ..Default::default()
+    });
+    context.config = std::sync::Arc::new(config);
+    crate::async_runtime::block_on(super::Cmd::is_open_allowed(
+      context,
+      "https://tauri.app".into(),
+      Some(super::OpenWith::Program(with.parse().unwrap())),
+    ))
+  }
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_with_allowed() {
+    use crate::utils::config::ShellOpenWith;
+
+    assert!(is_open_allowed_with(ShellOpenWith::Any, "firefox").is_ok());
+    assert!(
+      is_open_allowed_with(ShellOpenWith::Programs(vec!["firefox".into()]), "Firefox").is_ok()
+    );
+  }
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_with_denied_lists_allowed() {
+    use crate::utils::config::ShellOpenWith;
+
+    let policy = ShellOpenWith::Programs(vec!["chrome".into(), "firefox".into()]);
+    let error = is_open_allowed_with(policy, "safari").unwrap_err();
+    let error = super::into_invoke_error(error).0;
+    assert_eq!(error["kind"], "openWithNotAllowed");
+    assert_eq!(error["allowed"], serde_json::json!(["chrome", "firefox"]));
+    assert!(
+      error["message"]
+        .as_str()
+        .unwrap()
+        .contains("`chrome`, `firefox`"),
+      "{}",
+      error
+    );
+
+    let error = is_open_allowed_with(ShellOpenWith::Default, "safari").unwrap_err();
+    let error = super::into_invoke_error(error).0;
+    assert_eq!(error["kind"], "openWithNotAllowed");
+    assert_eq!(error["allowed"], serde_json::json!([]));
+  }
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_with_malformed() {
+    let open = |with: serde_json::Value| {
+      serde_json::from_value::<super::Cmd>(serde_json::json!({
+        "cmd": "open",
+        "path": "https://tauri.app",
+        "with": with,
+      }))
+    };
+
+    for with in ["", " firefox", "firefx", "code", "/usr/bin/firefox"] {
+      let error = open(with.into()).unwrap_err();
+      assert!(
+        error.to_string().contains("invalid `with` value") && error.to_string().contains("custom"),
+        "{}",
+        error
+      );
+    }
+    for custom in ["", " code", "--new-window", "/usr/bin/code", "co\nde"] {
+      let error = open(serde_json::json!({ "custom": custom })).unwrap_err();
+      assert!(
+        error.to_string().contains("invalid custom `with` program"),
+        "{}",
+        error
+      );
+    }
+    assert!(open(serde_json::json!({ "program": "code" })).is_err());
+    assert!(open(serde_json::json!(1)).is_err());
+
+    assert!(matches!(
+      open("Firefox".into()),
+      Ok(super::Cmd::Open {
+        with: Some(super::OpenWith::Program(
+          crate::api::shell::Program::Firefox
+        )),
+        ..
+      })
+    ));
+    assert!(matches!(
+      open(serde_json::json!({ "custom": "code" })),
+      Ok(super::Cmd::Open {
+        with: Some(super::OpenWith::Custom(name)),
+        ..
+      }) if name == "code"
+    ));
+  }
+
+  #[cfg(shell_open)]
+  #[test]
+  fn open_error_kind() {
+    let error = crate::api::Error::ShellOpen(Box::new(crate::ShellScopeError::PathValidation {
+      path: "javascript:alert(1)".into(),
+      pattern: "^https?://".into(),
+    }));
+    let error = super::into_invoke_error(error.into()).0;
+    assert_eq!(error["kind"], "pathValidation");
+
+    let error = super::into_invoke_error(anyhow::anyhow!("other error")).0;
+    assert_eq!(error, "other error");
+  }
 }
@@ -25,6 +25,8 @@ pub enum IconDto {
   File(std::path::PathBuf),
   #[cfg(any(feature = "icon-png", feature = "icon-ico"))]
   Raw(Vec<u8>),
+  #[cfg(feature = "icon-png")]
+  Named { name: String },
   Rgba {
     rgba: Vec<u8>,
     width: u32,
@@ -39,6 +41,8 @@ impl From<IconDto> for Icon {
       IconDto::File(path) => Self::File(path),
       #[cfg(any(feature = "icon-png", feature = "icon-ico"))]
       IconDto::Raw(raw) => Self::Raw(raw),
+      #[cfg(feature = "icon-png")]
+      IconDto::Named { name } => Self::Named(name),
       IconDto::Rgba {
         rgba,
         width,
@@ -350,3 +354,23 @@ impl Cmd {
     Ok(().into())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::IconDto;
+
+  #[cfg(feature = "icon-png")]
+  #[test]
+  fn icon_dto() {
+    let icon: IconDto = serde_json::from_str(r#"{ "name": "com.tauri.dev" }"#).unwrap();
+    assert!(matches!(icon, IconDto::Named { name } if name == "com.tauri.dev"));
+
+    let icon: IconDto = serde_json::from_str(r#""/tauri/awesome.png""#).unwrap();
+    assert!(matches!(icon, IconDto::File(_)));
+    let icon: IconDto = serde_json::from_str("[1, 2, 3]").unwrap();
+    assert!(matches!(icon, IconDto::Raw(_)));
+    let icon: IconDto =
+      serde_json::from_str(r#"{ "rgba": [0, 0, 0, 0], "width": 1, "height": 1 }"#).unwrap();
+    assert!(matches!(icon, IconDto::Rgba { .. }));
+  }
+}
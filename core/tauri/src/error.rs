@@ -68,6 +68,18 @@ pub enum Error {
   /// Failed to load window icon.
   #[error("invalid icon: {0}")]
   InvalidIcon(std::io::Error),
+  /// The name of an [`Icon::Named`](crate::Icon::Named) is not a valid icon name.
+  #[cfg(feature = "icon-png")]
+  #[error("invalid icon name `{0}`")]
+  InvalidIconName(String),
+  /// An [`Icon::Named`](crate::Icon::Named) was not found in the icon theme.
+  #[cfg(feature = "icon-png")]
+  #[error("icon `{0}` not found in the icon theme")]
+  IconNotFound(String),
+  /// [`Icon::Named`](crate::Icon::Named) is only supported on Linux.
+  #[cfg(feature = "icon-png")]
+  #[error("named icon `{0}` is only supported on Linux")]
+  NamedIconUnsupported(String),
   /// Client with specified ID not found.
   #[error("http client dropped or not initialized")]
   HttpClientNotInitialized,
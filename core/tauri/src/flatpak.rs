@@ -0,0 +1,232 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Talks to the host Flatpak installation the app runs from.
+
+use crate::{runtime::EventLoopProxy, AppHandle, EventLoopMessage, Manager, Runtime};
+
+use serde::Serialize;
+use tauri_utils::FlatpakInfo;
+
+use std::{
+  path::PathBuf,
+  sync::{
+    mpsc::{channel, RecvTimeoutError, Sender},
+    Mutex,
+  },
+  time::Duration,
+};
+
+/// The JS event emitted when the app was updated while running.
+const EVENT_UPDATED_WHILE_RUNNING: &str = "tauri://updated-while-running";
+
+/// Runs `flatpak` on the host, which requires the `--talk-name=org.freedesktop.Flatpak` permission.
+pub(crate) fn host_flatpak(args: &[&str]) -> Option<String> {
+  try_host_flatpak(args).ok()
+}
+
+/// Runs `flatpak` on the host, describing why it failed, e.g. with the stderr of `flatpak-spawn`
+/// when the permission is missing.
+pub(crate) fn try_host_flatpak(args: &[&str]) -> Result<String, String> {
+  let output = std::process::Command::new("flatpak-spawn")
+    .args(["--host", "flatpak"])
+    .args(args)
+    .output()
+    .map_err(|e| format!("failed to run flatpak-spawn: {}", e))?;
+  if output.status.success() {
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+  } else {
+    Err(format!(
+      "`flatpak {}` exited with {}: {}",
+      args.join(" "),
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ))
+  }
+}
+
+/// The payload of the `tauri://updated-while-running` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeploymentUpdate {
+  /// The commit the running instance was started from.
+  pub(crate) old_commit: String,
+  /// The commit that is deployed now.
+  pub(crate) new_commit: String,
+}
+
+/// Reads the commit currently deployed for the app.
+pub(crate) trait DeployedCommit: Send + 'static {
+  fn deployed_commit(&self) -> Option<String>;
+}
+
+/// Reads the `active` deployment link, falling back to the host `flatpak` when the installation
+/// isn't visible inside the sandbox.
+struct Deployment {
+  active_link: Option<PathBuf>,
+  app_ref: Option<String>,
+}
+
+impl DeployedCommit for Deployment {
+  fn deployed_commit(&self) -> Option<String> {
+    self
+      .active_link
+      .as_ref()
+      .and_then(|link| read_active_link(link))
+      .or_else(|| {
+        let output = host_flatpak(&["info", "--show-commit", self.app_ref.as_ref()?])?;
+        let commit = output.trim();
+        (!commit.is_empty()).then(|| commit.to_string())
+      })
+  }
+}
+
+fn read_active_link(link: &std::path::Path) -> Option<String> {
+  std::fs::read_link(link)
+    .ok()?
+    .file_name()?
+    .to_str()
+    .map(Into::into)
+}
+
+/// Polls the deployed commit until stopped, the thread exits as soon as the sender is dropped.
+pub(crate) struct DeploymentWatcher(Mutex<Option<Sender<()>>>);
+
+impl DeploymentWatcher {
+  /// Calls `on_updated` every time a commit other than `running` gets deployed.
+  pub(crate) fn start<S: DeployedCommit, F: Fn(DeploymentUpdate) + Send + 'static>(
+    running: String,
+    source: S,
+    interval: Duration,
+    on_updated: F,
+  ) -> Self {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+      let mut reported = running.clone();
+      while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(interval) {
+        match source.deployed_commit() {
+          Some(deployed) if deployed != reported => {
+            reported = deployed.clone();
+            if deployed != running {
+              on_updated(DeploymentUpdate {
+                old_commit: running.clone(),
+                new_commit: deployed,
+              });
+            }
+          }
+          _ => {}
+        }
+      }
+    });
+    Self(Mutex::new(Some(tx)))
+  }
+
+  pub(crate) fn stop(&self) {
+    self.0.lock().unwrap().take();
+  }
+}
+
+/// Starts watching for updates of the Flatpak the app runs from, see [`crate::Builder::watch_flatpak_updates`].
+///
+/// Does nothing outside a Flatpak sandbox or when the running commit isn't known.
+pub(crate) fn watch_updates<R: Runtime>(app: &AppHandle<R>, interval: Duration) {
+  let env = app.env();
+  let info: &FlatpakInfo = match &env.flatpak {
+    Some(info) => info,
+    None => return,
+  };
+  let running = match &info.app_commit {
+    Some(commit) => commit.clone(),
+    None => return,
+  };
+  let source = Deployment {
+    active_link: info.active_deployment_link(),
+    app_ref: info.app_ref(),
+  };
+  let app_ = app.clone();
+  app.manage(DeploymentWatcher::start(
+    running,
+    source,
+    interval,
+    move |update| {
+      let _ = app_.emit_all(EVENT_UPDATED_WHILE_RUNNING, update.clone());
+      let _ = app_
+        .create_proxy()
+        .send_event(EventLoopMessage::UpdatedWhileRunning {
+          old_commit: update.old_commit,
+          new_commit: update.new_commit,
+        });
+    },
+  ));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{read_active_link, DeployedCommit, DeploymentWatcher};
+  use std::{
+    os::unix::fs::symlink,
+    path::PathBuf,
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::Duration,
+  };
+
+  struct ActiveLink(PathBuf);
+
+  impl DeployedCommit for ActiveLink {
+    fn deployed_commit(&self) -> Option<String> {
+      read_active_link(&self.0)
+    }
+  }
+
+  /// Deploys the commit the way Flatpak does, replacing the `active` link.
+  fn deploy(branch_dir: &std::path::Path, commit: &str) {
+    std::fs::create_dir_all(branch_dir.join(commit).join("files")).unwrap();
+    let staged = branch_dir.join(".active-tmp");
+    symlink(commit, &staged).unwrap();
+    std::fs::rename(staged, branch_dir.join("active")).unwrap();
+  }
+
+  #[test]
+  fn detects_deployment_swap() {
+    let root = tempfile::tempdir().unwrap();
+    let branch_dir = root.path().join("app/com.tauri.dev/x86_64/stable");
+    deploy(&branch_dir, "aaa");
+
+    let (tx, rx) = channel();
+    let watcher = DeploymentWatcher::start(
+      "aaa".into(),
+      ActiveLink(branch_dir.join("active")),
+      Duration::from_millis(10),
+      move |update| tx.send(update).unwrap(),
+    );
+    assert_eq!(
+      rx.recv_timeout(Duration::from_millis(100)),
+      Err(RecvTimeoutError::Timeout)
+    );
+
+    deploy(&branch_dir, "bbb");
+    let update = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(update.old_commit, "aaa");
+    assert_eq!(update.new_commit, "bbb");
+    // reported once per deployment
+    assert_eq!(
+      rx.recv_timeout(Duration::from_millis(100)),
+      Err(RecvTimeoutError::Timeout)
+    );
+
+    // rolling back to the running commit is not an update
+    deploy(&branch_dir, "aaa");
+    assert_eq!(
+      rx.recv_timeout(Duration::from_millis(100)),
+      Err(RecvTimeoutError::Timeout)
+    );
+
+    watcher.stop();
+    // the thread drops the callback, and with it the sender, once it noticed the stop
+    assert_eq!(
+      rx.recv_timeout(Duration::from_secs(5)),
+      Err(RecvTimeoutError::Disconnected)
+    );
+  }
+}
@@ -87,7 +87,7 @@ pub struct Invoke<R: Runtime> {
 
 /// Error response from an [`InvokeMessage`].
 #[derive(Debug)]
-pub struct InvokeError(JsonValue);
+pub struct InvokeError(pub(crate) JsonValue);
 
 impl InvokeError {
   /// Create an [`InvokeError`] as a string of the [`serde_json::Error`] message.
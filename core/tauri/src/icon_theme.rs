@@ -0,0 +1,212 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Looks up [`Icon::Named`](crate::Icon::Named) icons following the freedesktop icon theme specification.
+
+use std::path::{Path, PathBuf};
+
+/// The size the named icons are resolved at, hosts scale it to the size they need.
+pub(crate) const NAMED_ICON_SIZE: u32 = 128;
+
+/// The theme every icon theme inherits from, where applications install their icons.
+const FALLBACK_THEME: &str = "hicolor";
+
+/// Whether the name can be looked up in an icon theme.
+///
+/// Icon names are file names without an extension, e.g. `com.tauri.dev` or `mail-unread-symbolic`.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+  !name.is_empty()
+    && !name.starts_with(|c| c == '.' || c == '-')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+    && !name.ends_with(".png")
+    && !name.ends_with(".svg")
+}
+
+/// The base directories of the icon themes, `~/.icons` then the `icons` directory of the XDG data directories.
+pub(crate) fn base_dirs() -> Vec<PathBuf> {
+  let mut dirs = Vec::new();
+  let home = crate::api::path::home_dir();
+  if let Some(home) = &home {
+    dirs.push(home.join(".icons"));
+  }
+  match std::env::var_os("XDG_DATA_HOME") {
+    Some(data_home) if !data_home.is_empty() => dirs.push(PathBuf::from(data_home).join("icons")),
+    _ => dirs.extend(home.map(|home| home.join(".local/share/icons"))),
+  }
+  let data_dirs = std::env::var("XDG_DATA_DIRS")
+    .ok()
+    .filter(|dirs| !dirs.is_empty())
+    .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+  dirs.extend(
+    data_dirs
+      .split(':')
+      .filter(|dir| !dir.is_empty())
+      .map(|dir| Path::new(dir).join("icons")),
+  );
+  dirs
+}
+
+/// Finds the PNG file of the icon, closest to the size.
+///
+/// `-symbolic` icons are SVG files, so those fall back to the regular icon of the same name.
+pub(crate) fn lookup(name: &str, size: u32, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+  let mut names = vec![name];
+  if let Some(regular) = name.strip_suffix("-symbolic") {
+    names.push(regular);
+  }
+  names.into_iter().find_map(|name| {
+    lookup_in_theme(name, size, base_dirs).or_else(|| {
+      // icons outside a theme, e.g. `/usr/share/pixmaps`
+      base_dirs
+        .iter()
+        .filter(|dir| dir.file_name() == Some("icons".as_ref()))
+        .filter_map(|dir| dir.parent())
+        .map(|data_dir| data_dir.join("pixmaps").join(format!("{}.png", name)))
+        .find(|path| path.is_file())
+    })
+  })
+}
+
+fn lookup_in_theme(name: &str, size: u32, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+  let file_name = format!("{}.png", name);
+  let mut candidates: Vec<(u32, PathBuf)> = Vec::new();
+  for theme_dir in base_dirs.iter().map(|dir| dir.join(FALLBACK_THEME)) {
+    let size_dirs = match std::fs::read_dir(&theme_dir) {
+      Ok(entries) => entries,
+      Err(_) => continue,
+    };
+    for size_dir in size_dirs.flatten() {
+      // `48x48` or `48x48@2`, scalable icons are SVG files
+      let dir_size = match size_dir
+        .file_name()
+        .to_str()
+        .and_then(|dir_name| parse_size_dir(dir_name))
+      {
+        Some(dir_size) => dir_size,
+        None => continue,
+      };
+      let contexts = match std::fs::read_dir(size_dir.path()) {
+        Ok(entries) => entries,
+        Err(_) => continue,
+      };
+      for context in contexts.flatten() {
+        let path = context.path().join(&file_name);
+        if path.is_file() {
+          candidates.push((dir_size, path));
+        }
+      }
+    }
+  }
+  // the earlier base directories take precedence for icons of the same size
+  candidates.sort_by_key(|(dir_size, _)| *dir_size);
+  let smallest_larger = candidates
+    .iter()
+    .position(|(dir_size, _)| *dir_size >= size);
+  match smallest_larger {
+    Some(index) => Some(candidates.swap_remove(index).1),
+    None => candidates.pop().map(|(_, path)| path),
+  }
+}
+
+/// The pixel size of an icon theme directory name.
+fn parse_size_dir(name: &str) -> Option<u32> {
+  let (size, scale) = match name.split_once('@') {
+    Some((size, scale)) => (size, scale.parse::<u32>().ok()?),
+    None => (name, 1),
+  };
+  let (width, height) = size.split_once('x')?;
+  let width = width.parse::<u32>().ok()?;
+  if height.parse::<u32>().ok()? != width {
+    return None;
+  }
+  Some(width * scale)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{is_valid_name, lookup, parse_size_dir};
+  use std::path::PathBuf;
+
+  #[test]
+  fn icon_names() {
+    for name in [
+      "com.tauri.dev",
+      "com.tauri.dev-tray-symbolic",
+      "mail_unread",
+      "gtk+",
+    ] {
+      assert!(is_valid_name(name), "{}", name);
+    }
+    for name in [
+      "",
+      ".hidden",
+      "-flag",
+      "../icon",
+      "/usr/share/icons/hicolor/48x48/apps/icon.png",
+      "icon.png",
+      "icon.svg",
+      "with space",
+    ] {
+      assert!(!is_valid_name(name), "{}", name);
+    }
+  }
+
+  #[test]
+  fn size_dirs() {
+    assert_eq!(parse_size_dir("48x48"), Some(48));
+    assert_eq!(parse_size_dir("32x32@2"), Some(64));
+    assert_eq!(parse_size_dir("scalable"), None);
+    assert_eq!(parse_size_dir("32x48"), None);
+  }
+
+  #[test]
+  fn theme_lookup() {
+    let root = tempfile::tempdir().unwrap();
+    let root = root.path();
+    let user = root.join("home/.local/share/icons");
+    let system = root.join("usr/share/icons");
+    let files = [
+      user.join("hicolor/256x256/apps/com.tauri.dev.png"),
+      system.join("hicolor/32x32/apps/com.tauri.dev.png"),
+      system.join("hicolor/128x128/apps/com.tauri.dev.png"),
+      system.join("hicolor/512x512/apps/com.tauri.dev.png"),
+      system.join("hicolor/scalable/apps/com.tauri.dev-symbolic.svg"),
+      system.join("hicolor/16x16/status/small.png"),
+      root.join("usr/share/pixmaps/legacy.png"),
+    ];
+    for file in &files {
+      std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+      std::fs::write(file, "").unwrap();
+    }
+    let base_dirs = vec![user, system.clone()];
+
+    assert_eq!(
+      lookup("com.tauri.dev", 128, &base_dirs),
+      Some(files[2].clone())
+    );
+    assert_eq!(
+      lookup("com.tauri.dev", 200, &base_dirs),
+      Some(files[0].clone())
+    );
+    assert_eq!(
+      lookup("com.tauri.dev", 1024, &base_dirs),
+      Some(files[3].clone())
+    );
+    // the SVG can't be rasterized, so the regular icon is used
+    assert_eq!(
+      lookup("com.tauri.dev-symbolic", 128, &base_dirs),
+      Some(files[2].clone())
+    );
+    // smaller icons are used if there is no larger one
+    assert_eq!(lookup("small", 128, &base_dirs), Some(files[5].clone()));
+    assert_eq!(lookup("legacy", 128, &base_dirs), Some(files[6].clone()));
+    assert_eq!(lookup("missing", 128, &base_dirs), None);
+    assert_eq!(
+      lookup("com.tauri.dev", 128, &[PathBuf::from("/nonexistent")]),
+      None
+    );
+  }
+}
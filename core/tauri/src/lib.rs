@@ -20,6 +20,7 @@
 //! - **devtools**: Enables the developer tools (Web inspector) and [`Window::open_devtools`]. Enabled by default on debug builds.
 //! On macOS it uses private APIs, so you can't enable it if your app will be published to the App Store.
 //! - **shell-open-api**: Enables the [`api::shell`] module.
+//! - **test**: Enables the [`test`] module exposing the mock runtime and desktop session, for unit testing apps.
 //! - **http-api**: Enables the [`api::http`] module.
 //! - **http-multipart**: Adds support to `multipart/form-data` requests.
 //! - **reqwest-client**: Uses `reqwest` as HTTP client on the `http` APIs. Improves performance, but increases the bundle size.
@@ -155,6 +156,7 @@
 //! - **app-all**: Enables all [App APIs](https://tauri.app/en/docs/api/js/modules/app).
 //! - **app-show**: Enables the [`show` API](https://tauri.app/en/docs/api/js/modules/app#show).
 //! - **app-hide**: Enables the [`hide` API](https://tauri.app/en/docs/api/js/modules/app#hide).
+//! - **app-portal-state**: Enables the [`getPortalState` API](https://tauri.app/en/docs/api/js/modules/app#getportalstate).
 
 #![warn(missing_docs, rust_2018_idioms)]
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
@@ -177,10 +179,17 @@ pub mod command;
 mod endpoints;
 mod error;
 mod event;
+#[cfg(target_os = "linux")]
+mod flatpak;
 mod hooks;
+#[cfg(feature = "icon-png")]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod icon_theme;
 mod manager;
 mod pattern;
 pub mod plugin;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod portal;
 pub mod window;
 use tauri_runtime as runtime;
 /// The allowlist scopes.
@@ -255,6 +264,12 @@ pub use {
   scope::*,
 };
 
+#[cfg(target_os = "linux")]
+#[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+pub use self::app::IdentifierMismatch;
+
+pub use self::portal::PortalState;
+
 #[cfg(feature = "clipboard")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "clipboard")))]
 pub use self::runtime::ClipboardManager;
@@ -313,11 +328,21 @@ impl UpdaterEvent {
 
 /// The user event type.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum EventLoopMessage {
   /// Updater event.
   #[cfg(updater)]
   #[cfg_attr(doc_cfg, doc(cfg(feature = "updater")))]
   Updater(UpdaterEvent),
+  /// The installed Flatpak was updated while the app is running.
+  #[cfg(target_os = "linux")]
+  #[cfg_attr(doc_cfg, doc(cfg(target_os = "linux")))]
+  UpdatedWhileRunning {
+    /// The commit the running instance was started from.
+    old_commit: String,
+    /// The commit that is deployed now.
+    new_commit: String,
+  },
 }
 
 /// The webview runtime interface. A wrapper around [`runtime::Runtime`] with the proper user event type associated.
@@ -373,6 +398,17 @@ pub enum Icon {
   #[cfg(any(feature = "icon-ico", feature = "icon-png"))]
   #[cfg_attr(doc_cfg, doc(cfg(any(feature = "icon-ico", feature = "icon-png"))))]
   Raw(Vec<u8>),
+  /// Icon from the icon theme, e.g. the application id of a Flatpak that installed its icons in the `hicolor` theme.
+  ///
+  /// A missing `-symbolic` icon falls back to the regular icon of the same name.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Looked up in the `hicolor` theme of the XDG data directories, then in their `pixmaps` directories. Only PNG icons are supported.
+  /// - **Windows / macOS:** Unsupported, using it is an error.
+  #[cfg(feature = "icon-png")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "icon-png")))]
+  Named(String),
   /// Icon from raw RGBA bytes.
   Rgba {
     /// RGBA bytes of the icon image.
@@ -408,6 +444,8 @@ impl TryFrom<Icon> for runtime::Icon {
         let bytes = match icon {
           Icon::File(p) => std::fs::read(p)?,
           Icon::Raw(r) => r,
+          #[cfg(feature = "icon-png")]
+          Icon::Named(name) => std::fs::read(resolve_named_icon(&name)?)?,
           Icon::Rgba { .. } => unreachable!(),
         };
         let extension = infer::get(&bytes)
@@ -448,6 +486,21 @@ impl TryFrom<Icon> for runtime::Icon {
   }
 }
 
+/// The file of a named icon.
+#[cfg(feature = "icon-png")]
+fn resolve_named_icon(name: &str) -> Result<std::path::PathBuf> {
+  if !icon_theme::is_valid_name(name) {
+    return Err(Error::InvalidIconName(name.into()));
+  }
+  #[cfg(target_os = "linux")]
+  {
+    icon_theme::lookup(name, icon_theme::NAMED_ICON_SIZE, &icon_theme::base_dirs())
+      .ok_or_else(|| Error::IconNotFound(name.into()))
+  }
+  #[cfg(not(target_os = "linux"))]
+  Err(Error::NamedIconUnsupported(name.into()))
+}
+
 /// User supplied data required inside of a Tauri application.
 ///
 /// # Stability
@@ -819,7 +872,8 @@ pub(crate) mod sealed {
 }
 
 /// Utilities for unit testing on Tauri applications.
-#[cfg(test)]
+#[cfg(any(test, feature = "test"))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "test")))]
 pub mod test;
 
 #[cfg(test)]
@@ -0,0 +1,572 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Detects the desktop integration environment of the app, see [`crate::App::portal_state`].
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+use crate::Env;
+
+const FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+const GLOBAL_SHORTCUTS: &str = "org.freedesktop.portal.GlobalShortcuts";
+const NOTIFICATION: &str = "org.freedesktop.portal.Notification";
+const OPEN_URI: &str = "org.freedesktop.portal.OpenURI";
+
+/// The sandbox and XDG desktop portals the app runs with.
+///
+/// Detected once, the first time it's requested.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PortalState {
+  /// Whether the desktop integration is detected on this platform, only on Linux.
+  pub supported: bool,
+  /// Whether the app runs inside a Flatpak sandbox.
+  pub sandboxed: bool,
+  /// The Flatpak application id, when sandboxed.
+  pub flatpak_id: Option<String>,
+  /// Whether the `org.freedesktop.portal.Desktop` service answered on the session bus.
+  pub portal_available: bool,
+  /// The version of each portal interface the service provides, by interface name, e.g.
+  /// `org.freedesktop.portal.FileChooser`. See [`Self::version`].
+  pub interfaces: BTreeMap<String, u32>,
+  /// Whether notifications can be sent through the `Notification` portal.
+  pub notification: bool,
+  /// Whether files can be picked through the `FileChooser` portal.
+  pub file_chooser: bool,
+  /// Whether global shortcuts can be registered through the `GlobalShortcuts` portal.
+  pub global_shortcuts: bool,
+  /// Whether directories can be picked through the `FileChooser` portal, from its version 3.
+  pub directory_chooser: bool,
+  /// Whether the folder of a file can be opened through the `OpenURI` portal, from its version 3.
+  pub open_directory: bool,
+}
+
+impl PortalState {
+  fn new(
+    sandboxed: bool,
+    flatpak_id: Option<String>,
+    interfaces: Option<BTreeMap<String, u32>>,
+  ) -> Self {
+    let has = |interface: &str| {
+      interfaces
+        .as_ref()
+        .map_or(false, |interfaces| interfaces.contains_key(interface))
+    };
+    let since = |interface: &str, version: u32| {
+      interfaces
+        .as_ref()
+        .and_then(|interfaces| interfaces.get(interface))
+        .map_or(false, |v| *v >= version)
+    };
+    Self {
+      supported: true,
+      sandboxed,
+      flatpak_id,
+      portal_available: interfaces.is_some(),
+      notification: has(NOTIFICATION),
+      file_chooser: has(FILE_CHOOSER),
+      global_shortcuts: has(GLOBAL_SHORTCUTS),
+      directory_chooser: since(FILE_CHOOSER, 3),
+      open_directory: since(OPEN_URI, 3),
+      interfaces: interfaces.unwrap_or_default(),
+    }
+  }
+
+  /// The version of the portal interface, `0` when the service doesn't provide it.
+  pub fn version(&self, interface: &str) -> u32 {
+    self.interfaces.get(interface).copied().unwrap_or(0)
+  }
+}
+
+/// The portal service on the session bus, replaced by [`crate::test::MockPortal`] in tests.
+pub(crate) trait PortalBus: Send + Sync {
+  /// The introspection XML of the portal object, `None` if the service isn't reachable.
+  fn introspect(&self) -> Option<String>;
+  /// The `version` property of the interface.
+  fn version(&self, interface: &str) -> Option<u32>;
+  /// The proxies the `ProxyResolver` portal returns for the URI, in order of preference.
+  fn proxy_lookup(&self, uri: &str) -> Option<Vec<String>>;
+}
+
+/// The portal interfaces the service provides, with their version.
+fn probe_interfaces(bus: &dyn PortalBus) -> Option<BTreeMap<String, u32>> {
+  let xml = bus.introspect()?;
+  let interfaces = xml
+    .split("<interface name=\"")
+    .skip(1)
+    .filter_map(|rest| rest.split('"').next())
+    .filter(|name| name.starts_with("org.freedesktop.portal."))
+    // interfaces from before the portals were versioned don't have the property
+    .map(|name| (name.to_string(), bus.version(name).unwrap_or(1)))
+    .collect();
+  Some(interfaces)
+}
+
+/// The portal state of the app, probed the first time it's requested.
+pub(crate) struct PortalProbe {
+  /// The bus to probe instead of the session bus.
+  bus: Option<Arc<dyn PortalBus>>,
+  state: OnceCell<PortalState>,
+}
+
+impl PortalProbe {
+  pub(crate) fn new(bus: Option<Arc<dyn PortalBus>>) -> Self {
+    Self {
+      bus,
+      state: OnceCell::new(),
+    }
+  }
+
+  /// The cached portal state.
+  pub(crate) fn state(&self, env: &Env) -> PortalState {
+    self
+      .state
+      .get_or_init(|| match &self.bus {
+        Some(bus) => probe(env, bus.as_ref()),
+        None => detect(env),
+      })
+      .clone()
+  }
+
+  /// A proxy resolver for the HTTP client, calling the same bus as the probe.
+  #[cfg(http_request)]
+  pub(crate) fn proxy_resolver(&self) -> proxy::PortalProxyResolver {
+    proxy::PortalProxyResolver::new(self.bus.clone())
+  }
+}
+
+#[cfg(http_request)]
+mod proxy {
+  use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+  };
+
+  use url::Url;
+
+  use super::PortalBus;
+
+  /// How long the proxy of an origin is reused before the portal is asked again.
+  const CACHE_DURATION: Duration = Duration::from_secs(300);
+
+  /// Resolves the proxy of each request origin through the `org.freedesktop.portal.ProxyResolver` portal.
+  ///
+  /// A missing portal or a `direct://` answer means no proxy.
+  pub(crate) struct PortalProxyResolver {
+    /// The bus to call instead of the session bus.
+    bus: Option<Arc<dyn PortalBus>>,
+    cache: Mutex<HashMap<String, (Instant, Option<Url>)>>,
+  }
+
+  impl PortalProxyResolver {
+    pub(super) fn new(bus: Option<Arc<dyn PortalBus>>) -> Self {
+      Self {
+        bus,
+        cache: Default::default(),
+      }
+    }
+
+    fn lookup_uncached(&self, url: &Url) -> Option<Vec<String>> {
+      match &self.bus {
+        Some(bus) => bus.proxy_lookup(url.as_str()),
+        #[cfg(target_os = "linux")]
+        None => super::dbus::DbusSend::new().proxy_lookup(url.as_str()),
+        #[cfg(not(target_os = "linux"))]
+        None => None,
+      }
+    }
+  }
+
+  impl crate::api::http::ProxyResolver for PortalProxyResolver {
+    fn lookup(&self, url: &Url) -> Option<Url> {
+      let origin = url.origin().ascii_serialization();
+      if let Some((resolved_at, proxy)) = self.cache.lock().unwrap().get(&origin) {
+        if resolved_at.elapsed() < CACHE_DURATION {
+          return proxy.clone();
+        }
+      }
+      // the client takes a single proxy, so the preferred one is used
+      let proxy = self
+        .lookup_uncached(url)
+        .and_then(|proxies| proxies.iter().find_map(|proxy| Url::parse(proxy).ok()));
+      self
+        .cache
+        .lock()
+        .unwrap()
+        .insert(origin, (Instant::now(), proxy.clone()));
+      proxy
+    }
+  }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn probe(env: &Env, bus: &dyn PortalBus) -> PortalState {
+  #[cfg(target_os = "linux")]
+  let flatpak_id = env.flatpak.as_ref().map(|info| info.id.clone());
+  #[cfg(not(target_os = "linux"))]
+  let flatpak_id = None;
+  PortalState::new(flatpak_id.is_some(), flatpak_id, probe_interfaces(bus))
+}
+
+#[cfg(target_os = "linux")]
+fn detect(env: &Env) -> PortalState {
+  probe(env, &dbus::DbusSend::new())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect(_env: &Env) -> PortalState {
+  PortalState::default()
+}
+
+#[cfg(target_os = "linux")]
+mod dbus {
+  use std::{
+    io::Read,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+  };
+
+  /// How long a single call may take.
+  const CALL_TIMEOUT: Duration = Duration::from_millis(500);
+  /// How long the whole probe may take, the remaining versions are not queried after it.
+  const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+  /// Calls the portal with `dbus-send`.
+  pub(super) struct DbusSend {
+    deadline: Instant,
+    /// The address of the bus to call instead of the session bus.
+    address: Option<String>,
+  }
+
+  impl DbusSend {
+    pub(super) fn new() -> Self {
+      Self {
+        deadline: Instant::now() + PROBE_TIMEOUT,
+        address: None,
+      }
+    }
+
+    /// Calls the portal on the bus at the address, with the whole probe due by the deadline.
+    #[cfg(test)]
+    pub(super) fn with_address(address: String, deadline: Instant) -> Self {
+      Self {
+        deadline,
+        address: Some(address),
+      }
+    }
+
+    fn call(&self, method: &str, args: &[&str]) -> Option<String> {
+      let timeout = self
+        .deadline
+        .checked_duration_since(Instant::now())?
+        .min(CALL_TIMEOUT);
+      let bus = match &self.address {
+        Some(address) => format!("--bus={}", address),
+        None => "--session".into(),
+      };
+      let mut child = Command::new("dbus-send")
+        .args([
+          &bus,
+          "--print-reply",
+          &format!("--reply-timeout={}", timeout.as_millis()),
+          "--dest=org.freedesktop.portal.Desktop",
+          "/org/freedesktop/portal/desktop",
+          method,
+        ])
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+      // read while polling, a large reply fills the pipe and blocks `dbus-send` otherwise
+      let mut stdout = child.stdout.take()?;
+      let reader = std::thread::spawn(move || {
+        let mut reply = String::new();
+        stdout.read_to_string(&mut reply).ok().map(|_| reply)
+      });
+      let started = Instant::now();
+      loop {
+        match child.try_wait() {
+          Ok(Some(status)) if status.success() => break,
+          Ok(None) if started.elapsed() < timeout => std::thread::sleep(Duration::from_millis(10)),
+          _ => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+          }
+        }
+      }
+      reader.join().ok()?
+    }
+  }
+
+  impl super::PortalBus for DbusSend {
+    fn introspect(&self) -> Option<String> {
+      self.call("org.freedesktop.DBus.Introspectable.Introspect", &[])
+    }
+
+    fn version(&self, interface: &str) -> Option<u32> {
+      // `variant       uint32 4`
+      self
+        .call(
+          "org.freedesktop.DBus.Properties.Get",
+          &[&format!("string:{}", interface), "string:version"],
+        )?
+        .split_whitespace()
+        .last()?
+        .parse()
+        .ok()
+    }
+
+    fn proxy_lookup(&self, uri: &str) -> Option<Vec<String>> {
+      // `   array [`, `      string "http://proxy.example.com:3128"`, `   ]`
+      let reply = self.call(
+        "org.freedesktop.portal.ProxyResolver.Lookup",
+        &[&format!("string:{}", uri)],
+      )?;
+      Some(
+        reply
+          .lines()
+          .filter_map(|line| {
+            line
+              .trim()
+              .strip_prefix("string \"")?
+              .strip_suffix('"')
+              .map(Into::into)
+          })
+          .collect(),
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{probe_interfaces, PortalState};
+  use crate::test::{MockPortal, PortalCall};
+
+  const BACKGROUND: &str = "org.freedesktop.portal.Background";
+  const FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+  const OPEN_URI: &str = "org.freedesktop.portal.OpenURI";
+  const SETTINGS: &str = "org.freedesktop.portal.Settings";
+
+  #[test]
+  fn partial_portals() {
+    let portal = MockPortal::new()
+      .interface(FILE_CHOOSER, 3)
+      .interface(OPEN_URI, 4)
+      // the version lookup timed out
+      .unversioned_interface(SETTINGS);
+    let state = PortalState::new(
+      true,
+      Some("com.tauri.dev".into()),
+      probe_interfaces(&portal),
+    );
+    assert!(state.portal_available);
+    assert!(state.file_chooser);
+    assert!(!state.notification);
+    assert!(!state.global_shortcuts);
+    assert!(state.directory_chooser);
+    assert!(state.open_directory);
+    assert_eq!(state.version(OPEN_URI), 4);
+    assert_eq!(state.version(SETTINGS), 1);
+    // missing interfaces read as version 0
+    assert_eq!(state.version(BACKGROUND), 0);
+    assert_eq!(
+      serde_json::to_value(&state).unwrap(),
+      serde_json::json!({
+        "supported": true,
+        "sandboxed": true,
+        "flatpakId": "com.tauri.dev",
+        "portalAvailable": true,
+        "interfaces": {
+          "org.freedesktop.portal.FileChooser": 3,
+          "org.freedesktop.portal.OpenURI": 4,
+          "org.freedesktop.portal.Settings": 1
+        },
+        "notification": false,
+        "fileChooser": true,
+        "globalShortcuts": false,
+        "directoryChooser": true,
+        "openDirectory": true
+      })
+    );
+    assert_eq!(
+      portal.calls(),
+      [
+        PortalCall::Introspect,
+        PortalCall::Version(FILE_CHOOSER.into()),
+        PortalCall::Version(OPEN_URI.into()),
+        PortalCall::Version(SETTINGS.into()),
+      ]
+    );
+  }
+
+  #[test]
+  fn no_portal() {
+    let portal = MockPortal::unavailable();
+    let state = PortalState::new(false, None, probe_interfaces(&portal));
+    assert!(state.supported);
+    assert!(!state.portal_available);
+    assert!(state.interfaces.is_empty());
+    assert!(!state.file_chooser);
+    assert!(!state.open_directory);
+    assert_eq!(state.version(OPEN_URI), 0);
+    // no versions are read from a missing service
+    assert_eq!(portal.calls(), [PortalCall::Introspect]);
+
+    let unsupported = PortalState::default();
+    assert!(!unsupported.supported);
+    assert!(!unsupported.sandboxed);
+  }
+
+  #[cfg(http_request)]
+  #[test]
+  fn proxy_resolver() {
+    use crate::{
+      api::http::ProxyResolver,
+      test::{mock_context, noop_assets, MockRuntime},
+      Builder, Manager,
+    };
+    use url::Url;
+
+    let portal = MockPortal::new().proxy(
+      "https://tauri.app",
+      &["http://proxy.example.com:3128", "direct://"],
+    );
+    let app = Builder::<MockRuntime>::new()
+      .mock_portal(portal.clone())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    let resolver = app.state::<super::PortalProbe>().proxy_resolver();
+
+    let url = |url: &str| Url::parse(url).unwrap();
+    assert_eq!(
+      resolver.lookup(&url("https://tauri.app/v1/guides")),
+      Some(url("http://proxy.example.com:3128"))
+    );
+    // looked up once per origin
+    assert_eq!(
+      resolver.lookup(&url("https://tauri.app/v1/api")),
+      Some(url("http://proxy.example.com:3128"))
+    );
+    let direct = resolver.lookup(&url("https://github.com"));
+    assert_eq!(direct.as_ref().map(Url::scheme), Some("direct"));
+    assert_eq!(
+      portal.calls(),
+      [
+        PortalCall::ProxyLookup("https://tauri.app/v1/guides".into()),
+        PortalCall::ProxyLookup("https://github.com/".into()),
+      ]
+    );
+
+    let unavailable = super::PortalProbe::new(Some(std::sync::Arc::new(MockPortal::unavailable())));
+    assert_eq!(
+      unavailable
+        .proxy_resolver()
+        .lookup(&url("https://tauri.app")),
+      None
+    );
+  }
+
+  /// Calls the portal with `dbus-send`, tests needing a bus are skipped without `dbus-daemon`.
+  #[cfg(target_os = "linux")]
+  mod private_bus {
+    use super::{super::dbus::DbusSend, probe_interfaces, PortalState, OPEN_URI};
+    use crate::portal::PortalBus;
+    use std::{
+      io::{BufRead, BufReader},
+      process::{Child, Command, Stdio},
+      time::{Duration, Instant},
+    };
+
+    /// A private bus run by `dbus-daemon`, stopped when dropped.
+    struct PrivateBus {
+      daemon: Child,
+      address: String,
+    }
+
+    impl PrivateBus {
+      /// Starts the bus, `None` when `dbus-daemon` or `dbus-send` isn't installed.
+      fn start() -> Option<Self> {
+        let started = Command::new("dbus-send")
+          .arg("--help")
+          .stdout(Stdio::null())
+          .stderr(Stdio::null())
+          .status()
+          .ok()
+          .and_then(|_| {
+            Command::new("dbus-daemon")
+              .args(["--session", "--nofork", "--print-address"])
+              .stdin(Stdio::null())
+              .stdout(Stdio::piped())
+              .stderr(Stdio::null())
+              .spawn()
+              .ok()
+          });
+        let mut daemon = match started {
+          Some(daemon) => daemon,
+          None => {
+            eprintln!("skipping, dbus-daemon or dbus-send is not installed");
+            return None;
+          }
+        };
+        let stdout = daemon.stdout.take();
+        let mut bus = Self {
+          daemon,
+          address: String::new(),
+        };
+        BufReader::new(stdout?).read_line(&mut bus.address).ok()?;
+        bus.address.truncate(bus.address.trim_end().len());
+        if bus.address.is_empty() {
+          None
+        } else {
+          Some(bus)
+        }
+      }
+    }
+
+    impl Drop for PrivateBus {
+      fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+      }
+    }
+
+    #[test]
+    fn service_missing() {
+      let bus = match PrivateBus::start() {
+        Some(bus) => bus,
+        None => return,
+      };
+
+      let started = Instant::now();
+      let dbus = DbusSend::with_address(bus.address.clone(), started + Duration::from_secs(5));
+      assert_eq!(dbus.introspect(), None);
+      assert_eq!(dbus.version(OPEN_URI), None);
+      assert_eq!(dbus.proxy_lookup("https://tauri.app"), None);
+      assert_eq!(probe_interfaces(&dbus), None);
+      // the bus answers that the service is missing instead of letting the calls time out
+      assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn probe_deadline() {
+      // calls after the deadline fail without reaching the bus
+      let dbus = DbusSend::with_address("unix:path=/nonexistent".into(), Instant::now());
+      std::thread::sleep(Duration::from_millis(1));
+      assert_eq!(dbus.introspect(), None);
+      assert_eq!(
+        PortalState::new(false, None, probe_interfaces(&dbus)),
+        PortalState::new(false, None, None)
+      );
+    }
+  }
+}
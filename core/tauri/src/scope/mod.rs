@@ -15,6 +15,10 @@ pub use shell::{
   ScopeAllowedCommand as ShellScopeAllowedCommand, ScopeConfig as ShellScopeConfig,
   ScopeError as ShellScopeError,
 };
+#[cfg(feature = "shell-open-api")]
+pub use shell::Event as ShellScopeEvent;
+#[cfg(all(any(test, feature = "test"), feature = "shell-open-api"))]
+pub(crate) use shell::Opener;
 use std::path::Path;
 
 pub(crate) struct Scopes {
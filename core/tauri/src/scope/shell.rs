@@ -5,12 +5,28 @@
 #[cfg(any(shell_execute, shell_sidecar))]
 use crate::api::process::Command;
 #[cfg(feature = "shell-open-api")]
-use crate::api::shell::Program;
+use crate::api::shell::{
+  OnShellOpen, OpenDecision, OpenMechanism, OpenOptions, OpenOutcome, OpenRequest, Program,
+  ShowInFolderMechanism,
+};
+use crate::FsScope;
 
 use regex::Regex;
-use tauri_utils::{config::Config, Env, PackageInfo};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use tauri_utils::{
+  config::{Config, ShellOpenLocalScope},
+  Env, PackageInfo,
+};
 
 use std::collections::HashMap;
+#[cfg(feature = "shell-open-api")]
+use std::{
+  borrow::Cow,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+};
+#[cfg(feature = "shell-open-api")]
+use uuid::Uuid;
 
 /// Allowed representation of `Execute` command arguments.
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -63,6 +79,36 @@ pub struct ScopeConfig {
   /// The validation regex that `shell > open` paths must match against.
   pub open: Option<Regex>,
 
+  /// Per-program validation regexes for `shell > open` paths, using program names as the keys.
+  ///
+  /// When not empty, these replace [`Self::open`]. The `default` rule applies to the system
+  /// default handler and to programs without their own rule. Without it, those are denied.
+  pub open_rules: HashMap<String, Regex>,
+
+  /// The URL schemes that `shell > open` paths must use when there is no validation regex.
+  ///
+  /// Schemes are matched case-insensitively. When empty, paths are not checked for a scheme.
+  pub open_schemes: Vec<String>,
+
+  /// Program names that `shell > open` paths can be opened with, in addition to the built-in ones.
+  pub open_custom_programs: Vec<String>,
+
+  /// The only programs that `shell > open` paths can be opened with, if set.
+  ///
+  /// The system default handler is not affected.
+  pub open_allowed_programs: Option<Vec<String>>,
+
+  /// The scope that local `shell > open` paths and `file://` URLs must be allowed by.
+  pub open_local_scope: ShellOpenLocalScope,
+
+  /// The patterns of the extra arguments each `shell > open` program can be launched with, using
+  /// the program names as keys.
+  pub open_allowed_args: HashMap<String, Vec<Regex>>,
+
+  /// Additional environment variables to clear when launching `shell > open` programs from an
+  /// AppImage.
+  pub open_sanitize_env: Vec<String>,
+
   /// All allowed commands, using their unique command name as the keys.
   pub scopes: HashMap<String, ScopeAllowedCommand>,
 }
@@ -105,9 +151,61 @@ impl ScopeAllowedArg {
   }
 }
 
-/// Scope for filesystem access.
+/// Shell scope change event.
+#[cfg(feature = "shell-open-api")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Event {
+  /// A `shell > open` pattern has been allowed.
+  OpenAllowed(String),
+  /// A `shell > open` pattern has been forbidden.
+  OpenForbidden(String),
+}
+
+#[cfg(feature = "shell-open-api")]
+type EventListener = Box<dyn Fn(&Event) + Send>;
+
+/// The `shell > open` patterns added at runtime.
+#[cfg(feature = "shell-open-api")]
+#[derive(Default)]
+struct OpenPatterns {
+  allowed: Vec<Regex>,
+  forbidden: Vec<Regex>,
+}
+
+#[cfg(feature = "shell-open-api")]
+impl OpenPatterns {
+  /// Ensures the path doesn't match a pattern forbidden with [`Scope::forbid_open`].
+  fn check_forbidden(&self, path: &str) -> Result<(), ScopeError> {
+    match self.forbidden.iter().find(|p| p.is_match(path)) {
+      Some(pattern) => Err(ScopeError::OpenForbidden {
+        path: path.into(),
+        pattern: pattern.as_str().into(),
+      }),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Scope for shell access.
 #[derive(Clone)]
-pub struct Scope(ScopeConfig);
+pub struct Scope {
+  config: ScopeConfig,
+  #[cfg(feature = "shell-open-api")]
+  fs: FsScope,
+  /// The environment variables to sanitize when launching programs, empty if not needed.
+  #[cfg(feature = "shell-open-api")]
+  sanitize_env: Vec<String>,
+  #[cfg(feature = "shell-open-api")]
+  open_patterns: Arc<Mutex<OpenPatterns>>,
+  #[cfg(feature = "shell-open-api")]
+  event_listeners: Arc<Mutex<HashMap<Uuid, EventListener>>>,
+  #[cfg(feature = "shell-open-api")]
+  open_hook: Option<Arc<OnShellOpen>>,
+  #[cfg(feature = "shell-open-api")]
+  opener: Arc<dyn Opener>,
+}
 
 /// All errors that can happen while validating a scoped command.
 #[derive(Debug, thiserror::Error)]
@@ -182,6 +280,159 @@ pub enum ScopeError {
   #[error("Scoped command {0} received arguments in an unexpected format")]
   InvalidInput(String),
 
+  /// The custom program is not listed in the `shell > open` configuration.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Program {0} is not allowed to open paths, it must be listed in `tauri > allowlist > shell > open > customPrograms`")]
+  ProgramNotAllowed(String),
+
+  /// The custom program is a path instead of a program name.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Program {0} must be a program name, not a path")]
+  InvalidProgramName(String),
+
+  /// The program is not listed in the `shell > open` allowed programs.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Program {0} is not allowed to open paths, it must be listed in `tauri > allowlist > shell > open > allowedPrograms`")]
+  ProgramNotInAllowedPrograms(String),
+
+  /// Some of the paths passed to [`Scope::open_all`] did not pass validation.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Paths did not pass the shell open validation: {}", .0.join(", "))]
+  RejectedPaths(Vec<String>),
+
+  /// The `file://` URL does not point to a local path.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Invalid file URL {0}")]
+  InvalidFileUrl(String),
+
+  /// The local path could not be canonicalized, e.g. because it does not exist.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Failed to resolve local path {0}: {1}")]
+  LocalPath(PathBuf, std::io::Error),
+
+  /// The canonical local path is not allowed on the fs scope.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Local path {0} is not allowed on the fs scope")]
+  LocalPathNotAllowed(PathBuf),
+
+  /// The program requested by the frontend is not allowed by the `shell > open > with` policy.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Opening with `{program}` is not allowed by `tauri.conf.json > tauri > allowlist > shell > open > with`, {}", describe_allowed_programs(.allowed))]
+  OpenWithNotAllowed {
+    /// The requested program.
+    program: String,
+    /// The programs the policy allows, empty if only the system default handler is allowed.
+    allowed: Vec<String>,
+  },
+
+  /// The `shell > open` validation rules have neither a rule for the program nor a `default` rule.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Opening paths with {0} is not allowed by the shell open validation rules")]
+  NoOpenRule(String),
+
+  /// [`OpenOptions::wait`] was set without a program, but the system default handler can't be
+  /// waited for.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Waiting for the opened path requires a program, the system default handler can't be waited for")]
+  WaitRequiresProgram,
+
+  /// Extra arguments were passed to [`Scope::open_with_options`] without a program, but the
+  /// system default handler takes no arguments.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Extra arguments require a program, the system default handler takes no arguments")]
+  ArgsRequireProgram,
+
+  /// The extra argument is not allowed by the `allowedArgs` patterns of the program.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Argument `{arg}` is not allowed for program `{program}`")]
+  ArgNotAllowed {
+    /// The program name.
+    program: String,
+    /// The rejected argument.
+    arg: String,
+  },
+
+  /// The path passed to [`Scope::show_in_folder`] is neither an absolute path nor a `file://` URL.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("{0} is not a local path")]
+  NotLocalPath(String),
+
+  /// The `shell > open` path did not match the validation regex.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Path {path} does not match the shell open validation regex {pattern}")]
+  PathValidation {
+    /// The rejected path.
+    path: String,
+
+    /// Regex that the path failed to match.
+    pattern: String,
+  },
+
+  /// The `shell > open` path uses a scheme that is always denied, such as `javascript:`.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Path {0} uses a denied URL scheme")]
+  DeniedScheme(String),
+
+  /// The `shell > open` path does not use one of the allowed URL schemes.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error(
+    "Path {0} does not use an allowed URL scheme, see `tauri > allowlist > shell > open > schemes`"
+  )]
+  SchemeNotAllowed(String),
+
+  /// The `shell > open` path matches a pattern forbidden with [`Scope::forbid_open`].
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Path {path} is forbidden by the shell open pattern {pattern}")]
+  OpenForbidden {
+    /// The path that was forbidden.
+    path: String,
+    /// The forbidden pattern it matched.
+    pattern: String,
+  },
+
+  /// The pattern passed to [`Scope::allow_open`] or [`Scope::forbid_open`] is not a valid regex.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Invalid shell open pattern: {0}")]
+  InvalidOpenPattern(#[from] regex::Error),
+
+  /// The [`crate::Builder::on_shell_open`] hook denied opening the path.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Opening {path} was denied: {reason}")]
+  OpenDenied {
+    /// The path that was denied.
+    path: String,
+    /// The reason given by the hook.
+    reason: String,
+  },
+
+  /// The program to open the path with could not be found.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  #[error("Program {name} not found")]
+  ProgramNotFound {
+    /// The program name.
+    name: String,
+  },
+
   /// A generic IO error that occurs while executing specified shell commands.
   #[cfg(shell_scope)]
   #[cfg_attr(
@@ -192,20 +443,256 @@ pub enum ScopeError {
   Io(#[from] std::io::Error),
 }
 
+impl ScopeError {
+  /// A stable identifier of the error variant, serialized as the `kind` field.
+  pub fn kind(&self) -> &'static str {
+    match self {
+      #[cfg(any(shell_execute, shell_sidecar))]
+      Self::BadSidecarFlag => "badSidecarFlag",
+      #[cfg(any(shell_execute, shell_sidecar))]
+      Self::Sidecar(_) => "sidecar",
+      #[cfg(any(shell_execute, shell_sidecar))]
+      Self::NotFound(_) => "notFound",
+      #[cfg(any(shell_execute, shell_sidecar))]
+      Self::MissingVar(_, _) => "missingVar",
+      #[cfg(shell_scope)]
+      Self::Validation { .. } => "validation",
+      #[cfg(any(shell_execute, shell_sidecar))]
+      Self::InvalidInput(_) => "invalidInput",
+      #[cfg(feature = "shell-open-api")]
+      Self::ProgramNotAllowed(_) => "programNotAllowed",
+      #[cfg(feature = "shell-open-api")]
+      Self::InvalidProgramName(_) => "invalidProgramName",
+      #[cfg(feature = "shell-open-api")]
+      Self::ProgramNotInAllowedPrograms(_) => "programNotInAllowedPrograms",
+      #[cfg(feature = "shell-open-api")]
+      Self::RejectedPaths(_) => "rejectedPaths",
+      #[cfg(feature = "shell-open-api")]
+      Self::InvalidFileUrl(_) => "invalidFileUrl",
+      #[cfg(feature = "shell-open-api")]
+      Self::LocalPath(_, _) => "localPath",
+      #[cfg(feature = "shell-open-api")]
+      Self::LocalPathNotAllowed(_) => "localPathNotAllowed",
+      #[cfg(feature = "shell-open-api")]
+      Self::NoOpenRule(_) => "noOpenRule",
+      #[cfg(feature = "shell-open-api")]
+      Self::OpenWithNotAllowed { .. } => "openWithNotAllowed",
+      #[cfg(feature = "shell-open-api")]
+      Self::WaitRequiresProgram => "waitRequiresProgram",
+      #[cfg(feature = "shell-open-api")]
+      Self::ArgsRequireProgram => "argsRequireProgram",
+      #[cfg(feature = "shell-open-api")]
+      Self::ArgNotAllowed { .. } => "argNotAllowed",
+      #[cfg(feature = "shell-open-api")]
+      Self::NotLocalPath(_) => "notLocalPath",
+      #[cfg(feature = "shell-open-api")]
+      Self::PathValidation { .. } => "pathValidation",
+      #[cfg(feature = "shell-open-api")]
+      Self::ProgramNotFound { .. } => "programNotFound",
+      #[cfg(feature = "shell-open-api")]
+      Self::DeniedScheme(_) => "deniedScheme",
+      #[cfg(feature = "shell-open-api")]
+      Self::SchemeNotAllowed(_) => "schemeNotAllowed",
+      #[cfg(feature = "shell-open-api")]
+      Self::OpenForbidden { .. } => "openForbidden",
+      #[cfg(feature = "shell-open-api")]
+      Self::InvalidOpenPattern(_) => "invalidOpenPattern",
+      #[cfg(feature = "shell-open-api")]
+      Self::OpenDenied { .. } => "openDenied",
+      #[cfg(shell_scope)]
+      Self::Io(_) => "io",
+    }
+  }
+}
+
+#[cfg(feature = "shell-open-api")]
+fn describe_allowed_programs(allowed: &[String]) -> String {
+  if allowed.is_empty() {
+    "only the system default program is allowed".into()
+  } else {
+    format!(
+      "allowed values are {}",
+      allowed
+        .iter()
+        .map(|program| format!("`{}`", program))
+        .collect::<Vec<_>>()
+        .join(", ")
+    )
+  }
+}
+
+/// Serializes the error as `{ "kind": "...", "message": "..." }`, so the frontend can tell errors
+/// apart without matching on the message.
+///
+/// [`ScopeError::OpenWithNotAllowed`] also includes the `allowed` programs.
+impl Serialize for ScopeError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    #[cfg(feature = "shell-open-api")]
+    if let Self::OpenWithNotAllowed { allowed, .. } = self {
+      let mut error = serializer.serialize_struct("ScopeError", 3)?;
+      error.serialize_field("kind", self.kind())?;
+      error.serialize_field("message", &self.to_string())?;
+      error.serialize_field("allowed", allowed)?;
+      return error.end();
+    }
+    let mut error = serializer.serialize_struct("ScopeError", 2)?;
+    error.serialize_field("kind", self.kind())?;
+    error.serialize_field("message", &self.to_string())?;
+    error.end()
+  }
+}
+
 impl Scope {
   /// Creates a new shell scope.
+  ///
+  /// The `fs` scope is used to validate local `shell > open` paths if configured to.
+  #[cfg_attr(not(feature = "shell-open-api"), allow(unused_variables))]
   pub(crate) fn new(
     config: &Config,
     package_info: &PackageInfo,
     env: &Env,
     mut scope: ScopeConfig,
+    fs: FsScope,
   ) -> Self {
     for cmd in scope.scopes.values_mut() {
       if let Ok(path) = crate::api::path::parse(config, package_info, env, &cmd.command) {
         cmd.command = path;
       }
     }
-    Self(scope)
+    #[cfg(feature = "shell-open-api")]
+    {
+      // rule keys use the same program names as the `with` argument, so resolve them the same way
+      scope.open_rules = std::mem::take(&mut scope.open_rules)
+        .into_iter()
+        .map(|(program, regex)| {
+          if program == DEFAULT_OPEN_RULE {
+            (program, regex)
+          } else {
+            match program.parse::<Program>() {
+              Ok(p) => (p.name().into_owned(), regex),
+              Err(_) => (program, regex),
+            }
+          }
+        })
+        .collect();
+      scope.open_allowed_args = std::mem::take(&mut scope.open_allowed_args)
+        .into_iter()
+        .map(|(program, patterns)| match program.parse::<Program>() {
+          Ok(p) => (p.name().into_owned(), patterns),
+          Err(_) => (program, patterns),
+        })
+        .collect();
+      if let Some(programs) = &mut scope.open_allowed_programs {
+        for program in programs.iter_mut() {
+          if let Ok(p) = program.parse::<Program>() {
+            *program = p.name().into_owned();
+          }
+        }
+      }
+    }
+    #[cfg(feature = "shell-open-api")]
+    let sanitize_env = sanitized_env_vars(env, &scope.open_sanitize_env);
+    Self {
+      config: scope,
+      #[cfg(feature = "shell-open-api")]
+      fs,
+      #[cfg(feature = "shell-open-api")]
+      sanitize_env,
+      #[cfg(feature = "shell-open-api")]
+      open_patterns: Default::default(),
+      #[cfg(feature = "shell-open-api")]
+      event_listeners: Default::default(),
+      #[cfg(feature = "shell-open-api")]
+      open_hook: None,
+      #[cfg(feature = "shell-open-api")]
+      opener: Arc::new(SystemOpener),
+    }
+  }
+
+  /// Sets what opens the validated `shell > open` targets, instead of the system.
+  #[cfg(all(any(test, feature = "test"), feature = "shell-open-api"))]
+  pub(crate) fn with_opener(mut self, opener: Arc<dyn Opener>) -> Self {
+    self.opener = opener;
+    self
+  }
+
+  /// Sets the [`crate::Builder::on_shell_open`] hook.
+  #[cfg(feature = "shell-open-api")]
+  pub(crate) fn with_open_hook(mut self, hook: Option<Arc<OnShellOpen>>) -> Self {
+    self.open_hook = hook;
+    self
+  }
+
+  /// Runs the [`crate::Builder::on_shell_open`] hook, if there is one, on a validated target.
+  #[cfg(feature = "shell-open-api")]
+  fn check_open_hook(
+    &self,
+    target: &str,
+    program: Option<&str>,
+    args: &[String],
+    window: Option<&str>,
+  ) -> Result<(), ScopeError> {
+    let hook = match &self.open_hook {
+      Some(hook) => hook,
+      None => return Ok(()),
+    };
+    match hook(OpenRequest {
+      target,
+      program,
+      args,
+      window,
+    }) {
+      OpenDecision::Allow => Ok(()),
+      OpenDecision::Deny { reason } => Err(ScopeError::OpenDenied {
+        path: target.into(),
+        reason,
+      }),
+    }
+  }
+
+  /// Listen to an event on this scope.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  pub fn listen<F: Fn(&Event) + Send + 'static>(&self, f: F) -> Uuid {
+    let id = Uuid::new_v4();
+    self.event_listeners.lock().unwrap().insert(id, Box::new(f));
+    id
+  }
+
+  #[cfg(feature = "shell-open-api")]
+  fn trigger(&self, event: Event) {
+    let listeners = self.event_listeners.lock().unwrap();
+    let handlers = listeners.values();
+    for listener in handlers {
+      listener(&event);
+    }
+  }
+
+  /// Allows `shell > open` paths matching the regex, in addition to the configured validation.
+  ///
+  /// The pattern is only kept in memory. Paths with a denied scheme such as `javascript:` and
+  /// paths matching a pattern forbidden with [`Self::forbid_open`] are still rejected.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  pub fn allow_open(&self, pattern: &str) -> Result<(), ScopeError> {
+    let regex = compile_open_pattern(pattern)?;
+    self.open_patterns.lock().unwrap().allowed.push(regex);
+    self.trigger(Event::OpenAllowed(pattern.into()));
+    Ok(())
+  }
+
+  /// Forbids `shell > open` paths matching the regex.
+  ///
+  /// **Note:** this takes precedence over the configured validation and the patterns allowed with
+  /// [`Self::allow_open`], so matching paths are denied **always**. The pattern is only kept in
+  /// memory.
+  #[cfg(feature = "shell-open-api")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "shell-open-api")))]
+  pub fn forbid_open(&self, pattern: &str) -> Result<(), ScopeError> {
+    let regex = compile_open_pattern(pattern)?;
+    self.open_patterns.lock().unwrap().forbidden.push(regex);
+    self.trigger(Event::OpenForbidden(pattern.into()));
+    Ok(())
   }
 
   /// Validates argument inputs and creates a Tauri sidecar [`Command`].
@@ -233,7 +720,7 @@ impl Scope {
     args: ExecuteArgs,
     sidecar: Option<&str>,
   ) -> Result<Command, ScopeError> {
-    let command = match self.0.scopes.get(command_name) {
+    let command = match self.config.scopes.get(command_name) {
       Some(command) => command,
       None => return Err(ScopeError::NotFound(command_name.into())),
     };
@@ -300,26 +787,1360 @@ impl Scope {
 
   /// Open a path in the default (or specified) browser.
   ///
-  /// The path is validated against the `tauri > allowlist > shell > open` validation regex, which
-  /// defaults to `^https?://`.
-  #[cfg(feature = "shell-open-api")]
-  pub fn open(&self, path: &str, with: Option<Program>) -> Result<(), ScopeError> {
-    // ensure we pass validation if the configuration has one
-    if let Some(regex) = &self.0.open {
-      if !regex.is_match(path) {
-        return Err(ScopeError::Validation {
-          index: 0,
-          validation: regex.as_str().into(),
+  /// URLs are validated against the `tauri > allowlist > shell > open` validation regex, or against
+  /// the validation rule for the program opening them. Without a regex, only the configured URL
+  /// schemes are allowed, which default to `https`, `http`, `mailto` and `tel`. URLs with a
+  /// dangerous scheme such as `javascript:` are always denied.
+  ///
+  /// Local targets, either `file://` URLs or absolute paths, are canonicalized first so symlinks
+  /// can't escape the allowed locations. The canonical path must then be allowed by the scope
+  /// configured in `tauri > allowlist > shell > open > localScope`, and it is what gets opened.
+  #[cfg(feature = "shell-open-api")]
+  pub fn open(&self, path: &str, with: Option<Program>) -> Result<OpenOutcome, ScopeError> {
+    self.open_with_options(path, with, OpenOptions::default())
+  }
+
+  /// Open a path like [`Self::open`], with [`OpenOptions`] controlling how the program is launched.
+  ///
+  /// [`OpenOptions::wait`] requires a program, otherwise [`ScopeError::WaitRequiresProgram`] is
+  /// returned. [`OpenOptions::args`] also requires a program and every argument must match one of
+  /// the program's `allowedArgs` patterns.
+  #[cfg(feature = "shell-open-api")]
+  pub fn open_with_options(
+    &self,
+    path: &str,
+    with: Option<Program>,
+    options: OpenOptions,
+  ) -> Result<OpenOutcome, ScopeError> {
+    self.open_from_window(path, with, options, None)
+  }
+
+  /// Open a path like [`Self::open_with_options`], passing the label of the requesting window to
+  /// the [`crate::Builder::on_shell_open`] hook.
+  #[cfg(feature = "shell-open-api")]
+  pub(crate) fn open_from_window(
+    &self,
+    path: &str,
+    with: Option<Program>,
+    options: OpenOptions,
+    window: Option<&str>,
+  ) -> Result<OpenOutcome, ScopeError> {
+    let (target, program) = self.validate_open(path, with, &options)?;
+    self.check_open_hook(&target, program.as_deref(), &options.args, window)?;
+    match program {
+      Some(program) => {
+        self
+          .opener
+          .launch(&program, &[target.as_ref()], &options, &self.sanitize_env)
+      }
+      None => self.opener.open_default(&target),
+    }
+  }
+
+  /// Checks whether [`Self::open`] would accept the path and program, without opening anything.
+  ///
+  /// This runs exactly the validation of [`Self::open`], which is implemented in terms of it, so
+  /// the two can't disagree. Local paths are still canonicalized, so their existence is checked.
+  /// The [`crate::Builder::on_shell_open`] hook is not consulted, since it may have side effects.
+  #[cfg(feature = "shell-open-api")]
+  pub fn is_allowed(&self, path: &str, with: Option<&Program>) -> Result<(), ScopeError> {
+    self
+      .validate_open(path, with.cloned(), &OpenOptions::default())
+      .map(|_| ())
+  }
+
+  /// Validates the arguments of [`Self::open_with_options`], returning the target to open and the
+  /// resolved program name.
+  #[cfg(feature = "shell-open-api")]
+  fn validate_open<'a>(
+    &self,
+    path: &'a str,
+    with: Option<Program>,
+    options: &OpenOptions,
+  ) -> Result<(Cow<'a, str>, Option<Cow<'static, str>>), ScopeError> {
+    if options.wait && with.is_none() {
+      return Err(ScopeError::WaitRequiresProgram);
+    }
+    if !options.args.is_empty() && with.is_none() {
+      return Err(ScopeError::ArgsRequireProgram);
+    }
+    let custom = matches!(with, Some(Program::Custom(_)));
+    let program = with.map(Program::name);
+
+    let target = self.validate_open_target(path, program.as_deref())?;
+
+    if let Some(name) = &program {
+      self.check_program(name, custom)?;
+      self.check_args(name, &options.args)?;
+    }
+
+    Ok((target, program))
+  }
+
+  /// Open multiple paths in the default (or specified) browser.
+  ///
+  /// Every path is validated like in [`Self::open`] before anything is opened. If any path fails
+  /// validation, nothing is opened and [`ScopeError::RejectedPaths`] lists the rejected entries.
+  ///
+  /// Programs that accept multiple paths, such as browsers, are launched once with all paths.
+  /// Other programs and the default handler are called once per path.
+  #[cfg(feature = "shell-open-api")]
+  pub fn open_all(
+    &self,
+    paths: &[&str],
+    with: Option<Program>,
+  ) -> Result<Vec<OpenOutcome>, ScopeError> {
+    let custom = matches!(with, Some(Program::Custom(_)));
+    let multiple_paths = with.as_ref().map_or(false, Program::accepts_multiple_paths);
+    let program = with.map(Program::name);
+
+    let mut targets = Vec::new();
+    let mut rejected = Vec::new();
+    for path in paths {
+      match self.validate_open_target(path, program.as_deref()) {
+        Ok(target) => targets.push(target),
+        Err(_) => rejected.push(path.to_string()),
+      }
+    }
+    if !rejected.is_empty() {
+      return Err(ScopeError::RejectedPaths(rejected));
+    }
+    let targets: Vec<&str> = targets.iter().map(AsRef::as_ref).collect();
+
+    if let Some(name) = &program {
+      self.check_program(name, custom)?;
+    }
+    for target in &targets {
+      self.check_open_hook(target, program.as_deref(), &[], None)?;
+    }
+
+    match program {
+      Some(program) if multiple_paths => self
+        .opener
+        .launch(
+          &program,
+          &targets,
+          &OpenOptions::default(),
+          &self.sanitize_env,
+        )
+        .map(|outcome| vec![outcome]),
+      Some(program) => targets
+        .iter()
+        .map(|target| {
+          self.opener.launch(
+            &program,
+            &[*target],
+            &OpenOptions::default(),
+            &self.sanitize_env,
+          )
+        })
+        .collect(),
+      None => targets
+        .iter()
+        .map(|target| self.opener.open_default(target))
+        .collect(),
+    }
+  }
+
+  /// Reveals a local path in the platform file manager, selecting it.
+  ///
+  /// The path is validated like a local target in [`Self::open`], so it must exist and be allowed
+  /// by the configured `tauri > allowlist > shell > open > localScope`.
+  #[cfg(feature = "shell-open-api")]
+  pub fn show_in_folder(&self, path: &str) -> Result<ShowInFolderMechanism, ScopeError> {
+    if local_path(path)?.is_none() {
+      return Err(ScopeError::NotLocalPath(path.into()));
+    }
+    let target = self.validate_open_target(path, None)?;
+    crate::api::shell::reveal(Path::new(target.as_ref())).map_err(Into::into)
+  }
+
+  /// Validates the `shell > open` path, returning the target that should be opened.
+  ///
+  /// `program` is the resolved name of the program opening the path, or `None` for the system
+  /// default handler.
+  #[cfg(feature = "shell-open-api")]
+  fn validate_open_target<'a>(
+    &self,
+    path: &'a str,
+    program: Option<&str>,
+  ) -> Result<Cow<'a, str>, ScopeError> {
+    match local_path(path)? {
+      Some(local) => {
+        let canonical =
+          std::fs::canonicalize(&local).map_err(|error| ScopeError::LocalPath(local, error))?;
+        match self.config.open_local_scope {
+          ShellOpenLocalScope::Fs => {
+            // the forbidden patterns apply to local paths regardless of the scope allowing them
+            self
+              .open_patterns
+              .lock()
+              .unwrap()
+              .check_forbidden(&canonical.to_string_lossy())?;
+            if !self.fs.is_allowed(&canonical) {
+              return Err(ScopeError::LocalPathNotAllowed(canonical));
+            }
+          }
+          _ => self.validate_open_path(&canonical.to_string_lossy(), program)?,
+        }
+        Ok(Cow::Owned(canonical.to_string_lossy().into_owned()))
+      }
+      None => {
+        self.validate_open_path(path, program)?;
+        Ok(Cow::Borrowed(path))
+      }
+    }
+  }
+
+  /// Ensures the path matches the `shell > open` validation regex, if the configuration has one,
+  /// or uses one of the allowed URL schemes otherwise.
+  ///
+  /// With per-program validation rules, the rule for `program` is used, falling back to the
+  /// `default` rule. Paths with a denied scheme are rejected regardless of the validation.
+  #[cfg(feature = "shell-open-api")]
+  fn validate_open_path(&self, path: &str, program: Option<&str>) -> Result<(), ScopeError> {
+    let scheme = url_scheme(path);
+    if let Some(scheme) = &scheme {
+      if DENIED_OPEN_SCHEMES.contains(&scheme.as_str()) {
+        return Err(ScopeError::DeniedScheme(path.into()));
+      }
+    }
+
+    {
+      // a single lock for both lists, so a concurrent mutation is either fully seen or not at all
+      let patterns = self.open_patterns.lock().unwrap();
+      patterns.check_forbidden(path)?;
+      if patterns.allowed.iter().any(|p| p.is_match(path)) {
+        return Ok(());
+      }
+    }
+
+    let regex = if self.config.open_rules.is_empty() {
+      self.config.open.as_ref()
+    } else {
+      let rule = program
+        .and_then(|program| self.config.open_rules.get(program))
+        .or_else(|| self.config.open_rules.get(DEFAULT_OPEN_RULE));
+      match rule {
+        Some(rule) => Some(rule),
+        None => {
+          return Err(ScopeError::NoOpenRule(
+            program.unwrap_or(DEFAULT_OPEN_RULE).into(),
+          ))
+        }
+      }
+    };
+    match regex {
+      Some(regex) if !regex.is_match(path) => Err(ScopeError::PathValidation {
+        path: path.into(),
+        pattern: regex.as_str().into(),
+      }),
+      None if !self.config.open_schemes.is_empty() => {
+        let allowed = scheme.map_or(false, |scheme| {
+          self
+            .config
+            .open_schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&scheme))
         });
+        if allowed {
+          Ok(())
+        } else {
+          Err(ScopeError::SchemeNotAllowed(path.into()))
+        }
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Ensures the resolved program name is allowed on the `shell > open` scope.
+  #[cfg(feature = "shell-open-api")]
+  fn check_program(&self, name: &str, custom: bool) -> Result<(), ScopeError> {
+    if custom {
+      self.check_custom_program(name)?;
+    }
+    match &self.config.open_allowed_programs {
+      Some(programs) if !programs.iter().any(|p| p == name) => {
+        Err(ScopeError::ProgramNotInAllowedPrograms(name.into()))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Ensures every extra argument matches one of the `allowedArgs` patterns of the program.
+  #[cfg(feature = "shell-open-api")]
+  fn check_args(&self, program: &str, args: &[String]) -> Result<(), ScopeError> {
+    let patterns = self.config.open_allowed_args.get(program);
+    for arg in args {
+      if !patterns.map_or(false, |patterns| patterns.iter().any(|p| p.is_match(arg))) {
+        return Err(ScopeError::ArgNotAllowed {
+          program: program.into(),
+          arg: arg.clone(),
+        });
+      }
+    }
+    Ok(())
+  }
+
+  /// Ensures a [`Program::Custom`] name is allowed on the `shell > open` scope.
+  #[cfg(feature = "shell-open-api")]
+  fn check_custom_program(&self, name: &str) -> Result<(), ScopeError> {
+    if name.contains(&['/', '\\'][..]) {
+      return Err(ScopeError::InvalidProgramName(name.into()));
+    }
+    if self.config.open_custom_programs.iter().any(|p| p == name) {
+      Ok(())
+    } else {
+      Err(ScopeError::ProgramNotAllowed(name.into()))
+    }
+  }
+}
+
+/// Compiles a `shell > open` pattern added at runtime.
+///
+/// The configured patterns are compiled along with the context, so validating a path only runs
+/// regexes that were compiled before.
+#[cfg(feature = "shell-open-api")]
+fn compile_open_pattern(pattern: &str) -> Result<Regex, ScopeError> {
+  #[cfg(test)]
+  tests::COMPILED_OPEN_PATTERNS.with(|compiled| compiled.set(compiled.get() + 1));
+  Regex::new(pattern).map_err(Into::into)
+}
+
+/// The `shell > open` validation rule for the default handler and programs without their own rule.
+#[cfg(feature = "shell-open-api")]
+const DEFAULT_OPEN_RULE: &str = "default";
+
+/// URL schemes that `shell > open` always denies, even if the validation regex matches them.
+///
+/// `file://` URLs are handled as local paths before validation, so `file` only denies other forms
+/// like `file:/etc/passwd` that would skip the local path checks.
+#[cfg(feature = "shell-open-api")]
+const DENIED_OPEN_SCHEMES: &[&str] = &["javascript", "vbscript", "data", "file"];
+
+/// Returns the lowercase URL scheme of the path, if it has one.
+///
+/// Like URL parsers, leading whitespace and control characters are skipped and tabs and newlines
+/// are ignored, so `" JavaScript:"` and `"java\tscript:"` both have the `javascript` scheme.
+#[cfg(feature = "shell-open-api")]
+fn url_scheme(path: &str) -> Option<String> {
+  let path = path.trim_start_matches(|c: char| c <= ' ');
+  let (scheme, _) = path.split_once(':')?;
+  let scheme: String = scheme
+    .chars()
+    .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+    .collect();
+  let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+    && scheme
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+  if valid {
+    Some(scheme.to_ascii_lowercase())
+  } else {
+    None
+  }
+}
+
+/// Returns the local path of `file://` URLs and absolute paths, or `None` for other targets.
+#[cfg(feature = "shell-open-api")]
+fn local_path(path: &str) -> Result<Option<PathBuf>, ScopeError> {
+  let is_file_url = path
+    .get(..7)
+    .map_or(false, |scheme| scheme.eq_ignore_ascii_case("file://"));
+  if is_file_url {
+    url::Url::parse(path)
+      .ok()
+      .and_then(|url| url.to_file_path().ok())
+      .map(Some)
+      .ok_or_else(|| ScopeError::InvalidFileUrl(path.into()))
+  } else if Path::new(path).is_absolute() {
+    Ok(Some(PathBuf::from(path)))
+  } else {
+    Ok(None)
+  }
+}
+
+/// The environment variables to sanitize when launching programs, which is only needed when
+/// running from an AppImage.
+#[cfg(feature = "shell-open-api")]
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn sanitized_env_vars(env: &Env, extra: &[String]) -> Vec<String> {
+  #[cfg(target_os = "linux")]
+  if env.appimage.is_some() {
+    return crate::api::shell::APPIMAGE_ENV_VARS
+      .iter()
+      .map(|name| name.to_string())
+      .chain(extra.iter().cloned())
+      .collect();
+  }
+  Vec::new()
+}
+
+/// Creates the command that opens the paths with the program, with the extra arguments first.
+#[cfg(feature = "shell-open-api")]
+fn open_command(
+  program: &str,
+  paths: &[&str],
+  options: &OpenOptions,
+) -> Result<std::process::Command, ScopeError> {
+  // The prevention of argument escaping is handled by the usage of std::process::Command::arg,
+  // which passes each path as a single argument to the program, and by `program_arg` for the
+  // cmd.exe metacharacters on Windows.
+  let mut command = crate::api::shell::program_command(program, options.wait)?;
+  for arg in options
+    .args
+    .iter()
+    .map(String::as_str)
+    .chain(paths.iter().copied())
+  {
+    command.arg(crate::api::shell::program_arg(arg)?.as_ref());
+  }
+  Ok(command)
+}
+
+/// Opens the validated `shell > open` targets, replaced by [`crate::test::MockShell`] in tests.
+#[cfg(feature = "shell-open-api")]
+pub(crate) trait Opener: Send + Sync {
+  /// Launches the program with the given paths.
+  fn launch(
+    &self,
+    program: &str,
+    paths: &[&str],
+    options: &OpenOptions,
+    sanitize_env: &[String],
+  ) -> Result<OpenOutcome, ScopeError>;
+
+  /// Opens the path with the system default handler.
+  fn open_default(&self, path: &str) -> Result<OpenOutcome, ScopeError>;
+}
+
+/// Launches the programs and the default handler of the system.
+#[cfg(feature = "shell-open-api")]
+struct SystemOpener;
+
+#[cfg(feature = "shell-open-api")]
+impl Opener for SystemOpener {
+  fn launch(
+    &self,
+    program: &str,
+    paths: &[&str],
+    options: &OpenOptions,
+    sanitize_env: &[String],
+  ) -> Result<OpenOutcome, ScopeError> {
+    launch_program(program, paths, options, sanitize_env)
+  }
+
+  fn open_default(&self, path: &str) -> Result<OpenOutcome, ScopeError> {
+    open_default(path)
+  }
+}
+
+/// Launches the program with the given paths.
+#[cfg(feature = "shell-open-api")]
+fn launch_program(
+  program: &str,
+  paths: &[&str],
+  options: &OpenOptions,
+  sanitize_env: &[String],
+) -> Result<OpenOutcome, ScopeError> {
+  let mut command = open_command(program, paths, options)?;
+  crate::api::shell::sanitize_env(&mut command, sanitize_env, |name| std::env::var_os(name));
+  if options.detach {
+    crate::api::shell::detach(&mut command);
+  }
+  let not_found = |error: std::io::Error| {
+    if error.kind() == std::io::ErrorKind::NotFound {
+      ScopeError::ProgramNotFound {
+        name: program.into(),
+      }
+    } else {
+      ScopeError::Io(error)
+    }
+  };
+  let mechanism = OpenMechanism::Launched(program.into());
+  if options.wait {
+    let status = crate::api::shell::launch_and_wait(command).map_err(not_found)?;
+    Ok(OpenOutcome {
+      mechanism,
+      program: None,
+      status: Some(status),
+    })
+  } else {
+    let program = crate::api::shell::launch(command).map_err(not_found)?;
+    Ok(OpenOutcome {
+      mechanism,
+      program,
+      status: None,
+    })
+  }
+}
+
+/// Opens the path with the system default handler.
+#[cfg(feature = "shell-open-api")]
+fn open_default(path: &str) -> Result<OpenOutcome, ScopeError> {
+  ::open::that(path)?;
+  Ok(OpenOutcome {
+    mechanism: OpenMechanism::Default,
+    program: None,
+    status: None,
+  })
+}
+
+#[cfg(all(test, feature = "shell-open-api"))]
+mod tests {
+  use super::{Scope, ScopeConfig, ScopeError, ShellOpenLocalScope};
+  use crate::{
+    api::shell::{OpenOptions, Program},
+    FsScope,
+  };
+  use std::path::{Path, PathBuf};
+
+  thread_local! {
+    /// The patterns compiled by [`super::compile_open_pattern`] on the test thread.
+    pub(super) static COMPILED_OPEN_PATTERNS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+  }
+
+  fn fs_scope() -> FsScope {
+    FsScope::for_fs_api(
+      &Default::default(),
+      &crate::PackageInfo {
+        name: "test".into(),
+        version: "0.1.0".parse().unwrap(),
+        authors: "Tauri",
+        description: "Tauri test",
+      },
+      &Default::default(),
+      &Default::default(),
+    )
+    .unwrap()
+  }
+
+  fn scope_with(open: &str, local_scope: ShellOpenLocalScope, custom_programs: &[&str]) -> Scope {
+    Scope {
+      config: ScopeConfig {
+        open: Some(regex::Regex::new(open).unwrap()),
+        open_rules: Default::default(),
+        open_schemes: Vec::new(),
+        open_custom_programs: custom_programs.iter().map(|p| p.to_string()).collect(),
+        open_allowed_programs: None,
+        open_allowed_args: Default::default(),
+        open_local_scope: local_scope,
+        open_sanitize_env: Vec::new(),
+        scopes: Default::default(),
+      },
+      fs: fs_scope(),
+      sanitize_env: Vec::new(),
+      open_patterns: Default::default(),
+      event_listeners: Default::default(),
+      open_hook: None,
+      opener: std::sync::Arc::new(super::SystemOpener),
+    }
+  }
+
+  fn scope(custom_programs: &[&str]) -> Scope {
+    scope_with("^https://", ShellOpenLocalScope::Open, custom_programs)
+  }
+
+  /// Creates `allowed/file.txt`, `allowed/my file.txt`, `outside/secret.txt`, and on unix a
+  /// `allowed/link.txt` symlink to `outside/secret.txt`.
+  fn local_files() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = std::fs::canonicalize(dir.path()).unwrap();
+    let allowed = root.join("allowed");
+    let outside = root.join("outside");
+    std::fs::create_dir(&allowed).unwrap();
+    std::fs::create_dir(&outside).unwrap();
+    std::fs::write(allowed.join("file.txt"), "").unwrap();
+    std::fs::write(allowed.join("my file.txt"), "").unwrap();
+    std::fs::write(outside.join("secret.txt"), "").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(outside.join("secret.txt"), allowed.join("link.txt")).unwrap();
+    (dir, allowed)
+  }
+
+  fn allowed_dir_regex(allowed: &Path) -> String {
+    format!(
+      "^{}",
+      regex::escape(&format!(
+        "{}{}",
+        allowed.display(),
+        std::path::MAIN_SEPARATOR
+      ))
+    )
+  }
+
+  #[test]
+  fn custom_program_allowed() {
+    let scope = scope(&["code", "inkscape"]);
+    assert!(scope.check_custom_program("code").is_ok());
+    assert!(scope.check_custom_program("inkscape").is_ok());
+  }
+
+  #[test]
+  fn custom_program_denied() {
+    let scope = scope(&["code"]);
+    assert!(matches!(
+      scope.check_custom_program("vim"),
+      Err(ScopeError::ProgramNotAllowed(name)) if name == "vim"
+    ));
+    assert!(matches!(
+      scope.check_custom_program("Code"),
+      Err(ScopeError::ProgramNotAllowed(_))
+    ));
+  }
+
+  #[test]
+  fn custom_program_path_rejected() {
+    let scope = scope(&["/usr/bin/code", "code"]);
+    for name in ["/usr/bin/code", "./code", "bin\\code.exe", "../code"] {
+      assert!(matches!(
+        scope.check_custom_program(name),
+        Err(ScopeError::InvalidProgramName(_))
+      ));
+    }
+  }
+
+  #[test]
+  fn open_all_rejects_atomically() {
+    let scope = scope(&[]);
+    let paths = [
+      "https://tauri.app",
+      "javascript:alert(1)",
+      "https://github.com/tauri-apps/tauri",
+      "file:///etc/passwd",
+    ];
+    // a custom program that is not allowed would fail if validation didn't reject first
+    match scope.open_all(&paths, Some(Program::Custom("vim".into()))) {
+      Err(ScopeError::RejectedPaths(rejected)) => {
+        assert_eq!(rejected, vec!["javascript:alert(1)", "file:///etc/passwd"])
+      }
+      other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+  }
+
+  #[test]
+  fn open_all_empty() {
+    assert!(scope(&[]).open_all(&[], None).unwrap().is_empty());
+  }
+
+  #[test]
+  fn local_path_allowed() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    let file = allowed.join("file.txt");
+    assert_eq!(
+      scope
+        .validate_open_target(&file.to_string_lossy(), None)
+        .unwrap(),
+      file.to_string_lossy()
+    );
+  }
+
+  #[test]
+  fn local_path_outside_rejected() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    let secret = allowed.join("..").join("outside").join("secret.txt");
+    assert!(matches!(
+      scope.validate_open_target(&secret.to_string_lossy(), None),
+      Err(ScopeError::PathValidation { .. })
+    ));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn local_path_symlink_escape_rejected() {
+    let (_dir, allowed) = local_files();
+    let link = allowed.join("link.txt");
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    assert!(matches!(
+      scope.validate_open_target(&link.to_string_lossy(), None),
+      Err(ScopeError::PathValidation { .. })
+    ));
+
+    let scope = scope_with("^https://", ShellOpenLocalScope::Fs, &[]);
+    scope.fs.allow_directory(&allowed, true).unwrap();
+    assert!(matches!(
+      scope.validate_open_target(&link.to_string_lossy(), None),
+      Err(ScopeError::LocalPathNotAllowed(_))
+    ));
+  }
+
+  #[test]
+  fn local_path_nonexistent() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    let missing = allowed.join("missing.txt");
+    assert!(matches!(
+      scope.validate_open_target(&missing.to_string_lossy(), None),
+      Err(ScopeError::LocalPath(path, _)) if path == missing
+    ));
+  }
+
+  #[test]
+  fn file_url_percent_encoded() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    let file = allowed.join("my file.txt");
+    let url = url::Url::from_file_path(&file).unwrap();
+    assert!(url.as_str().contains("my%20file.txt"));
+    assert_eq!(
+      scope.validate_open_target(url.as_str(), None).unwrap(),
+      file.to_string_lossy()
+    );
+  }
+
+  #[test]
+  fn local_path_fs_scope() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with("^https://", ShellOpenLocalScope::Fs, &[]);
+    let file = allowed.join("file.txt");
+    assert!(matches!(
+      scope.validate_open_target(&file.to_string_lossy(), None),
+      Err(ScopeError::LocalPathNotAllowed(_))
+    ));
+    scope.fs.allow_directory(&allowed, true).unwrap();
+    assert!(scope
+      .validate_open_target(&file.to_string_lossy(), None)
+      .is_ok());
+  }
+
+  #[test]
+  fn local_path_fs_scope_forbidden() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with("^https://", ShellOpenLocalScope::Fs, &[]);
+    scope.fs.allow_directory(&allowed, true).unwrap();
+    let pattern = format!(
+      "{}$",
+      regex::escape(&allowed.join("file.txt").to_string_lossy())
+    );
+    scope.forbid_open(&pattern).unwrap();
+
+    // allowed by the fs scope, but forbidden
+    assert!(matches!(
+      scope.validate_open_target(&allowed.join("file.txt").to_string_lossy(), None),
+      Err(ScopeError::OpenForbidden { pattern: p, .. }) if p == pattern
+    ));
+    assert!(scope
+      .validate_open_target(&allowed.join("my file.txt").to_string_lossy(), None)
+      .is_ok());
+  }
+
+  fn scope_with_rules(rules: &[(&str, &str)]) -> Scope {
+    let mut scope = scope(&[]);
+    scope.config.open = None;
+    scope.config.open_rules = rules
+      .iter()
+      .map(|(program, regex)| (program.to_string(), regex::Regex::new(regex).unwrap()))
+      .collect();
+    scope
+  }
+
+  #[test]
+  fn open_rules_per_program() {
+    let firefox = Program::Firefox.name();
+    let scope = scope_with_rules(&[
+      ("default", "^https://tauri\\.app"),
+      (firefox.as_ref(), "^https://github\\.com"),
+    ]);
+    assert!(scope
+      .validate_open_path("https://github.com/tauri-apps", Some(&firefox))
+      .is_ok());
+    assert!(matches!(
+      scope.validate_open_path("https://tauri.app", Some(&firefox)),
+      Err(ScopeError::PathValidation { .. })
+    ));
+    assert!(scope.validate_open_path("https://tauri.app", None).is_ok());
+    assert!(matches!(
+      scope.validate_open_path("https://github.com/tauri-apps", None),
+      Err(ScopeError::PathValidation { .. })
+    ));
+  }
+
+  #[test]
+  fn open_rules_default_fallback() {
+    let scope = scope_with_rules(&[("default", "^https://")]);
+    let chrome = Program::Chrome.name();
+    assert!(scope
+      .validate_open_path("https://tauri.app", Some(&chrome))
+      .is_ok());
+    assert!(matches!(
+      scope.validate_open_path("http://tauri.app", Some(&chrome)),
+      Err(ScopeError::PathValidation { .. })
+    ));
+  }
+
+  #[test]
+  fn open_rules_without_default_deny() {
+    let firefox = Program::Firefox.name();
+    let scope = scope_with_rules(&[(firefox.as_ref(), "^https://")]);
+    assert!(matches!(
+      scope.validate_open_path("https://tauri.app", None),
+      Err(ScopeError::NoOpenRule(name)) if name == "default"
+    ));
+    assert!(matches!(
+      scope.validate_open_path("https://tauri.app", Some("chromium")),
+      Err(ScopeError::NoOpenRule(name)) if name == "chromium"
+    ));
+    assert!(scope
+      .validate_open_path("https://tauri.app", Some(&firefox))
+      .is_ok());
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn open_async_concurrent() {
+    let scope = scope_with("^5$", ShellOpenLocalScope::Open, &["sleep"]);
+    let started = std::time::Instant::now();
+    // each open watches `sleep 5` for an early exit before returning the program
+    let first = crate::api::shell::open_async(&scope, "5", Some(Program::Custom("sleep".into())));
+    let second = crate::api::shell::open_async(&scope, "5", Some(Program::Custom("sleep".into())));
+    let outcomes = crate::async_runtime::block_on(async move { (first.await, second.await) });
+    let elapsed = started.elapsed();
+    for outcome in [outcomes.0, outcomes.1] {
+      outcome.unwrap().program.unwrap().kill().unwrap();
+    }
+    assert!(
+      elapsed < crate::api::shell::EXIT_CHECK_TIMEOUT * 2,
+      "opens were serialized: {:?}",
+      elapsed
+    );
+  }
+
+  #[test]
+  fn runtime_open_patterns() {
+    let scope = scope(&[]);
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    scope.listen(move |event| events_.lock().unwrap().push(format!("{:?}", event)));
+
+    assert!(matches!(
+      scope.validate_open_path("http://docs.tauri.app", None),
+      Err(ScopeError::PathValidation { .. })
+    ));
+    scope.allow_open(r"^http://[a-z]+\.tauri\.app").unwrap();
+    assert!(scope
+      .validate_open_path("http://docs.tauri.app", None)
+      .is_ok());
+    // denied schemes still take precedence
+    scope.allow_open("^javascript:").unwrap();
+    assert!(matches!(
+      scope.validate_open_path("javascript:alert(1)", None),
+      Err(ScopeError::DeniedScheme(_))
+    ));
+
+    scope.forbid_open(r"^https://evil\.tauri\.app").unwrap();
+    assert!(matches!(
+      scope.validate_open_path("https://evil.tauri.app/", None),
+      Err(ScopeError::OpenForbidden { pattern, .. }) if pattern == r"^https://evil\.tauri\.app"
+    ));
+    assert!(scope.validate_open_path("https://tauri.app", None).is_ok());
+
+    assert!(matches!(
+      scope.allow_open("(unclosed"),
+      Err(ScopeError::InvalidOpenPattern(_))
+    ));
+    assert_eq!(
+      *events.lock().unwrap(),
+      [
+        r#"OpenAllowed("^http://[a-z]+\\.tauri\\.app")"#,
+        r#"OpenAllowed("^javascript:")"#,
+        r#"OpenForbidden("^https://evil\\.tauri\\.app")"#,
+      ]
+    );
+  }
+
+  #[test]
+  fn open_patterns_compiled_once() {
+    let compiled = || COMPILED_OPEN_PATTERNS.with(std::cell::Cell::get);
+    let firefox = Program::Firefox.name();
+    let scope = scope_with_rules(&[("default", "^https://"), (firefox.as_ref(), "^https?://")])
+      .with_opener(std::sync::Arc::new(crate::test::MockShell::new()));
+    scope.allow_open(r"^http://[a-z]+\.tauri\.app").unwrap();
+    assert_eq!(compiled(), 1);
+
+    for _ in 0..100 {
+      assert!(scope.is_allowed("https://tauri.app", None).is_ok());
+      assert!(scope.is_allowed("http://docs.tauri.app", None).is_ok());
+      assert!(scope
+        .is_allowed("http://github.com", Some(&Program::Firefox))
+        .is_ok());
+      assert!(scope.is_allowed("ftp://tauri.app", None).is_err());
+      assert!(scope.open("http://docs.tauri.app", None).is_ok());
+      // clones of the scope share the compiled patterns
+      assert!(scope.clone().open("https://tauri.app", None).is_ok());
+    }
+    assert_eq!(compiled(), 1);
+
+    // invalid patterns fail when added, not when validating
+    assert!(matches!(
+      scope.allow_open("(unclosed"),
+      Err(ScopeError::InvalidOpenPattern(_))
+    ));
+    assert!(scope.is_allowed("https://tauri.app", None).is_ok());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn runtime_forbidden_local_paths() {
+    let (_dir, allowed) = local_files();
+    let outside = allowed.parent().unwrap().join("outside");
+    let scope = scope_with("^https://", ShellOpenLocalScope::Fs, &[]);
+    scope
+      .fs
+      .allow_directory(allowed.parent().unwrap(), true)
+      .unwrap();
+    let link = allowed.join("link.txt");
+    assert!(scope
+      .validate_open_target(&link.to_string_lossy(), None)
+      .is_ok());
+
+    // the deny list is matched against the canonical path, even if the fs scope allows it
+    scope
+      .forbid_open(&format!("^{}", regex::escape(&outside.to_string_lossy())))
+      .unwrap();
+    for target in [
+      link.to_string_lossy().into_owned(),
+      url::Url::from_file_path(&link).unwrap().to_string(),
+      outside.join("secret.txt").to_string_lossy().into_owned(),
+    ] {
+      assert!(
+        matches!(
+          scope.validate_open_target(&target, None),
+          Err(ScopeError::OpenForbidden { .. })
+        ),
+        "{} should be forbidden",
+        target
+      );
+    }
+    assert!(scope
+      .validate_open_target(&allowed.join("file.txt").to_string_lossy(), None)
+      .is_ok());
+  }
+
+  #[test]
+  fn runtime_open_patterns_concurrent() {
+    let scope = scope(&[]);
+    let readers = (0..4)
+      .map(|_| {
+        let scope = scope.clone();
+        std::thread::spawn(move || {
+          // once the mutation is observed, it must stay observed
+          let mut allowed = false;
+          for _ in 0..1000 {
+            match scope.validate_open_path("http://tauri.app", None) {
+              Ok(()) => allowed = true,
+              Err(ScopeError::PathValidation { .. }) => assert!(!allowed),
+              Err(e) => panic!("unexpected error {}", e),
+            }
+          }
+        })
+      })
+      .collect::<Vec<_>>();
+    scope.allow_open("^http://tauri\\.app").unwrap();
+    for reader in readers {
+      reader.join().unwrap();
+    }
+    assert!(scope.validate_open_path("http://tauri.app", None).is_ok());
+  }
+
+  #[test]
+  fn open_hook() {
+    use crate::api::shell::{OpenDecision, OpenRequest};
+    use std::sync::{Arc, Mutex};
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_ = requests.clone();
+    let hook = move |request: OpenRequest<'_>| {
+      requests_.lock().unwrap().push((
+        request.target.to_string(),
+        request.program.map(ToString::to_string),
+        request.window.map(ToString::to_string),
+      ));
+      OpenDecision::Deny {
+        reason: "leaving the app".into(),
+      }
+    };
+    let scope = scope(&["code"]).with_open_hook(Some(Arc::new(hook)));
+
+    assert!(matches!(
+      scope.open_from_window(
+        "https://tauri.app",
+        Some(Program::Custom("code".into())),
+        OpenOptions::default(),
+        Some("main"),
+      ),
+      Err(ScopeError::OpenDenied { reason, .. }) if reason == "leaving the app"
+    ));
+    assert!(matches!(
+      scope.open_all(&["https://tauri.app", "https://github.com"], None),
+      Err(ScopeError::OpenDenied { .. })
+    ));
+    // the hook only sees validated requests
+    assert!(matches!(
+      scope.open("http://tauri.app", None),
+      Err(ScopeError::PathValidation { .. })
+    ));
+    // and is not consulted by `is_allowed`
+    assert!(scope.is_allowed("https://tauri.app", None).is_ok());
+
+    assert_eq!(
+      *requests.lock().unwrap(),
+      [
+        (
+          "https://tauri.app".to_string(),
+          Some("code".to_string()),
+          Some("main".to_string())
+        ),
+        ("https://tauri.app".to_string(), None, None),
+      ]
+    );
+  }
+
+  #[test]
+  fn open_wait_requires_program() {
+    let scope = scope(&[]);
+    assert!(matches!(
+      scope.open_with_options("https://tauri.app", None, OpenOptions::new().wait(true)),
+      Err(ScopeError::WaitRequiresProgram)
+    ));
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn open_wait_returns_status() {
+    let scope = scope_with("^0\\.3$", ShellOpenLocalScope::Open, &["sleep"]);
+    let outcome = scope
+      .open_with_options(
+        "0.3",
+        Some(Program::Custom("sleep".into())),
+        OpenOptions::new().wait(true).detach(true),
+      )
+      .unwrap();
+    assert!(outcome.program.is_none());
+    assert!(outcome.status.unwrap().success());
+  }
+
+  #[test]
+  fn show_in_folder_requires_local_path() {
+    assert!(matches!(
+      scope(&[]).show_in_folder("https://tauri.app"),
+      Err(ScopeError::NotLocalPath(_))
+    ));
+  }
+
+  #[test]
+  fn show_in_folder_nonexistent() {
+    let (_dir, allowed) = local_files();
+    let scope = scope_with(&allowed_dir_regex(&allowed), ShellOpenLocalScope::Open, &[]);
+    let missing = allowed.join("missing.txt");
+    assert!(matches!(
+      scope.show_in_folder(&missing.to_string_lossy()),
+      Err(ScopeError::LocalPath(path, _)) if path == missing
+    ));
+  }
+
+  #[test]
+  fn open_args() {
+    let mut scope = scope(&[]);
+    let firefox = Program::Firefox.name().into_owned();
+    scope.config.open_allowed_args = std::collections::HashMap::from([(
+      firefox.clone(),
+      vec![
+        regex::Regex::new("^(?:--private-window)$").unwrap(),
+        regex::Regex::new("^(?:-P=[a-z]+)$").unwrap(),
+      ],
+    )]);
+
+    assert!(scope
+      .check_args(&firefox, &["--private-window".into(), "-P=work".into()])
+      .is_ok());
+    assert!(matches!(
+      scope.check_args(&firefox, &["--load-extension=evil".into()]),
+      Err(ScopeError::ArgNotAllowed { arg, .. }) if arg == "--load-extension=evil"
+    ));
+    assert!(matches!(
+      scope.check_args(&firefox, &["-P=work --load-extension".into()]),
+      Err(ScopeError::ArgNotAllowed { .. })
+    ));
+    // programs without patterns take no extra arguments
+    let chrome = Program::Chrome.name();
+    assert!(scope.check_args(&chrome, &[]).is_ok());
+    assert!(matches!(
+      scope.check_args(&chrome, &["--private-window".into()]),
+      Err(ScopeError::ArgNotAllowed { program, .. }) if program == chrome
+    ));
+    assert!(matches!(
+      scope.open_with_options(
+        "https://tauri.app",
+        None,
+        OpenOptions::new().args(["--private-window"])
+      ),
+      Err(ScopeError::ArgsRequireProgram)
+    ));
+  }
+
+  #[cfg(not(any(windows, target_os = "macos")))]
+  #[test]
+  fn open_args_order() {
+    let command = super::open_command(
+      "firefox",
+      &["https://tauri.app", "https://github.com"],
+      &OpenOptions::new().args(["--private-window", "-P=work"]),
+    )
+    .unwrap();
+    let args: Vec<_> = command.get_args().collect();
+    assert_eq!(
+      args,
+      [
+        "--private-window",
+        "-P=work",
+        "https://tauri.app",
+        "https://github.com"
+      ]
+    );
+  }
+
+  #[test]
+  fn allowed_programs() {
+    let mut scope = scope(&["code"]);
+    assert!(scope.check_program(&Program::Firefox.name(), false).is_ok());
+    assert!(scope.check_program("code", true).is_ok());
+
+    let firefox = Program::Firefox.name().into_owned();
+    scope.config.open_allowed_programs = Some(vec![firefox.clone()]);
+    assert!(scope.check_program(&firefox, false).is_ok());
+    for program in [Program::Start, Program::Open, Program::Chrome] {
+      let name = program.name();
+      assert!(matches!(
+        scope.check_program(&name, false),
+        Err(ScopeError::ProgramNotInAllowedPrograms(n)) if n == name
+      ));
+    }
+    // custom programs must be listed in both
+    assert!(matches!(
+      scope.check_program("code", true),
+      Err(ScopeError::ProgramNotInAllowedPrograms(_))
+    ));
+    scope.config.open_allowed_programs = Some(vec!["code".into()]);
+    assert!(scope.check_program("code", true).is_ok());
+    assert!(matches!(
+      scope.check_program("vim", true),
+      Err(ScopeError::ProgramNotAllowed(_))
+    ));
+  }
+
+  #[test]
+  fn allowed_programs_resolved_from_config() {
+    let mut config = scope(&[]).config;
+    config.open_allowed_programs = Some(vec!["FireFox".into(), "code".into()]);
+    let scope = Scope::new(
+      &Default::default(),
+      &crate::PackageInfo {
+        name: "test".into(),
+        version: "0.1.0".parse().unwrap(),
+        authors: "Tauri",
+        description: "Tauri test",
+      },
+      &Default::default(),
+      config,
+      fs_scope(),
+    );
+    assert_eq!(
+      scope.config.open_allowed_programs,
+      Some(vec![Program::Firefox.name().into_owned(), "code".into()])
+    );
+    // the default handler is governed by the validation only
+    assert!(scope
+      .validate_open_target("https://tauri.app", None)
+      .is_ok());
+  }
+
+  #[test]
+  fn serialize_validation_error() {
+    let err = scope(&[]).open("http://tauri.app", None).unwrap_err();
+    assert_eq!(
+      serde_json::to_value(&err).unwrap(),
+      serde_json::json!({
+        "kind": "pathValidation",
+        "message": "Path http://tauri.app does not match the shell open validation regex ^https://",
+      })
+    );
+  }
+
+  // `tauri-missing-program` is reported as not found instead of being opened, so `open` can be
+  // compared against `is_allowed` without launching anything
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn is_allowed_matches_open() {
+    let scope = scope(&["tauri-missing-program"]);
+    scope.forbid_open("^https://evil\\.").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("file.txt");
+    std::fs::write(&file, "").unwrap();
+    let file = file.to_string_lossy().into_owned();
+    let file_url = format!("file://{}", file);
+
+    let corpus = [
+      "https://tauri.app",
+      "HTTPS://tauri.app/?a=1&b=2",
+      "http://tauri.app",
+      "https://evil.tauri.app",
+      "javascript:alert(1)",
+      " javascript:alert(1)",
+      "java\tscript:alert(1)",
+      "data:text/html,hi",
+      "file:/etc/passwd",
+      "mailto:hi@tauri.app",
+      "tauri.app",
+      "",
+      "/does/not/exist",
+      "file:///does/not/exist",
+      file.as_str(),
+      file_url.as_str(),
+    ];
+    for path in corpus {
+      for with in [None, Some("tauri-missing-program"), Some("other-program")] {
+        let program = || with.map(|name| Program::Custom(name.into()));
+        let allowed = scope.is_allowed(path, program().as_ref());
+        if allowed.is_ok() && with.is_none() {
+          // the default handler would actually open the path
+          continue;
+        }
+        match (allowed, scope.open(path, program())) {
+          (Ok(()), Err(ScopeError::ProgramNotFound { .. })) => {}
+          (Err(allowed), Err(opened)) => assert_eq!(
+            allowed.kind(),
+            opened.kind(),
+            "{:?} with {:?}: {} / {}",
+            path,
+            with,
+            allowed,
+            opened
+          ),
+          (allowed, opened) => panic!(
+            "{:?} with {:?}: is_allowed {:?}, open {:?}",
+            path,
+            with,
+            allowed.err().map(|e| e.to_string()),
+            opened.err().map(|e| e.to_string())
+          ),
+        }
       }
     }
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn serialize_program_not_found_error() {
+    let err = super::launch_program(
+      "tauri-missing-program",
+      &["https://tauri.app"],
+      &OpenOptions::default(),
+      &[],
+    )
+    .unwrap_err();
+    assert_eq!(
+      serde_json::to_value(&err).unwrap(),
+      serde_json::json!({
+        "kind": "programNotFound",
+        "message": "Program tauri-missing-program not found",
+      })
+    );
+  }
+
+  fn scope_with_schemes(schemes: &[&str]) -> Scope {
+    let mut scope = scope(&[]);
+    scope.config.open = None;
+    scope.config.open_schemes = schemes.iter().map(|s| s.to_string()).collect();
+    scope
+  }
 
-    // The prevention of argument escaping is handled by the usage of std::process::Command::arg by
-    // the `open` dependency. This behavior should be re-confirmed during upgrades of `open`.
-    match with.map(Program::name) {
-      Some(program) => ::open::with(path, program),
-      None => ::open::that(path),
+  #[test]
+  fn sanitized_env_vars() {
+    // `Env` is non-exhaustive, so it can only be changed field by field
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    let mut env = tauri_utils::Env::default();
+    #[cfg(target_os = "linux")]
+    {
+      env.appimage = None;
     }
-    .map_err(Into::into)
+    assert!(super::sanitized_env_vars(&env, &["MY_VAR".into()]).is_empty());
+
+    #[cfg(target_os = "linux")]
+    {
+      env.appimage = Some("/tmp/app.AppImage".into());
+      let vars = super::sanitized_env_vars(&env, &["MY_VAR".into()]);
+      assert!(vars.iter().any(|name| name == "LD_LIBRARY_PATH"));
+      assert_eq!(vars.last().map(String::as_str), Some("MY_VAR"));
+    }
+  }
+
+  #[test]
+  fn default_schemes() {
+    let scope = scope_with_schemes(&["https", "http", "mailto", "tel"]);
+    for path in [
+      "https://tauri.app",
+      "HTTP://tauri.app",
+      "mailto:support@example.com",
+      "Tel:+1-555-0100",
+    ] {
+      assert!(scope.validate_open_path(path, None).is_ok(), "{}", path);
+    }
+    for path in [
+      "ftp://example.com",
+      "myapp-companion://open",
+      "--enable-debugging",
+      "/R",
+      "",
+    ] {
+      assert!(
+        matches!(
+          scope.validate_open_path(path, None),
+          Err(ScopeError::SchemeNotAllowed(_))
+        ),
+        "{}",
+        path
+      );
+    }
+  }
+
+  #[test]
+  fn additional_schemes() {
+    let scope = scope_with_schemes(&["https", "myapp-companion"]);
+    assert!(scope
+      .validate_open_path("MyApp-Companion://open", None)
+      .is_ok());
+  }
+
+  #[test]
+  fn denied_schemes() {
+    // a sloppy regex doesn't let dangerous schemes through
+    let scope = scope_with(".*", ShellOpenLocalScope::Open, &[]);
+    for path in [
+      "javascript:alert(1)",
+      "JavaScript:alert(1)",
+      " javascript:alert(1)",
+      "java\tscript:alert(1)",
+      "vbscript:msgbox(1)",
+      "data:text/html,<script>alert(1)</script>",
+      "file:/etc/passwd",
+    ] {
+      assert!(
+        matches!(
+          scope.validate_open_path(path, None),
+          Err(ScopeError::DeniedScheme(_))
+        ),
+        "{}",
+        path
+      );
+    }
+    assert!(scope.validate_open_path("https://tauri.app", None).is_ok());
+
+    let scope = scope_with_schemes(&["javascript"]);
+    assert!(matches!(
+      scope.validate_open_path("javascript:alert(1)", None),
+      Err(ScopeError::DeniedScheme(_))
+    ));
   }
 }
@@ -0,0 +1,449 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Fakes of the desktop session, so code using the portals and the shell can be tested without one.
+
+use std::{
+  collections::BTreeMap,
+  sync::{Arc, Mutex},
+};
+
+use crate::portal::PortalBus;
+
+/// The fakes an app built with [`crate::Builder::mock_portal`] and [`crate::Builder::mock_shell`] uses.
+#[derive(Default)]
+pub(crate) struct Mocks {
+  pub(crate) portal: Option<MockPortal>,
+  #[cfg(feature = "shell-open-api")]
+  pub(crate) shell: Option<MockShell>,
+}
+
+/// A call to the portal service recorded by [`MockPortal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortalCall {
+  /// The portal object was introspected for its interfaces.
+  Introspect,
+  /// The `version` property of the interface was read.
+  Version(String),
+  /// The `ProxyResolver` portal was asked for the proxies of the URI.
+  ProxyLookup(String),
+}
+
+#[derive(Debug, Default)]
+struct MockPortalInner {
+  available: bool,
+  /// The interfaces by name, with the version they answer with.
+  interfaces: BTreeMap<String, Option<u32>>,
+  /// The proxies the `ProxyResolver` portal returns, by URI prefix.
+  proxies: BTreeMap<String, Vec<String>>,
+  calls: Vec<PortalCall>,
+}
+
+/// A scripted `org.freedesktop.portal.Desktop` service, see [`crate::Builder::mock_portal`].
+///
+/// Clones share the script and the recorded calls, so a test keeps a clone to assert on.
+#[derive(Debug, Clone)]
+pub struct MockPortal {
+  inner: Arc<Mutex<MockPortalInner>>,
+}
+
+impl MockPortal {
+  /// A portal service that is reachable but doesn't provide any interface.
+  pub fn new() -> Self {
+    Self {
+      inner: Arc::new(Mutex::new(MockPortalInner {
+        available: true,
+        ..Default::default()
+      })),
+    }
+  }
+
+  /// A session without a portal service.
+  pub fn unavailable() -> Self {
+    Self {
+      inner: Default::default(),
+    }
+  }
+
+  /// Provides the interface at the version.
+  #[must_use]
+  pub fn interface(self, name: &str, version: u32) -> Self {
+    self
+      .inner
+      .lock()
+      .unwrap()
+      .interfaces
+      .insert(name.into(), Some(version));
+    self
+  }
+
+  /// Provides the interface without answering for its version, like a timed out call.
+  #[must_use]
+  pub fn unversioned_interface(self, name: &str) -> Self {
+    self
+      .inner
+      .lock()
+      .unwrap()
+      .interfaces
+      .insert(name.into(), None);
+    self
+  }
+
+  /// Returns the proxies for the URIs starting with the prefix, other URIs get `direct://`.
+  #[must_use]
+  pub fn proxy(self, uri_prefix: &str, proxies: &[&str]) -> Self {
+    self.inner.lock().unwrap().proxies.insert(
+      uri_prefix.into(),
+      proxies.iter().map(|proxy| proxy.to_string()).collect(),
+    );
+    self
+  }
+
+  /// The calls made to the service so far.
+  pub fn calls(&self) -> Vec<PortalCall> {
+    self.inner.lock().unwrap().calls.clone()
+  }
+}
+
+impl PortalBus for MockPortal {
+  fn introspect(&self) -> Option<String> {
+    let mut inner = self.inner.lock().unwrap();
+    inner.calls.push(PortalCall::Introspect);
+    if !inner.available {
+      return None;
+    }
+    let mut xml = String::from("<node>\n");
+    for name in inner.interfaces.keys() {
+      xml.push_str(&format!(
+        "  <interface name=\"{}\">\n  </interface>\n",
+        name
+      ));
+    }
+    xml.push_str("</node>\n");
+    Some(xml)
+  }
+
+  fn version(&self, interface: &str) -> Option<u32> {
+    let mut inner = self.inner.lock().unwrap();
+    inner.calls.push(PortalCall::Version(interface.into()));
+    *inner.interfaces.get(interface)?
+  }
+
+  fn proxy_lookup(&self, uri: &str) -> Option<Vec<String>> {
+    let mut inner = self.inner.lock().unwrap();
+    inner.calls.push(PortalCall::ProxyLookup(uri.into()));
+    if !inner.available {
+      return None;
+    }
+    let proxies = inner
+      .proxies
+      .iter()
+      .find(|(prefix, _)| uri.starts_with(prefix.as_str()))
+      .map(|(_, proxies)| proxies.clone())
+      .unwrap_or_else(|| vec!["direct://".into()]);
+    Some(proxies)
+  }
+}
+
+#[cfg(feature = "shell-open-api")]
+pub use shell::{MockOpen, MockOpenResponse, MockShell};
+
+#[cfg(feature = "shell-open-api")]
+mod shell {
+  use std::{
+    collections::HashMap,
+    process::ExitStatus,
+    sync::{Arc, Mutex},
+  };
+
+  use crate::{
+    api::shell::{OpenMechanism, OpenOptions, OpenOutcome},
+    scope::{ShellScopeConfig, ShellScopeError},
+  };
+
+  /// A target handed to [`MockShell`] to open.
+  #[derive(Debug, Clone, PartialEq, Eq)]
+  pub struct MockOpen {
+    /// The validated targets, canonicalized for local paths.
+    ///
+    /// More than one when a program accepting multiple paths opens them at once.
+    pub targets: Vec<String>,
+    /// The resolved program name, or `None` for the system default handler.
+    pub program: Option<String>,
+    /// The extra arguments passed to the program.
+    pub args: Vec<String>,
+    /// Whether the program was waited for.
+    pub wait: bool,
+    /// Whether the program was launched detached.
+    pub detach: bool,
+  }
+
+  /// How [`MockShell`] answers an open.
+  #[derive(Debug, Clone, PartialEq, Eq)]
+  pub enum MockOpenResponse {
+    /// The target was opened, a waited program exits successfully.
+    Opened,
+    /// The program exits with the code, when waited for.
+    Exited(i32),
+    /// The program isn't installed.
+    ProgramNotFound,
+    /// Opening failed with the error.
+    Failed(std::io::ErrorKind),
+  }
+
+  #[derive(Debug, Default)]
+  struct MockShellInner {
+    permissive: bool,
+    /// The responses by target, then by program.
+    targets: HashMap<String, MockOpenResponse>,
+    programs: HashMap<String, MockOpenResponse>,
+    calls: Vec<MockOpen>,
+  }
+
+  /// A fake of the system shell that records what `shell > open` would open, see
+  /// [`crate::Builder::mock_shell`].
+  ///
+  /// The targets are still validated by the shell scope, only what passes reaches the mock, which
+  /// answers [`MockOpenResponse::Opened`] unless scripted otherwise. Clones share the script and
+  /// the recorded opens.
+  #[derive(Debug, Clone, Default)]
+  pub struct MockShell {
+    inner: Arc<Mutex<MockShellInner>>,
+  }
+
+  impl MockShell {
+    /// A shell validating the targets with the configured scope.
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    /// A shell allowing every URL and local path, replacing the configured validation regex.
+    ///
+    /// Denied schemes such as `javascript:` are still rejected.
+    pub fn permissive() -> Self {
+      let shell = Self::default();
+      shell.inner.lock().unwrap().permissive = true;
+      shell
+    }
+
+    /// Answers opens of the target with the response.
+    #[must_use]
+    pub fn respond(self, target: &str, response: MockOpenResponse) -> Self {
+      self
+        .inner
+        .lock()
+        .unwrap()
+        .targets
+        .insert(target.into(), response);
+      self
+    }
+
+    /// Answers launches of the program with the response, unless the target has its own.
+    #[must_use]
+    pub fn respond_to_program(self, program: &str, response: MockOpenResponse) -> Self {
+      self
+        .inner
+        .lock()
+        .unwrap()
+        .programs
+        .insert(program.into(), response);
+      self
+    }
+
+    /// The opens so far.
+    pub fn calls(&self) -> Vec<MockOpen> {
+      self.inner.lock().unwrap().calls.clone()
+    }
+
+    /// The scope configuration the app uses with this shell.
+    pub(crate) fn scope_config(&self, mut config: ShellScopeConfig) -> ShellScopeConfig {
+      if self.inner.lock().unwrap().permissive {
+        config.open = Some(regex::Regex::new(".*").unwrap());
+        config.open_rules.clear();
+        config.open_local_scope = crate::utils::config::ShellOpenLocalScope::Open;
+      }
+      config
+    }
+
+    fn record(
+      &self,
+      targets: &[&str],
+      program: Option<&str>,
+      options: &OpenOptions,
+    ) -> Result<OpenOutcome, ShellScopeError> {
+      let mut inner = self.inner.lock().unwrap();
+      inner.calls.push(MockOpen {
+        targets: targets.iter().map(ToString::to_string).collect(),
+        program: program.map(Into::into),
+        args: options.args.clone(),
+        wait: options.wait,
+        detach: options.detach,
+      });
+      let response = targets
+        .first()
+        .and_then(|target| inner.targets.get(*target))
+        .or_else(|| program.and_then(|program| inner.programs.get(program)))
+        .cloned()
+        .unwrap_or(MockOpenResponse::Opened);
+      let code = match response {
+        MockOpenResponse::Opened => 0,
+        MockOpenResponse::Exited(code) => code,
+        MockOpenResponse::ProgramNotFound => {
+          return Err(ShellScopeError::ProgramNotFound {
+            name: program.unwrap_or_default().into(),
+          })
+        }
+        MockOpenResponse::Failed(kind) => return Err(std::io::Error::from(kind).into()),
+      };
+      Ok(OpenOutcome {
+        mechanism: match program {
+          Some(program) => OpenMechanism::Launched(program.into()),
+          None => OpenMechanism::Default,
+        },
+        program: None,
+        status: options.wait.then(|| exit_status(code)),
+      })
+    }
+  }
+
+  impl crate::scope::Opener for MockShell {
+    fn launch(
+      &self,
+      program: &str,
+      paths: &[&str],
+      options: &OpenOptions,
+      _sanitize_env: &[String],
+    ) -> Result<OpenOutcome, ShellScopeError> {
+      self.record(paths, Some(program), options)
+    }
+
+    fn open_default(&self, path: &str) -> Result<OpenOutcome, ShellScopeError> {
+      self.record(&[path], None, &OpenOptions::default())
+    }
+  }
+
+  #[cfg(unix)]
+  fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+  }
+
+  #[cfg(windows)]
+  fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{MockPortal, PortalCall};
+  use crate::{
+    test::{mock_context, noop_assets, MockRuntime},
+    Builder,
+  };
+
+  const FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+  const NOTIFICATION: &str = "org.freedesktop.portal.Notification";
+
+  #[test]
+  fn portal_state() {
+    let portal = MockPortal::new()
+      .interface(FILE_CHOOSER, 3)
+      .unversioned_interface(NOTIFICATION);
+    let app = Builder::<MockRuntime>::new()
+      .mock_portal(portal.clone())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+
+    let state = app.portal_state();
+    assert!(state.portal_available);
+    assert!(state.file_chooser);
+    assert!(state.notification);
+    assert!(!state.global_shortcuts);
+    assert_eq!(state.interfaces[NOTIFICATION], 1);
+
+    // probed once per app
+    assert_eq!(app.handle().portal_state(), state);
+    assert_eq!(
+      portal.calls(),
+      [
+        PortalCall::Introspect,
+        PortalCall::Version(FILE_CHOOSER.into()),
+        PortalCall::Version(NOTIFICATION.into()),
+      ]
+    );
+  }
+
+  #[test]
+  fn portal_unavailable() {
+    let portal = MockPortal::unavailable();
+    let app = Builder::<MockRuntime>::new()
+      .mock_portal(portal.clone())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+
+    let state = app.portal_state();
+    assert!(!state.portal_available);
+    assert!(!state.file_chooser);
+    assert_eq!(portal.calls(), [PortalCall::Introspect]);
+  }
+
+  #[cfg(feature = "shell-open-api")]
+  #[test]
+  fn shell_open() {
+    use super::{MockOpen, MockOpenResponse, MockShell};
+    use crate::{
+      api::shell::{OpenMechanism, OpenOptions, Program},
+      scope::ShellScopeError,
+      Manager,
+    };
+
+    let firefox = Program::Firefox.name().into_owned();
+    let shell = MockShell::permissive()
+      .respond_to_program(&firefox, MockOpenResponse::ProgramNotFound)
+      .respond("https://github.com", MockOpenResponse::Exited(1));
+    let app = Builder::<MockRuntime>::new()
+      .mock_shell(shell.clone())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    let scope = app.shell_scope();
+
+    let outcome = scope.open("https://tauri.app", None).unwrap();
+    assert_eq!(outcome.mechanism, OpenMechanism::Default);
+    assert!(matches!(
+      scope.open("https://tauri.app", Some(Program::Firefox)),
+      Err(ShellScopeError::ProgramNotFound { name }) if name == firefox
+    ));
+    let outcome = scope
+      .open_with_options(
+        "https://github.com",
+        Some(Program::Firefox),
+        OpenOptions::new().wait(true),
+      )
+      .unwrap();
+    assert_eq!(outcome.status.and_then(|status| status.code()), Some(1));
+    // rejected before reaching the shell
+    assert!(matches!(
+      scope.open("javascript:alert(1)", None),
+      Err(ShellScopeError::DeniedScheme(_))
+    ));
+
+    let open = |target: &str, program: Option<&str>, wait: bool| MockOpen {
+      targets: vec![target.into()],
+      program: program.map(Into::into),
+      args: Vec::new(),
+      wait,
+      detach: false,
+    };
+    assert_eq!(
+      shell.calls(),
+      [
+        open("https://tauri.app", None, false),
+        open("https://tauri.app", Some(&firefox), false),
+        open("https://github.com", Some(&firefox), true),
+      ]
+    );
+  }
+}
@@ -4,7 +4,9 @@
 
 #![allow(unused_variables)]
 
+mod mock_desktop;
 mod mock_runtime;
+pub use mock_desktop::*;
 pub use mock_runtime::*;
 
 #[cfg(shell_scope)]
@@ -80,6 +82,13 @@ pub fn mock_context<A: Assets>(assets: A) -> crate::Context<A> {
     #[cfg(shell_scope)]
     shell_scope: ShellScopeConfig {
       open: None,
+      open_rules: HashMap::new(),
+      open_schemes: Vec::new(),
+      open_custom_programs: Vec::new(),
+      open_allowed_programs: None,
+      open_allowed_args: HashMap::new(),
+      open_local_scope: Default::default(),
+      open_sanitize_env: Vec::new(),
       scopes: HashMap::new(),
     },
   }
@@ -91,6 +100,7 @@ pub fn mock_app() -> crate::App<MockRuntime> {
     .unwrap()
 }
 
+#[cfg(test)]
 pub(crate) fn mock_invoke_context() -> crate::endpoints::InvokeContext<MockRuntime> {
   let app = mock_app();
   crate::endpoints::InvokeContext {
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::error::{Error, Result};
+use super::{
+  error::{Error, Result},
+  flatpak::RemoteCommit,
+  UpdateInstall, UpdaterEnvironment, UpdaterInstallHook,
+};
 #[cfg(desktop)]
 use crate::api::file::{ArchiveFormat, Extract, Move};
 use crate::{
@@ -17,7 +21,7 @@ use http::{
 use minisign_verify::{PublicKey, Signature};
 use semver::Version;
 use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize};
-use tauri_utils::{platform::current_exe, Env};
+use tauri_utils::{config::UpdaterSource, platform::current_exe, Env};
 use time::OffsetDateTime;
 use url::Url;
 
@@ -317,6 +321,10 @@ impl<R: Runtime> UpdateBuilder<R> {
   }
 
   pub async fn build(mut self) -> Result<Update<R>> {
+    if self.app.config().tauri.updater.source == UpdaterSource::Flatpak {
+      return self.build_flatpak().await;
+    }
+
     let mut remote_release: Option<RemoteRelease> = None;
 
     // make sure we have at least one url
@@ -438,15 +446,72 @@ impl<R: Runtime> UpdateBuilder<R> {
       version: final_release.version().to_string(),
       date: final_release.pub_date().cloned(),
       current_version: self.current_version,
-      download_url: final_release.download_url(&json_target)?.to_owned(),
       body: final_release.notes().cloned(),
-      signature: final_release.signature(&json_target)?.to_owned(),
-      #[cfg(target_os = "windows")]
-      with_elevated_task: final_release.with_elevated_task(&json_target)?,
+      commit: None,
+      environment: UpdaterEnvironment::detect(&self.app.state::<Env>()),
+      install: Install::Download {
+        url: final_release.download_url(&json_target)?.to_owned(),
+        signature: final_release.signature(&json_target)?.to_owned(),
+        #[cfg(target_os = "windows")]
+        with_elevated_task: final_release.with_elevated_task(&json_target)?,
+      },
       timeout: self.timeout,
       headers,
     })
   }
+
+  /// Checks the remote the Flatpak was installed from instead of the update servers.
+  ///
+  /// Any failure means no update, the app may lack the permission to talk to Flatpak.
+  async fn build_flatpak(self) -> Result<Update<R>> {
+    #[cfg(target_os = "linux")]
+    let latest = match self.app.state::<Env>().flatpak.clone() {
+      Some(info) => crate::async_runtime::spawn_blocking(move || super::flatpak::check(&info))
+        .await
+        .ok()
+        .flatten(),
+      None => None,
+    };
+    #[cfg(not(target_os = "linux"))]
+    let latest: Option<RemoteCommit> = None;
+
+    let executable_path = self.executable_path.unwrap_or(current_exe()?);
+    let extract_path = extract_path_from_executable(&self.app.state::<Env>(), &executable_path);
+    let (version, body, date, commit) = match latest {
+      Some(RemoteCommit {
+        commit,
+        version,
+        subject,
+        date,
+      }) => (
+        version.unwrap_or_else(|| commit.clone()),
+        subject,
+        date,
+        Some(commit),
+      ),
+      None => (self.current_version.to_string(), None, None, None),
+    };
+
+    let environment = UpdaterEnvironment::detect(&self.app.state::<Env>());
+    Ok(Update {
+      app: self.app,
+      target: self
+        .target
+        .or_else(|| get_updater_target().map(Into::into))
+        .unwrap_or_default(),
+      extract_path,
+      should_update: commit.is_some(),
+      version,
+      date,
+      current_version: self.current_version,
+      body,
+      commit,
+      environment,
+      install: Install::Restart,
+      timeout: self.timeout,
+      headers: self.headers,
+    })
+  }
 }
 
 pub fn builder<R: Runtime>(app: AppHandle<R>) -> UpdateBuilder<R> {
@@ -467,19 +532,17 @@ pub struct Update<R: Runtime> {
   pub current_version: Version,
   /// Update publish date
   pub date: Option<OffsetDateTime>,
+  /// Commit announced by the Flatpak remote
+  pub commit: Option<String>,
+  /// The environment the update is installed in
+  pub environment: UpdaterEnvironment,
   /// Target
   #[allow(dead_code)]
   target: String,
   /// Extract path
   extract_path: PathBuf,
-  /// Download URL announced
-  download_url: Url,
-  /// Signature announced
-  signature: String,
-  #[cfg(target_os = "windows")]
-  /// Optional: Windows only try to use elevated task
-  /// Default to false
-  with_elevated_task: bool,
+  /// How the update is installed
+  install: Install,
   /// Request timeout
   timeout: Option<Duration>,
   /// Request headers
@@ -495,19 +558,59 @@ impl<R: Runtime> Clone for Update<R> {
       version: self.version.clone(),
       current_version: self.current_version.clone(),
       date: self.date,
+      commit: self.commit.clone(),
+      environment: self.environment,
       target: self.target.clone(),
       extract_path: self.extract_path.clone(),
-      download_url: self.download_url.clone(),
-      signature: self.signature.clone(),
-      #[cfg(target_os = "windows")]
-      with_elevated_task: self.with_elevated_task,
+      install: self.install.clone(),
       timeout: self.timeout,
       headers: self.headers.clone(),
     }
   }
 }
 
+/// How an update is installed.
+#[derive(Debug, Clone)]
+enum Install {
+  /// Download the archive announced by the update server and replace the app with it.
+  Download {
+    url: Url,
+    signature: String,
+    /// Windows only, try to use the elevated task
+    #[cfg(target_os = "windows")]
+    with_elevated_task: bool,
+  },
+  /// Flatpak already deployed the update, restarting the app picks it up.
+  Restart,
+}
+
 impl<R: Runtime> Update<R> {
+  /// Hands the update to the [`crate::Builder::on_updater_install`] hook, where the updater can't
+  /// replace the app.
+  ///
+  /// Fails with [`Error::UnsupportedEnvironment`] unless `tauri > updater > customInstall` is set
+  /// and the app has the hook.
+  fn custom_install(&self) -> Result {
+    let hook = match self.app.try_state::<UpdaterInstallHook<R>>() {
+      Some(hook) if self.app.config().tauri.updater.custom_install => hook,
+      _ => return Err(Error::UnsupportedEnvironment),
+    };
+    (hook.inner().0)(
+      &self.app,
+      UpdateInstall {
+        version: self.version.clone(),
+        current_version: self.current_version.clone(),
+        body: self.body.clone(),
+        environment: self.environment,
+      },
+    )
+  }
+
+  /// Whether installing the update only takes restarting the app.
+  pub(crate) fn installs_on_restart(&self) -> bool {
+    matches!(self.install, Install::Restart)
+  }
+
   // Download and install our update
   // @todo(lemarier): Split into download and install (two step) but need to be thread safe
   pub(crate) async fn download_and_install<C: Fn(usize, Option<u64>), D: FnOnce()>(
@@ -516,13 +619,30 @@ impl<R: Runtime> Update<R> {
     on_chunk: C,
     on_download_finish: D,
   ) -> Result {
+    #[allow(unused_variables)]
+    let (download_url, signature, with_elevated_task) = match &self.install {
+      #[cfg(target_os = "windows")]
+      Install::Download {
+        url,
+        signature,
+        with_elevated_task,
+      } => (url, signature, *with_elevated_task),
+      #[cfg(not(target_os = "windows"))]
+      Install::Download { url, signature } => (url, signature, false),
+      Install::Restart => return Ok(()),
+    };
+
     // make sure we can install the update on linux
     // We fail here because later we can add more linux support
     // actually if we use APPIMAGE, our extract path should already
     // be set with our APPIMAGE env variable, we don't need to do
     // anything with it yet
+    // the Flatpak deployment is read-only, so check it before looking for an AppImage
+    if self.environment != UpdaterEnvironment::Native {
+      return self.custom_install();
+    }
     #[cfg(target_os = "linux")]
-    if self.app.state::<Env>().appimage.is_none() {
+    if self.app.state::<Env>().appimage_info.is_none() {
       return Err(Error::UnsupportedLinuxPackage);
     }
 
@@ -539,7 +659,7 @@ impl<R: Runtime> Update<R> {
 
     let client = ClientBuilder::new().build()?;
     // Create our request
-    let mut req = HttpRequestBuilder::new("GET", self.download_url.as_str())?.headers(headers);
+    let mut req = HttpRequestBuilder::new("GET", download_url.as_str())?.headers(headers);
     if let Some(timeout) = self.timeout {
       req = req.timeout(timeout);
     }
@@ -599,7 +719,7 @@ impl<R: Runtime> Update<R> {
 
     // We need an announced signature by the server
     // if there is no signature, bail out.
-    verify_signature(&mut archive_buffer, &self.signature, &pub_key)?;
+    verify_signature(&mut archive_buffer, signature, &pub_key)?;
 
     // TODO: implement updater in mobile
     #[cfg(desktop)]
@@ -611,7 +731,7 @@ impl<R: Runtime> Update<R> {
       copy_files_and_run(
         archive_buffer,
         &self.extract_path,
-        self.with_elevated_task,
+        with_elevated_task,
         self
           .app
           .config()
@@ -959,8 +1079,8 @@ pub fn extract_path_from_executable(env: &Env, executable_path: &Path) -> PathBu
   // We should use APPIMAGE exposed env variable
   // This is where our APPIMAGE should sit and should be replaced
   #[cfg(target_os = "linux")]
-  if let Some(app_image_path) = &env.appimage {
-    return PathBuf::from(app_image_path);
+  if let Some(appimage) = &env.appimage_info {
+    return appimage.path.clone();
   }
 
   extract_path
@@ -1138,9 +1258,13 @@ mod test {
 
     assert!(updater.should_update);
     assert_eq!(updater.version, "2.0.0");
-    assert_eq!(updater.signature, "dW50cnVzdGVkIGNvbW1lbnQ6IHNpZ25hdHVyZSBmcm9tIHRhdXJpIHNlY3JldCBrZXkKUldUTE5QWWxkQnlZOVJHMWlvTzRUSlQzTHJOMm5waWpic0p0VVI2R0hUNGxhQVMxdzBPRndlbGpXQXJJakpTN0toRURtVzBkcm15R0VaNTJuS1lZRWdzMzZsWlNKUVAzZGdJPQp0cnVzdGVkIGNvbW1lbnQ6IHRpbWVzdGFtcDoxNTkyOTE1NTIzCWZpbGU6RDpcYVx0YXVyaVx0YXVyaVx0YXVyaVxleGFtcGxlc1xjb21tdW5pY2F0aW9uXHNyYy10YXVyaVx0YXJnZXRcZGVidWdcYXBwLng2NC5tc2kuemlwCitXa1lQc3A2MCs1KzEwZnVhOGxyZ2dGMlZqbjBaVUplWEltYUdyZ255eUF6eVF1dldWZzFObStaVEQ3QU1RS1lzcjhDVU4wWFovQ1p1QjJXbW1YZUJ3PT0K");
+    let (download_url, signature) = match &updater.install {
+      Install::Download { url, signature, .. } => (url, signature),
+      Install::Restart => panic!("the update must be downloaded"),
+    };
+    assert_eq!(signature, "dW50cnVzdGVkIGNvbW1lbnQ6IHNpZ25hdHVyZSBmcm9tIHRhdXJpIHNlY3JldCBrZXkKUldUTE5QWWxkQnlZOVJHMWlvTzRUSlQzTHJOMm5waWpic0p0VVI2R0hUNGxhQVMxdzBPRndlbGpXQXJJakpTN0toRURtVzBkcm15R0VaNTJuS1lZRWdzMzZsWlNKUVAzZGdJPQp0cnVzdGVkIGNvbW1lbnQ6IHRpbWVzdGFtcDoxNTkyOTE1NTIzCWZpbGU6RDpcYVx0YXVyaVx0YXVyaVx0YXVyaVxleGFtcGxlc1xjb21tdW5pY2F0aW9uXHNyYy10YXVyaVx0YXJnZXRcZGVidWdcYXBwLng2NC5tc2kuemlwCitXa1lQc3A2MCs1KzEwZnVhOGxyZ2dGMlZqbjBaVUplWEltYUdyZ255eUF6eVF1dldWZzFObStaVEQ3QU1RS1lzcjhDVU4wWFovQ1p1QjJXbW1YZUJ3PT0K");
     assert_eq!(
-      updater.download_url.to_string(),
+      download_url.to_string(),
       "https://github.com/tauri-apps/updater-test/releases/download/v1.0.0/app.x64.msi.zip"
     );
   }
@@ -1667,4 +1791,73 @@ mod test {
 
     assert!(bin_file.exists());
   }
+
+  fn sandboxed_update(
+    app: AppHandle<crate::test::MockRuntime>,
+  ) -> Update<crate::test::MockRuntime> {
+    Update {
+      app,
+      body: Some("the notes".into()),
+      should_update: true,
+      version: "2.0.0".into(),
+      current_version: "1.0.0".parse().unwrap(),
+      date: None,
+      commit: None,
+      environment: UpdaterEnvironment::Flatpak,
+      target: "linux".into(),
+      extract_path: PathBuf::new(),
+      // never downloaded
+      install: Install::Download {
+        url: "https://tauri.app/update.tar.gz".parse().unwrap(),
+        signature: String::new(),
+        #[cfg(target_os = "windows")]
+        with_elevated_task: false,
+      },
+      timeout: None,
+      headers: HeaderMap::new(),
+    }
+  }
+
+  #[test]
+  fn sandboxed_install() {
+    use crate::test::{mock_context, noop_assets, MockRuntime};
+    use std::sync::{Arc, Mutex};
+
+    let installed = Arc::new(Mutex::new(Vec::new()));
+    let build = |custom_install: bool, with_hook: bool| {
+      let mut context = mock_context(noop_assets());
+      context.config_mut().tauri.updater.custom_install = custom_install;
+      let mut builder = crate::Builder::<MockRuntime>::new();
+      if with_hook {
+        let installed = installed.clone();
+        builder = builder.on_updater_install(move |_app, update| {
+          installed.lock().unwrap().push(update);
+          Ok(())
+        });
+      }
+      builder.build(context).unwrap()
+    };
+    let install = |app: &crate::App<MockRuntime>| {
+      block!(sandboxed_update(app.handle()).download_and_install(String::new(), |_, _| (), || ()))
+    };
+
+    // the updater can't replace the app and nothing is downloaded
+    for (custom_install, with_hook) in [(false, false), (false, true), (true, false)] {
+      let err = install(&build(custom_install, with_hook)).unwrap_err();
+      assert!(matches!(err, Error::UnsupportedEnvironment));
+      assert_eq!(
+        err.code(),
+        Some(super::super::ERROR_UNSUPPORTED_ENVIRONMENT)
+      );
+    }
+    assert!(installed.lock().unwrap().is_empty());
+
+    assert!(install(&build(true, true)).is_ok());
+    let installed = installed.lock().unwrap();
+    assert_eq!(installed.len(), 1);
+    assert_eq!(installed[0].version, "2.0.0");
+    assert_eq!(installed[0].current_version, "1.0.0".parse().unwrap());
+    assert_eq!(installed[0].body.as_deref(), Some("the notes"));
+    assert_eq!(installed[0].environment, UpdaterEnvironment::Flatpak);
+  }
 }
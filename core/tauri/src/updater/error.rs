@@ -44,6 +44,9 @@ pub enum Error {
   /// Updater cannot be executed on this Linux package. Currently the updater is enabled only on AppImages.
   #[error("Cannot run updater on this Linux package. Currently only an AppImage can be updated.")]
   UnsupportedLinuxPackage,
+  /// The app runs inside a Flatpak sandbox, where updates are installed by Flatpak itself.
+  #[error("Cannot run updater inside a Flatpak sandbox, the update must be installed with `flatpak update`.")]
+  UnsupportedEnvironment,
   /// Operating system is not supported.
   #[error("unsupported OS, expected one of `linux`, `darwin` or `windows`.")]
   UnsupportedOs,
@@ -71,4 +74,14 @@ pub enum Error {
   TempDirNotOnSameMountPoint,
 }
 
+impl Error {
+  /// The code sent to the frontend along with the error message, for errors apps need to tell apart.
+  pub fn code(&self) -> Option<&'static str> {
+    match self {
+      Self::UnsupportedEnvironment => Some(super::ERROR_UNSUPPORTED_ENVIRONMENT),
+      _ => None,
+    }
+  }
+}
+
 pub type Result<T = ()> = std::result::Result<T, Error>;
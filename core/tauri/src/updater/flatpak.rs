@@ -0,0 +1,162 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Checks the remote the Flatpak was installed from for a newer commit of the app.
+
+use time::OffsetDateTime;
+
+/// The latest commit of the app on its remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteCommit {
+  /// The commit checksum.
+  pub(crate) commit: String,
+  /// The version of the app from its AppStream metadata, if the remote publishes it.
+  pub(crate) version: Option<String>,
+  /// The commit subject.
+  pub(crate) subject: Option<String>,
+  /// The commit date.
+  pub(crate) date: Option<OffsetDateTime>,
+}
+
+/// Parses the output of `flatpak remote-info`.
+pub(crate) fn parse_remote_info(output: &str) -> Option<RemoteCommit> {
+  let date_format = time::format_description::parse(
+    "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]",
+  )
+  .ok()?;
+
+  let mut commit = None;
+  let mut version = None;
+  let mut subject = None;
+  let mut date = None;
+  for line in output.lines() {
+    if let Some((key, value)) = line.split_once(':') {
+      let value = value.trim();
+      if value.is_empty() {
+        continue;
+      }
+      match key.trim() {
+        "Commit" => commit = Some(value.to_string()),
+        "Version" => version = Some(value.to_string()),
+        "Subject" => subject = Some(value.to_string()),
+        "Date" => date = OffsetDateTime::parse(value, &date_format).ok(),
+        _ => {}
+      }
+    }
+  }
+  Some(RemoteCommit {
+    commit: commit?,
+    version,
+    subject,
+    date,
+  })
+}
+
+/// The remote commit, if it differs from the commit the app runs from.
+///
+/// Without the running commit there is nothing to compare against, so no update is reported.
+pub(crate) fn newer_commit(running: Option<&str>, remote: RemoteCommit) -> Option<RemoteCommit> {
+  match running {
+    Some(running) if running != remote.commit => Some(remote),
+    _ => None,
+  }
+}
+
+/// Looks up a newer commit of the app on the remote it was installed from.
+///
+/// Uses the remote metadata cached by Flatpak, so no network access is needed.
+/// Returns `None` when the app can't talk to Flatpak, has no remote or is up to date.
+#[cfg(target_os = "linux")]
+pub(crate) fn check(info: &tauri_utils::FlatpakInfo) -> Option<RemoteCommit> {
+  use crate::flatpak::try_host_flatpak;
+  use tauri_utils::debug_eprintln;
+
+  let app_ref = match info.app_ref() {
+    Some(app_ref) => app_ref,
+    None => {
+      debug_eprintln!("updater: /.flatpak-info has no complete ref of the app");
+      return None;
+    }
+  };
+  // fails without the `--talk-name=org.freedesktop.Flatpak` permission
+  let origin = match try_host_flatpak(&["info", "--show-origin", &app_ref]) {
+    Ok(origin) => origin,
+    Err(e) => {
+      debug_eprintln!("updater: failed to read the remote of {}: {}", app_ref, e);
+      return None;
+    }
+  };
+  let origin = origin.trim();
+  if origin.is_empty() {
+    debug_eprintln!("updater: {} was not installed from a remote", app_ref);
+    return None;
+  }
+  let remote = match try_host_flatpak(&["remote-info", "--cached", origin, &app_ref]) {
+    Ok(remote) => remote,
+    Err(e) => {
+      debug_eprintln!(
+        "updater: failed to read {} on the {} remote: {}",
+        app_ref,
+        origin,
+        e
+      );
+      return None;
+    }
+  };
+  let remote = match parse_remote_info(&remote) {
+    Some(remote) => remote,
+    None => {
+      debug_eprintln!("updater: the remote info of {} has no commit", app_ref);
+      return None;
+    }
+  };
+  newer_commit(info.app_commit.as_deref(), remote)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{newer_commit, parse_remote_info};
+
+  const REMOTE_INFO: &str = "
+My App - A Tauri app
+
+        ID: com.tauri.dev
+       Ref: app/com.tauri.dev/x86_64/stable
+      Arch: x86_64
+    Branch: stable
+   Version: 1.2.0
+   License: MIT
+  Download: 12.3 MB
+ Installed: 40.1 MB
+   Runtime: org.gnome.Platform/x86_64/43
+       Sdk: org.gnome.Sdk/x86_64/43
+
+    Commit: 8d1c3a1f6b5e
+    Parent: 2f0ab0c4e9d7
+   Subject: Update to 1.2.0
+      Date: 2022-12-01 10:30:00 +0000
+";
+
+  #[test]
+  fn remote_info() {
+    let commit = parse_remote_info(REMOTE_INFO).unwrap();
+    assert_eq!(commit.commit, "8d1c3a1f6b5e");
+    assert_eq!(commit.version.as_deref(), Some("1.2.0"));
+    assert_eq!(commit.subject.as_deref(), Some("Update to 1.2.0"));
+    assert_eq!(commit.date.unwrap().unix_timestamp(), 1_669_890_600);
+
+    assert!(parse_remote_info("        ID: com.tauri.dev\n").is_none());
+  }
+
+  #[test]
+  fn compares_commits() {
+    let remote = parse_remote_info(REMOTE_INFO).unwrap();
+    assert_eq!(
+      newer_commit(Some("2f0ab0c4e9d7"), remote.clone()),
+      Some(remote.clone())
+    );
+    assert_eq!(newer_commit(Some("8d1c3a1f6b5e"), remote.clone()), None);
+    assert_eq!(newer_commit(None, remote), None);
+  }
+}
@@ -57,6 +57,8 @@
 
 mod core;
 mod error;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod flatpak;
 
 use std::time::Duration;
 
@@ -68,7 +70,10 @@ pub use self::{core::RemoteRelease, error::Error};
 /// Alias for [`std::result::Result`] using our own [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
 
-use crate::{runtime::EventLoopProxy, AppHandle, EventLoopMessage, Manager, Runtime, UpdaterEvent};
+use crate::{
+  runtime::EventLoopProxy, utils::config::UpdaterSource, AppHandle, Env, EventLoopMessage, Manager,
+  Runtime, UpdaterEvent,
+};
 
 #[cfg(desktop)]
 use crate::api::dialog::blocking::ask;
@@ -105,6 +110,50 @@ pub const EVENT_STATUS_DOWNLOADED: &str = "DOWNLOADED";
 pub const EVENT_STATUS_SUCCESS: &str = "DONE";
 /// When you receive this status, this is because the application is running last version
 pub const EVENT_STATUS_UPTODATE: &str = "UPTODATE";
+/// The error code of [`Error::UnsupportedEnvironment`], sent in the `code` field of the status event.
+pub const ERROR_UNSUPPORTED_ENVIRONMENT: &str = "UNSUPPORTED_ENVIRONMENT";
+
+/// The environment the app runs in, which decides how updates are installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum UpdaterEnvironment {
+  /// The updater replaces the app with the downloaded update.
+  Native,
+  /// The app runs inside a Flatpak sandbox, where Flatpak installs the updates.
+  Flatpak,
+}
+
+impl UpdaterEnvironment {
+  #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+  pub(crate) fn detect(env: &Env) -> Self {
+    #[cfg(target_os = "linux")]
+    if env.flatpak.is_some() {
+      return Self::Flatpak;
+    }
+    Self::Native
+  }
+}
+
+/// The update handed to the [`crate::Builder::on_updater_install`] hook.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UpdateInstall {
+  /// The version to install.
+  pub version: String,
+  /// The running version.
+  pub current_version: Version,
+  /// The update description.
+  pub body: Option<String>,
+  /// The environment the update must be installed in.
+  pub environment: UpdaterEnvironment,
+}
+
+pub(crate) type InstallHook<R> =
+  dyn Fn(&AppHandle<R>, UpdateInstall) -> Result<()> + Send + Sync + 'static;
+
+/// The hook set with [`crate::Builder::on_updater_install`], managed by the app.
+pub(crate) struct UpdaterInstallHook<R: Runtime>(pub(crate) Box<InstallHook<R>>);
 
 /// Gets the target string used on the updater.
 pub fn target() -> Option<String> {
@@ -119,6 +168,9 @@ pub fn target() -> Option<String> {
 struct StatusEvent {
   status: String,
   error: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  code: Option<&'static str>,
+  environment: UpdaterEnvironment,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -133,6 +185,9 @@ struct UpdateManifest {
   version: String,
   date: Option<String>,
   body: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  commit: Option<String>,
+  environment: UpdaterEnvironment,
 }
 
 /// An update check builder.
@@ -316,6 +371,8 @@ impl<R: Runtime> UpdateBuilder<R> {
                 body: body.clone(),
                 date: update.date.map(|d| d.to_string()),
                 version: update.version.clone(),
+                commit: update.commit.clone(),
+                environment: update.environment,
               },
             );
             let _ = handle.create_proxy().send_event(EventLoopMessage::Updater(
@@ -341,7 +398,7 @@ impl<R: Runtime> UpdateBuilder<R> {
       }
       Err(e) => {
         if self.events {
-          send_status_update(&handle, UpdaterEvent::Error(e.to_string()));
+          send_error(&handle, &e);
         }
         Err(e)
       }
@@ -388,7 +445,19 @@ impl<R: Runtime> UpdateResponse<R> {
     self.update.body.as_ref()
   }
 
+  /// The commit of the update, when the updater checks the remote the Flatpak was installed from.
+  pub fn commit(&self) -> Option<&str> {
+    self.update.commit.as_deref()
+  }
+
+  /// The environment the update is installed in.
+  pub fn environment(&self) -> UpdaterEnvironment {
+    self.update.environment
+  }
+
   /// Downloads and installs the update.
+  ///
+  /// With the `flatpak` updater source, Flatpak installs the update and this restarts the app into it.
   pub async fn download_and_install(self) -> Result<()> {
     download_and_install(self.update).await
   }
@@ -398,7 +467,11 @@ impl<R: Runtime> UpdateResponse<R> {
 pub(crate) async fn check_update_with_dialog<R: Runtime>(handle: AppHandle<R>) {
   let updater_config = handle.config().tauri.updater.clone();
   let package_info = handle.package_info().clone();
-  if let Some(endpoints) = updater_config.endpoints.clone() {
+  if let Some(endpoints) = updater_config
+    .endpoints
+    .clone()
+    .or_else(|| (updater_config.source == UpdaterSource::Flatpak).then(Vec::new))
+  {
     let endpoints = endpoints
       .iter()
       .map(|e| e.to_string())
@@ -423,12 +496,12 @@ pub(crate) async fn check_update_with_dialog<R: Runtime>(handle: AppHandle<R>) {
             prompt_for_install(&updater.clone(), &package_info.name, &body.clone(), pubkey).await;
 
           if let Err(e) = dialog {
-            send_status_update(&handle, UpdaterEvent::Error(e.to_string()));
+            send_error(&handle, &e);
           }
         }
       }
       Err(e) => {
-        send_status_update(&handle, UpdaterEvent::Error(e.to_string()));
+        send_error(&handle, &e);
       }
     }
   }
@@ -473,10 +546,13 @@ pub(crate) async fn download_and_install<R: Runtime>(update: core::Update<R>) ->
 
   if let Err(err) = &update_result {
     // emit {"status": "ERROR", "error": "The error message"}
-    send_status_update(&update.app, UpdaterEvent::Error(err.to_string()));
+    send_error(&update.app, err);
   } else {
     // emit {"status": "DONE"}
     send_status_update(&update.app, UpdaterEvent::Updated);
+    if update.installs_on_restart() {
+      update.app.restart();
+    }
   }
   update_result
 }
@@ -487,11 +563,11 @@ pub fn builder<R: Runtime>(handle: AppHandle<R>) -> UpdateBuilder<R> {
   let package_info = handle.package_info().clone();
 
   // prepare our endpoints
+  // the `flatpak` source doesn't use the endpoints
   let endpoints = updater_config
     .endpoints
-    .as_ref()
-    .expect("Something wrong with endpoints")
     .iter()
+    .flatten()
     .map(|e| e.to_string())
     .collect::<Vec<String>>();
 
@@ -531,17 +607,35 @@ fn send_download_progress_event<R: Runtime>(
 
 // Send a status update via `tauri://update-status` event.
 fn send_status_update<R: Runtime>(handle: &AppHandle<R>, message: UpdaterEvent) {
+  send_status_event(handle, message, None);
+}
+
+// Send an error via `tauri://update-status` event, with its error code.
+fn send_error<R: Runtime>(handle: &AppHandle<R>, error: &Error) {
+  send_status_event(handle, UpdaterEvent::Error(error.to_string()), error.code());
+}
+
+fn send_status_event<R: Runtime>(
+  handle: &AppHandle<R>,
+  message: UpdaterEvent,
+  code: Option<&'static str>,
+) {
+  let environment = UpdaterEnvironment::detect(&handle.env());
   let _ = handle.emit_all(
     EVENT_STATUS_UPDATE,
     if let UpdaterEvent::Error(error) = &message {
       StatusEvent {
         error: Some(error.clone()),
         status: message.clone().status_message().into(),
+        code,
+        environment,
       }
     } else {
       StatusEvent {
         error: None,
         status: message.clone().status_message().into(),
+        code: None,
+        environment,
       }
     },
   );
@@ -5,8 +5,24 @@
 //! The Tauri window types and functions.
 
 pub(crate) mod menu;
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+mod portal;
 
 pub use menu::{MenuEvent, MenuHandle};
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+pub use portal::PortalWindowIdentifier;
 
 #[cfg(target_os = "macos")]
 use crate::TitleBarStyle;
@@ -990,6 +1006,34 @@ impl<R: Runtime> Window<R> {
     self.window.dispatcher.gtk_window().map_err(Into::into)
   }
 
+  /// Returns the identifier of this window for the `parent_window` argument of the XDG desktop portals,
+  /// `x11:<XID>` on X11 and `wayland:<handle>` on Wayland.
+  ///
+  /// On Wayland the window is exported through the `xdg_foreign` protocol, and stays exported while
+  /// any returned identifier is alive. Keep the identifier until the portal request that uses it
+  /// completed, the window is unexported once the last one is dropped.
+  ///
+  /// Returns `None` on other backends or when the compositor can't export the window.
+  /// On the main thread, the Wayland identifier is only returned while another identifier of
+  /// the window is alive, since waiting for the compositor would block the event loop.
+  /// Call it from a command or another thread instead.
+  #[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+  ))]
+  pub fn portal_window_identifier(&self) -> Option<PortalWindowIdentifier> {
+    match self.window.dispatcher.raw_window_handle().ok()? {
+      raw_window_handle::RawWindowHandle::Xlib(handle) => {
+        Some(PortalWindowIdentifier::x11(handle.window))
+      }
+      raw_window_handle::RawWindowHandle::Wayland(_) => portal::wayland_identifier(self),
+      _ => None,
+    }
+  }
+
   /// Returns the current window theme.
   ///
   /// ## Platform-specific
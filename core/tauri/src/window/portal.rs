@@ -0,0 +1,456 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Wayland handles of the windows, used as the `parent_window` of the XDG desktop portal requests.
+
+use crate::{Runtime, Window, WindowEvent};
+
+use glib::{
+  ffi::{gboolean, gpointer, GDestroyNotify, GFALSE},
+  translate::ToGlibPtr,
+};
+use gtk::{gdk::ffi::GdkWindow, prelude::WidgetExt};
+
+use std::{
+  cell::RefCell, collections::HashMap, ffi::CStr, fmt, os::raw::c_char, rc::Rc,
+  sync::mpsc::sync_channel, time::Duration,
+};
+
+/// How long to wait for the compositor to send the handle of an exported window.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+type GdkWaylandWindowExported =
+  Option<unsafe extern "C" fn(window: *mut GdkWindow, handle: *const c_char, user_data: gpointer)>;
+
+extern "C" {
+  fn gdk_wayland_window_export_handle(
+    window: *mut GdkWindow,
+    callback: GdkWaylandWindowExported,
+    user_data: gpointer,
+    destroy_func: GDestroyNotify,
+  ) -> gboolean;
+  fn gdk_wayland_window_unexport_handle(window: *mut GdkWindow);
+}
+
+/// Called with the exported handle, or `None` if the window could not be exported.
+type OnExported = Box<dyn FnOnce(Option<String>)>;
+
+/// Exports a window through the `xdg_foreign` protocol.
+pub(crate) trait Exporter {
+  /// Starts exporting the window, `on_exported` is called once the compositor sent the handle.
+  fn export(&self, on_exported: OnExported);
+  /// Revokes the exported handle.
+  fn unexport(&self);
+}
+
+#[derive(Default)]
+struct HandleState {
+  handle: Option<String>,
+  exporting: bool,
+  waiting: Vec<OnExported>,
+  /// The requests that haven't been finished yet.
+  requests: usize,
+  released: bool,
+}
+
+/// The exported handle of a window, shared by the pending requests.
+///
+/// GTK can only export a window once at a time, so concurrent requests wait for the same export.
+/// The window is unexported once the last request is finished, or when it is destroyed.
+pub(crate) struct ExportedHandle<E: Exporter> {
+  exporter: Rc<E>,
+  state: Rc<RefCell<HandleState>>,
+}
+
+impl<E: Exporter + 'static> ExportedHandle<E> {
+  pub(crate) fn new(exporter: E) -> Self {
+    Self {
+      exporter: Rc::new(exporter),
+      state: Default::default(),
+    }
+  }
+
+  /// The handle, if the window has been exported already.
+  pub(crate) fn handle(&self) -> Option<String> {
+    self.state.borrow().handle.clone()
+  }
+
+  /// Calls `on_exported` with the handle, exporting the window first if needed.
+  ///
+  /// The window stays exported until [`Self::finish`] is called for the request.
+  pub(crate) fn request(&self, on_exported: OnExported) {
+    let mut state = self.state.borrow_mut();
+    state.requests += 1;
+    if let Some(handle) = state.handle.clone() {
+      drop(state);
+      on_exported(Some(handle));
+      return;
+    }
+    state.waiting.push(on_exported);
+    if state.exporting {
+      return;
+    }
+    state.exporting = true;
+    drop(state);
+
+    let shared = self.state.clone();
+    let exporter = self.exporter.clone();
+    // the callback may run right away when exporting fails, so the state must not be borrowed here
+    self.exporter.export(Box::new(move |handle| {
+      let (waiting, unused) = {
+        let mut state = shared.borrow_mut();
+        state.exporting = false;
+        // every request gave up waiting before the compositor replied
+        let unused = state.requests == 0 && !state.released && handle.is_some();
+        if !state.released && !unused {
+          state.handle = handle.clone();
+        }
+        (std::mem::take(&mut state.waiting), unused)
+      };
+      if unused {
+        exporter.unexport();
+      }
+      for on_exported in waiting {
+        on_exported(handle.clone());
+      }
+    }));
+  }
+
+  /// Finishes a request, unexporting the window if no other request uses the handle.
+  pub(crate) fn finish(&self) {
+    let unexport = {
+      let mut state = self.state.borrow_mut();
+      state.requests = state.requests.saturating_sub(1);
+      state.requests == 0 && state.handle.take().is_some()
+    };
+    if unexport {
+      self.exporter.unexport();
+    }
+  }
+
+  /// Revokes the handle, pending requests get `None`.
+  pub(crate) fn release(self) {
+    let (exported, waiting) = {
+      let mut state = self.state.borrow_mut();
+      state.released = true;
+      (
+        state.handle.take().is_some() || state.exporting,
+        std::mem::take(&mut state.waiting),
+      )
+    };
+    if exported {
+      self.exporter.unexport();
+    }
+    for on_exported in waiting {
+      on_exported(None);
+    }
+  }
+}
+
+/// Exports a GDK window.
+struct GdkExporter(gtk::gdk::Window);
+
+type ExportCallback = RefCell<Option<OnExported>>;
+
+unsafe extern "C" fn exported_trampoline(
+  _window: *mut GdkWindow,
+  handle: *const c_char,
+  user_data: gpointer,
+) {
+  let callback = &*(user_data as *const ExportCallback);
+  if let Some(on_exported) = callback.borrow_mut().take() {
+    on_exported(Some(CStr::from_ptr(handle).to_string_lossy().into_owned()));
+  }
+}
+
+unsafe extern "C" fn destroy_trampoline(user_data: gpointer) {
+  let callback = Box::from_raw(user_data as *mut ExportCallback);
+  // the export was revoked or failed before the compositor sent a handle
+  if let Some(on_exported) = callback.into_inner() {
+    on_exported(None);
+  }
+}
+
+impl Exporter for GdkExporter {
+  fn export(&self, on_exported: OnExported) {
+    let user_data = Box::into_raw(Box::new(RefCell::new(Some(on_exported)))) as gpointer;
+    unsafe {
+      let exporting = gdk_wayland_window_export_handle(
+        self.0.to_glib_none().0,
+        Some(exported_trampoline),
+        user_data,
+        Some(destroy_trampoline),
+      );
+      // GDK keeps the callback only when the export started
+      if exporting == GFALSE {
+        destroy_trampoline(user_data);
+      }
+    }
+  }
+
+  fn unexport(&self) {
+    unsafe { gdk_wayland_window_unexport_handle(self.0.to_glib_none().0) }
+  }
+}
+
+thread_local! {
+  // GTK objects live on the main thread, so this is only ever used there
+  static HANDLES: RefCell<HashMap<String, ExportedHandle<GdkExporter>>> = Default::default();
+}
+
+/// The identifier of a window for the `parent_window` argument of the XDG desktop portals, see
+/// [`Window::portal_window_identifier`].
+///
+/// On Wayland the window stays exported while the identifier is alive, so it should be dropped
+/// once the portal request that uses it completed.
+pub struct PortalWindowIdentifier {
+  identifier: String,
+  finish: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl PortalWindowIdentifier {
+  pub(crate) fn x11(xid: std::os::raw::c_ulong) -> Self {
+    Self {
+      identifier: format!("x11:{:x}", xid),
+      finish: None,
+    }
+  }
+
+  /// The identifier, `x11:<XID>` or `wayland:<handle>`.
+  pub fn as_str(&self) -> &str {
+    &self.identifier
+  }
+}
+
+impl fmt::Display for PortalWindowIdentifier {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.identifier)
+  }
+}
+
+impl fmt::Debug for PortalWindowIdentifier {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("PortalWindowIdentifier")
+      .field(&self.identifier)
+      .finish()
+  }
+}
+
+impl Drop for PortalWindowIdentifier {
+  fn drop(&mut self) {
+    if let Some(finish) = self.finish.take() {
+      finish();
+    }
+  }
+}
+
+/// Finishes a request on the main thread, where the handles live.
+fn finish_request<R: Runtime>(window: &Window<R>) {
+  let label = window.label().to_string();
+  let _ = window.run_on_main_thread(move || {
+    HANDLES.with(|handles| {
+      if let Some(handle) = handles.borrow().get(&label) {
+        handle.finish();
+      }
+    })
+  });
+}
+
+/// The identifier of the window exported through the `xdg_foreign` protocol.
+pub(crate) fn wayland_identifier<R: Runtime>(window: &Window<R>) -> Option<PortalWindowIdentifier> {
+  let identifier = |handle: String| {
+    let window = window.clone();
+    PortalWindowIdentifier {
+      identifier: format!("wayland:{}", handle),
+      finish: Some(Box::new(move || finish_request(&window))),
+    }
+  };
+
+  if gtk::is_initialized_main_thread() {
+    // waiting for the compositor would block the event loop that receives its reply,
+    // so only a handle another request keeps exported can be shared
+    let handle = HANDLES.with(|handles| {
+      let handles = handles.borrow();
+      let exported = handles.get(window.label())?;
+      let handle = exported.handle()?;
+      exported.request(Box::new(|_| {}));
+      Some(handle)
+    });
+    return handle.map(identifier);
+  }
+
+  let (tx, rx) = sync_channel(1);
+  let window_ = window.clone();
+  window
+    .run_on_main_thread(move || {
+      let on_exported: OnExported = Box::new(move |handle| {
+        let _ = tx.send(handle);
+      });
+      HANDLES.with(|handles| {
+        let mut handles = handles.borrow_mut();
+        if !handles.contains_key(window_.label()) {
+          let gdk_window = match window_.gtk_window().ok().and_then(|w| w.window()) {
+            Some(gdk_window) => gdk_window,
+            None => return on_exported(None),
+          };
+          let label = window_.label().to_string();
+          window_.on_window_event(move |event| {
+            if let WindowEvent::Destroyed = event {
+              if let Some(handle) = HANDLES.with(|handles| handles.borrow_mut().remove(&label)) {
+                handle.release();
+              }
+            }
+          });
+          handles.insert(
+            window_.label().to_string(),
+            ExportedHandle::new(GdkExporter(gdk_window)),
+          );
+        }
+        let handle = &handles[window_.label()];
+        handle.request(on_exported);
+      });
+    })
+    .ok()?;
+  match rx.recv_timeout(EXPORT_TIMEOUT) {
+    Ok(Some(handle)) => Some(identifier(handle)),
+    _ => {
+      // the request got no handle, but it still counts until it's finished
+      finish_request(window);
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ExportedHandle, Exporter, OnExported};
+  use std::{cell::RefCell, rc::Rc};
+
+  /// Keeps the export pending until the test completes it.
+  #[derive(Default, Clone)]
+  struct FakeExporter {
+    exports: Rc<RefCell<usize>>,
+    unexports: Rc<RefCell<usize>>,
+    pending: Rc<RefCell<Option<OnExported>>>,
+  }
+
+  impl FakeExporter {
+    fn complete(&self, handle: Option<&str>) {
+      let on_exported = self.pending.borrow_mut().take().expect("no pending export");
+      on_exported(handle.map(Into::into));
+    }
+  }
+
+  impl Exporter for FakeExporter {
+    fn export(&self, on_exported: OnExported) {
+      *self.exports.borrow_mut() += 1;
+      *self.pending.borrow_mut() = Some(on_exported);
+    }
+
+    fn unexport(&self) {
+      *self.unexports.borrow_mut() += 1;
+    }
+  }
+
+  fn recorder() -> (Rc<RefCell<Vec<Option<String>>>>, impl Fn() -> OnExported) {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let received_ = received.clone();
+    (received, move || {
+      let received = received_.clone();
+      Box::new(move |handle| received.borrow_mut().push(handle))
+    })
+  }
+
+  #[test]
+  fn shares_the_export() {
+    let exporter = FakeExporter::default();
+    let (received, on_exported) = recorder();
+    for i in 0..10 {
+      let handle = ExportedHandle::new(exporter.clone());
+      handle.request(on_exported());
+      handle.request(on_exported());
+      assert_eq!(handle.handle(), None);
+      exporter.complete(Some("abc"));
+      handle.request(on_exported());
+      assert_eq!(handle.handle().as_deref(), Some("abc"));
+      handle.release();
+      assert_eq!(*exporter.exports.borrow(), i + 1);
+      assert_eq!(*exporter.unexports.borrow(), i + 1);
+    }
+    assert_eq!(received.borrow().len(), 30);
+    assert!(received
+      .borrow()
+      .iter()
+      .all(|h| h.as_deref() == Some("abc")));
+  }
+
+  #[test]
+  fn release_while_pending() {
+    let exporter = FakeExporter::default();
+    let (received, on_exported) = recorder();
+    let handle = ExportedHandle::new(exporter.clone());
+    handle.request(on_exported());
+    handle.release();
+    assert_eq!(*received.borrow(), vec![None]);
+    assert_eq!(*exporter.unexports.borrow(), 1);
+    // the compositor replying late doesn't resurrect the handle
+    exporter.complete(Some("abc"));
+    assert_eq!(received.borrow().len(), 1);
+  }
+
+  #[test]
+  fn unexports_after_the_last_request() {
+    let exporter = FakeExporter::default();
+    let (received, on_exported) = recorder();
+    let handle = ExportedHandle::new(exporter.clone());
+    for i in 0..10 {
+      handle.request(on_exported());
+      handle.request(on_exported());
+      exporter.complete(Some("abc"));
+      handle.finish();
+      // still used by the second request
+      assert_eq!(*exporter.unexports.borrow(), i);
+      assert_eq!(handle.handle().as_deref(), Some("abc"));
+      handle.finish();
+      assert_eq!(*exporter.unexports.borrow(), i + 1);
+      assert_eq!(handle.handle(), None);
+    }
+    // every export was revoked
+    assert_eq!(*exporter.exports.borrow(), 10);
+    assert_eq!(received.borrow().len(), 20);
+    handle.release();
+    assert_eq!(*exporter.unexports.borrow(), 10);
+  }
+
+  #[test]
+  fn finished_before_the_reply() {
+    let exporter = FakeExporter::default();
+    let (received, on_exported) = recorder();
+    let handle = ExportedHandle::new(exporter.clone());
+    handle.request(on_exported());
+    // the request timed out
+    handle.finish();
+    exporter.complete(Some("abc"));
+    assert_eq!(*exporter.unexports.borrow(), 1);
+    assert_eq!(handle.handle(), None);
+    assert_eq!(*received.borrow(), vec![Some("abc".into())]);
+    handle.release();
+    assert_eq!(*exporter.unexports.borrow(), 1);
+  }
+
+  #[test]
+  fn retries_failed_exports() {
+    let exporter = FakeExporter::default();
+    let (received, on_exported) = recorder();
+    let handle = ExportedHandle::new(exporter.clone());
+    handle.request(on_exported());
+    exporter.complete(None);
+    handle.request(on_exported());
+    exporter.complete(Some("abc"));
+    assert_eq!(*received.borrow(), vec![None, Some("abc".into())]);
+    assert_eq!(*exporter.exports.borrow(), 2);
+    handle.release();
+    assert_eq!(*exporter.unexports.borrow(), 1);
+  }
+}
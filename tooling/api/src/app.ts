@@ -15,7 +15,8 @@
  *       "app": {
  *         "all": true, // enable all app APIs
  *         "show": true,
- *         "hide": true
+ *         "hide": true,
+ *         "portalState": true
  *       }
  *     }
  *   }
@@ -126,4 +127,56 @@ async function hide(): Promise<void> {
   })
 }
 
-export { getName, getVersion, getTauriVersion, show, hide }
+/**
+ * The sandbox and XDG desktop portals the app runs with.
+ *
+ * @since 1.3.0
+ */
+interface PortalState {
+  /** Whether the desktop integration is detected on this platform, only on Linux. */
+  supported: boolean
+  /** Whether the app runs inside a Flatpak sandbox. */
+  sandboxed: boolean
+  /** The Flatpak application id, when sandboxed. */
+  flatpakId: string | null
+  /** Whether the `org.freedesktop.portal.Desktop` service answered on the session bus. */
+  portalAvailable: boolean
+  /** The version of each portal interface the service provides, by interface name. */
+  interfaces: Record<string, number>
+  /** Whether notifications can be sent through the `Notification` portal. */
+  notification: boolean
+  /** Whether files can be picked through the `FileChooser` portal. */
+  fileChooser: boolean
+  /** Whether global shortcuts can be registered through the `GlobalShortcuts` portal. */
+  globalShortcuts: boolean
+  /** Whether directories can be picked through the `FileChooser` portal, from its version 3. */
+  directoryChooser: boolean
+  /** Whether the folder of a file can be opened through the `OpenURI` portal, from its version 3. */
+  openDirectory: boolean
+}
+
+/**
+ * Gets the sandbox and XDG desktop portals the app runs with, for feature detection.
+ *
+ * The portals are probed once, the first time the state is requested.
+ *
+ * @example
+ * ```typescript
+ * import { getPortalState } from '@tauri-apps/api/app';
+ * const { fileChooser } = await getPortalState();
+ * ```
+ *
+ * @since 1.3.0
+ */
+async function getPortalState(): Promise<PortalState> {
+  return invokeTauriCommand({
+    __tauriModule: 'App',
+    message: {
+      cmd: 'portalState'
+    }
+  })
+}
+
+export type { PortalState }
+
+export { getName, getVersion, getTauriVersion, show, hide, getPortalState }
@@ -35,7 +35,8 @@ export enum TauriEvent {
   UPDATE_AVAILABLE = 'tauri://update-available',
   INSTALL_UPDATE = 'tauri://update-install',
   STATUS_UPDATE = 'tauri://update-status',
-  DOWNLOAD_PROGRESS = 'tauri://update-download-progress'
+  DOWNLOAD_PROGRESS = 'tauri://update-download-progress',
+  UPDATED_WHILE_RUNNING = 'tauri://updated-while-running'
 }
 
 /**
@@ -31,9 +31,10 @@
  *
  * ### Restricting access to the {@link open | `open`} API
  *
- * On the allowlist, `open: true` means that the {@link open} API can be used with any URL,
- * as the argument is validated with the `^https?://` regex.
- * You can change that regex by changing the boolean value to a string, e.g. `open: ^https://github.com/`.
+ * On the allowlist, `open: true` means that the {@link open} API can be used with any `https`, `http`, `mailto` or `tel` URL.
+ * You can replace that check with a regex by changing the boolean value to a string, e.g. `open: ^https://github.com/`,
+ * or allow more schemes with `open: { schemes: ['myapp-companion'] }`.
+ * Dangerous schemes such as `javascript:` are always rejected.
  *
  * ### Restricting access to the {@link Command | `Command`} APIs
  *
@@ -533,12 +534,23 @@ type CommandEvent =
   | Event<'Terminated', TerminatedPayload>
   | Event<'Error', string>
 
+/**
+ * The app to {@link open} a path or URL with: the name of a known program,
+ * or a program listed in `tauri.conf.json > tauri > allowlist > shell > open > customPrograms`.
+ *
+ * @since 1.3.0
+ */
+type OpenWith = string | { custom: string }
+
 /**
  * Opens a path or URL with the system's default app,
  * or the one specified with `openWith`.
  *
- * The `openWith` value must be one of `firefox`, `google chrome`, `chromium` `safari`,
- * `open`, `start`, `xdg-open`, `gio`, `gnome-open`, `kde-open` or `wslview`.
+ * The `openWith` value must be one of `firefox`, `google chrome`, `chromium`, `safari`,
+ * `brave`, `edge`, `opera`, `epiphany`, `librewolf`,
+ * `open`, `start`, `xdg-open`, `gio`, `gnome-open`, `kde-open` or `wslview`,
+ * or `{ custom: name }` with a program name listed in `tauri.conf.json > tauri > allowlist > shell > open > customPrograms`.
+ * Other values are rejected.
  *
  * @example
  * ```typescript
@@ -547,19 +559,21 @@ type CommandEvent =
  * await open('https://github.com/tauri-apps/tauri');
  * // opens the given URL using `firefox`:
  * await open('https://github.com/tauri-apps/tauri', 'firefox');
+ * // opens a file using the `code` custom program:
+ * await open('/path/to/file', { custom: 'code' });
  * // opens a file using the default program:
  * await open('/path/to/file');
  * ```
  *
  * @param path The path or URL to open.
  * This value is matched against the string regex defined on `tauri.conf.json > tauri > allowlist > shell > open`,
- * which defaults to `^https?://`.
+ * or by default must be an `https`, `http`, `mailto` or `tel` URL.
  * @param openWith The app to open the file or URL with.
  * Defaults to the system default application for the specified path type.
  *
  * @since 1.0.0
  */
-async function open(path: string, openWith?: string): Promise<void> {
+async function open(path: string, openWith?: OpenWith): Promise<void> {
   return invokeTauriCommand({
     __tauriModule: 'Shell',
     message: {
@@ -570,5 +584,39 @@ async function open(path: string, openWith?: string): Promise<void> {
   })
 }
 
-export { Command, Child, EventEmitter, open }
-export type { ChildProcess, SpawnOptions }
+/**
+ * Checks whether {@link open} would accept the path or URL and the `openWith` app,
+ * without opening anything.
+ *
+ * The exact same validation as {@link open} is used, so the two never disagree.
+ *
+ * @example
+ * ```typescript
+ * import { isOpenAllowed } from '@tauri-apps/api/shell';
+ * const allowed = await isOpenAllowed('https://github.com/tauri-apps/tauri')
+ *   .then(() => true)
+ *   .catch(() => false);
+ * ```
+ *
+ * @param path The path or URL to check.
+ * @param openWith The app the file or URL would be opened with.
+ * @returns A promise that resolves if the path can be opened, and rejects with the same error as {@link open} otherwise.
+ *
+ * @since 1.3.0
+ */
+async function isOpenAllowed(
+  path: string,
+  openWith?: OpenWith
+): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Shell',
+    message: {
+      cmd: 'isOpenAllowed',
+      path,
+      with: openWith
+    }
+  })
+}
+
+export { Command, Child, EventEmitter, open, isOpenAllowed }
+export type { ChildProcess, SpawnOptions, OpenWith }
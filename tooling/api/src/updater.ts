@@ -17,12 +17,40 @@ import { UnlistenFn } from './helpers/event'
  */
 type UpdateStatus = 'PENDING' | 'ERROR' | 'DONE' | 'UPTODATE'
 
+/**
+ * The environment the app runs in, which decides how updates are installed.
+ * - `native`: the updater replaces the app with the downloaded update.
+ * - `flatpak`: the app runs inside a Flatpak sandbox, where Flatpak installs the updates.
+ *
+ * @since 1.3.0
+ */
+type UpdaterEnvironment = 'native' | 'flatpak'
+
+/**
+ * The code of an updater error apps need to tell apart.
+ * - `UNSUPPORTED_ENVIRONMENT`: the update can't be installed in this environment, for example inside a Flatpak sandbox
+ * without the `tauri > updater > customInstall` hook.
+ *
+ * @since 1.3.0
+ */
+type UpdateErrorCode = 'UNSUPPORTED_ENVIRONMENT'
+
 /**
  * @since 1.0.0
  */
 interface UpdateStatusResult {
   error?: string
+  /**
+   * The code of the error, if it is one apps need to tell apart.
+   *
+   * @since 1.3.0
+   */
+  code?: UpdateErrorCode
   status: UpdateStatus
+  /**
+   * @since 1.3.0
+   */
+  environment: UpdaterEnvironment
 }
 
 /**
@@ -32,6 +60,10 @@ interface UpdateManifest {
   version: string
   date: string
   body: string
+  /**
+   * @since 1.3.0
+   */
+  environment: UpdaterEnvironment
 }
 
 /**
@@ -204,6 +236,13 @@ async function checkUpdate(): Promise<UpdateResult> {
   })
 }
 
-export type { UpdateStatus, UpdateStatusResult, UpdateManifest, UpdateResult }
+export type {
+  UpdateStatus,
+  UpdateStatusResult,
+  UpdateManifest,
+  UpdateResult,
+  UpdaterEnvironment,
+  UpdateErrorCode
+}
 
 export { onUpdaterEvent, installUpdate, checkUpdate }
@@ -1324,10 +1324,12 @@ class WindowManager extends WebviewWindowHandle {
    * tauri = { version = "...", features = ["...", "icon-png"] }
    * ```
    *
-   * @param icon Icon bytes or path to the icon file.
+   * On Linux, `{ name: 'com.tauri.dev' }` sets an icon from the icon theme, which requires the `icon-png` feature.
+   *
+   * @param icon Icon bytes, path to the icon file or name of the icon in the icon theme.
    * @returns A promise indicating the success or failure of the operation.
    */
-  async setIcon(icon: string | Uint8Array): Promise<void> {
+  async setIcon(icon: string | Uint8Array | { name: string }): Promise<void> {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
@@ -1338,7 +1340,7 @@ class WindowManager extends WebviewWindowHandle {
             type: 'setIcon',
             payload: {
               // correctly serialize Uint8Arrays
-              icon: typeof icon === 'string' ? icon : Array.from(icon)
+              icon: icon instanceof Uint8Array ? Array.from(icon) : icon
             }
           }
         }
@@ -12,6 +12,7 @@ mod macos;
 mod path_utils;
 mod platform;
 mod settings;
+mod sign;
 mod updater_bundle;
 #[cfg(target_os = "windows")]
 mod windows;
@@ -19,9 +20,10 @@ mod windows;
 pub use self::{
   category::AppCategory,
   settings::{
-    BundleBinary, BundleSettings, DebianSettings, MacOsSettings, PackageSettings, PackageType,
-    Settings, SettingsBuilder, UpdaterSettings,
+    BundleBinary, BundleSettings, DebianSettings, DesktopEntrySettings, LinuxSigningSettings,
+    MacOsSettings, PackageSettings, PackageType, Settings, SettingsBuilder, UpdaterSettings,
   },
+  sign::{GpgSigner, Signer},
 };
 use log::{info, warn};
 pub use settings::{WindowsSettings, WixLanguage, WixLanguageConfig, WixSettings};
@@ -6,14 +6,19 @@
 use log::debug;
 
 use std::{
+  collections::VecDeque,
   ffi::OsStr,
   fs::{self, File},
-  io::{self, BufReader, BufWriter},
+  io::{self, BufReader, BufWriter, Read},
   path::Path,
   process::{Command, Output, Stdio},
   sync::{Arc, Mutex},
+  thread::JoinHandle,
 };
 
+/// The number of output lines of each stream [`CommandExt::pipe_ok`] keeps for its error.
+const PIPE_TAIL_LINES: usize = 20;
+
 /// Returns true if the path has a filename indicating that it is a high-density
 /// "retina" icon.  Specifically, returns true the file stem ends with
 /// "@2x" (a convention specified by the [Apple developer docs](
@@ -88,6 +93,15 @@ pub fn copy_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> crate::Result<
   Ok(())
 }
 
+/// The `SOURCE_DATE_EPOCH` timestamp bundles must not embed a later time than, see
+/// <https://reproducible-builds.org/specs/source-date-epoch/>.
+#[cfg(target_os = "linux")]
+pub fn source_date_epoch() -> Option<u64> {
+  std::env::var("SOURCE_DATE_EPOCH")
+    .ok()
+    .and_then(|timestamp| timestamp.trim().parse().ok())
+}
+
 /// Recursively copies a directory file from one path to another, creating any
 /// parent directories of the destination path as necessary.  Fails if the
 /// source path is not a directory or doesn't exist, or if the destination path
@@ -137,6 +151,12 @@ pub fn copy_dir(from: &Path, to: &Path) -> crate::Result<()> {
 
 pub trait CommandExt {
   fn output_ok(&mut self) -> crate::Result<Output>;
+
+  /// Runs the command, logging its output lines with the `log_target` as they arrive.
+  ///
+  /// Use it instead of [`Self::output_ok`] for long-running tools. Only the last lines are kept,
+  /// and they are included in the error if the command fails.
+  fn pipe_ok(&mut self, log_target: &str) -> crate::Result<()>;
 }
 
 impl CommandExt for Command {
@@ -201,11 +221,68 @@ impl CommandExt for Command {
       )))
     }
   }
+
+  fn pipe_ok(&mut self, log_target: &str) -> crate::Result<()> {
+    let program = self.get_program().to_string_lossy().into_owned();
+    debug!(action = "Running"; "Command `{} {}`", program, self.get_args().map(|arg| arg.to_string_lossy()).fold(String::new(), |acc, arg| format!("{} {}", acc, arg)));
+
+    self.stdout(Stdio::piped());
+    self.stderr(Stdio::piped());
+
+    let mut child = self.spawn()?;
+    let stdout = forward_lines(child.stdout.take().unwrap(), log_target.to_string());
+    let stderr = forward_lines(child.stderr.take().unwrap(), log_target.to_string());
+
+    let status = child.wait()?;
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if status.success() {
+      return Ok(());
+    }
+    let mut message = format!("failed to run {} ({})", program, status);
+    for (name, tail) in [("stderr", stderr), ("stdout", stdout)] {
+      if !tail.is_empty() {
+        message.push_str(&format!("\n{}:", name));
+        for line in tail {
+          message.push_str("\n  ");
+          message.push_str(&line);
+        }
+      }
+    }
+    Err(crate::Error::GenericError(message))
+  }
+}
+
+/// Logs the lines of the stream as they arrive, returning the last [`PIPE_TAIL_LINES`] of them.
+fn forward_lines<R: Read + Send + 'static>(
+  stream: R,
+  log_target: String,
+) -> JoinHandle<VecDeque<String>> {
+  std::thread::spawn(move || {
+    let mut stream = BufReader::new(stream);
+    let mut tail = VecDeque::with_capacity(PIPE_TAIL_LINES);
+    let mut buf = Vec::new();
+    loop {
+      buf.clear();
+      match tauri_utils::io::read_line(&mut stream, &mut buf) {
+        Ok(0) | Err(_) => break,
+        Ok(_) => (),
+      }
+      let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+      debug!(target: log_target.as_str(), "{}", line);
+      if tail.len() == PIPE_TAIL_LINES {
+        tail.pop_front();
+      }
+      tail.push_back(line);
+    }
+    tail
+  })
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{create_file, is_retina};
+  use super::{create_file, is_retina, CommandExt, PIPE_TAIL_LINES};
   use std::{io::Write, path::PathBuf};
   use tauri_utils::resources::resource_relpath;
 
@@ -294,4 +371,47 @@ mod tests {
       PathBuf::from("_root_/home/ferris/crab.png")
     );
   }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn pipe_ok() {
+    assert!(std::process::Command::new("sh")
+      .args(["-c", "echo out; echo err >&2"])
+      .pipe_ok("test")
+      .is_ok());
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn pipe_ok_failure_includes_output() {
+    let error = std::process::Command::new("sh")
+      .args(["-c", "echo building; echo broken >&2; exit 1"])
+      .pipe_ok("test")
+      .unwrap_err()
+      .to_string();
+    assert!(error.contains("failed to run sh"), "{}", error);
+    assert!(error.contains("stderr:\n  broken"), "{}", error);
+    assert!(error.contains("stdout:\n  building"), "{}", error);
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn pipe_ok_keeps_the_tail() {
+    let error = std::process::Command::new("sh")
+      .args(["-c", "for i in $(seq 1 100); do echo line$i; done; exit 1"])
+      .pipe_ok("test")
+      .unwrap_err()
+      .to_string();
+    assert!(error.contains("line100"), "{}", error);
+    assert!(
+      error.contains(&format!("line{}\n", 100 - PIPE_TAIL_LINES + 1)),
+      "{}",
+      error
+    );
+    assert!(
+      !error.contains(&format!("line{}\n", 100 - PIPE_TAIL_LINES)),
+      "{}",
+      error
+    );
+  }
 }
@@ -5,6 +5,7 @@
 
 use super::{
   super::{common::CommandExt, path_utils},
+  arch::LinuxArch,
   debian,
 };
 use crate::Settings;
@@ -21,12 +22,7 @@ use std::{
 /// Bundles the project.
 /// Returns a vector of PathBuf that shows where the AppImage was created.
 pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
-  // generate the deb binary name
-  let arch = match settings.binary_arch() {
-    "x86" => "i386",
-    "x86_64" => "amd64",
-    other => other,
-  };
+  let arch = LinuxArch::from_settings(settings)?;
   let package_dir = settings.project_out_directory().join("bundle/appimage_deb");
 
   // generate deb_folder structure
@@ -43,7 +39,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     "{}_{}_{}.AppImage",
     settings.main_binary_name(),
     settings.version_string(),
-    arch
+    arch.appimage_suffix()
   );
   let appimage_path = output_path.join(&appimage_filename);
   path_utils::create(app_dir_path, true)?;
@@ -52,7 +48,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
 
   // setup data to insert into shell script
   let mut sh_map = BTreeMap::new();
-  sh_map.insert("arch", settings.target().split('-').next().unwrap());
+  sh_map.insert("arch", arch.appimage_tool());
   sh_map.insert("app_name", settings.main_binary_name());
   sh_map.insert("app_name_uppercase", &upcase_app_name);
   sh_map.insert("appimage_filename", &appimage_filename);
@@ -69,7 +65,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
   let larger_icon = icons
     .iter()
     .filter(|i| i.width == i.height)
-    .max_by_key(|i| i.width)
+    .max_by_key(|i| (i.width, !i.is_high_density))
     .expect("couldn't find a square icon to use as AppImage icon");
   let larger_icon_path = larger_icon
     .path
@@ -78,6 +74,16 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     .to_string_lossy()
     .to_string();
   sh_map.insert("icon_path", &larger_icon_path);
+  // linuxdeploy verbosity goes from 0 (debug) to 3 (error)
+  sh_map.insert(
+    "linuxdeploy_verbosity",
+    match settings.log_level() {
+      log::Level::Trace | log::Level::Debug => "0",
+      log::Level::Info => "1",
+      log::Level::Warn => "2",
+      log::Level::Error => "3",
+    },
+  );
 
   // initialize shell script template.
   let mut handlebars = Handlebars::new();
@@ -106,7 +112,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
   // execute the shell script to build the appimage.
   Command::new(&sh_file)
     .current_dir(output_path)
-    .output_ok()
+    .pipe_ok("appimage")
     .context("error running appimage.sh")?;
 
   remove_dir_all(&package_dir)?;
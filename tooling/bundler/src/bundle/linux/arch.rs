@@ -0,0 +1,124 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::Settings;
+
+/// A CPU architecture supported by the Linux bundles, with the name each package format uses for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinuxArch {
+  X86,
+  X86_64,
+  Armhf,
+  Aarch64,
+}
+
+impl LinuxArch {
+  /// The architecture of the binary being bundled.
+  pub fn from_settings(settings: &Settings) -> crate::Result<Self> {
+    Self::from_target(settings.target())
+  }
+
+  /// The architecture of a target triple, e.g. `aarch64-unknown-linux-gnu`.
+  pub fn from_target(target: &str) -> crate::Result<Self> {
+    match target.split('-').next().unwrap_or_default() {
+      "x86_64" => Ok(Self::X86_64),
+      "i386" | "i586" | "i686" => Ok(Self::X86),
+      "aarch64" => Ok(Self::Aarch64),
+      // ARM64 is detected differently, armel isn't supported, so armhf is the only reasonable choice here.
+      arch if arch.starts_with("arm") || arch.starts_with("thumbv7") => Ok(Self::Armhf),
+      _ => Err(crate::Error::ArchError(format!(
+        "target triple {} is not supported by the Linux bundles",
+        target
+      ))),
+    }
+  }
+
+  /// The Debian architecture name.
+  pub fn debian(self) -> &'static str {
+    match self {
+      Self::X86 => "i386",
+      Self::X86_64 => "amd64",
+      Self::Armhf => "armhf",
+      Self::Aarch64 => "arm64",
+    }
+  }
+
+  /// The suffix of the AppImage file name.
+  pub fn appimage_suffix(self) -> &'static str {
+    match self {
+      Self::X86 => "i386",
+      Self::X86_64 => "amd64",
+      Self::Armhf => "armhf",
+      Self::Aarch64 => "aarch64",
+    }
+  }
+
+  /// The `ARCH` the AppImage tools expect.
+  pub fn appimage_tool(self) -> &'static str {
+    match self {
+      Self::X86 => "i686",
+      Self::X86_64 => "x86_64",
+      Self::Armhf => "armhf",
+      Self::Aarch64 => "aarch64",
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::LinuxArch;
+
+  #[test]
+  fn target_mapping() {
+    for (target, arch, debian, appimage_suffix, appimage_tool) in [
+      (
+        "x86_64-unknown-linux-gnu",
+        LinuxArch::X86_64,
+        "amd64",
+        "amd64",
+        "x86_64",
+      ),
+      (
+        "i686-unknown-linux-gnu",
+        LinuxArch::X86,
+        "i386",
+        "i386",
+        "i686",
+      ),
+      (
+        "aarch64-unknown-linux-gnu",
+        LinuxArch::Aarch64,
+        "arm64",
+        "aarch64",
+        "aarch64",
+      ),
+      (
+        "armv7-unknown-linux-gnueabihf",
+        LinuxArch::Armhf,
+        "armhf",
+        "armhf",
+        "armhf",
+      ),
+      (
+        "arm-unknown-linux-gnueabihf",
+        LinuxArch::Armhf,
+        "armhf",
+        "armhf",
+        "armhf",
+      ),
+    ] {
+      let mapped = LinuxArch::from_target(target).unwrap();
+      assert_eq!(mapped, arch, "{}", target);
+      assert_eq!(mapped.debian(), debian, "{}", target);
+      assert_eq!(mapped.appimage_suffix(), appimage_suffix, "{}", target);
+      assert_eq!(mapped.appimage_tool(), appimage_tool, "{}", target);
+    }
+  }
+
+  #[test]
+  fn unsupported_target() {
+    let error = LinuxArch::from_target("riscv64gc-unknown-linux-gnu").unwrap_err();
+    assert!(error.to_string().contains("riscv64gc-unknown-linux-gnu"));
+  }
+}
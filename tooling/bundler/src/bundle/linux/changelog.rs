@@ -0,0 +1,374 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Parses the Markdown changelog of the application so the Linux bundles can ship
+//! the release history in their own formats.
+
+use std::fmt::Write;
+
+/// The number of releases converted, starting from the most recent one.
+const MAX_RELEASES: usize = 10;
+/// The maximum width of a line in the Debian changelog.
+const DEBIAN_LINE_WIDTH: usize = 80;
+
+/// A calendar date in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+  pub year: i64,
+  pub month: u32,
+  pub day: u32,
+}
+
+impl Date {
+  /// Parses a `YYYY-MM-DD` date.
+  pub fn parse(date: &str) -> Option<Self> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?;
+    let month = parts.next()?;
+    let day = parts.next()?;
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+      return None;
+    }
+    let date = Self {
+      year: year.parse().ok()?,
+      month: month.parse().ok()?,
+      day: day.parse().ok()?,
+    };
+    if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) {
+      Some(date)
+    } else {
+      None
+    }
+  }
+
+  /// The date of the given UNIX timestamp.
+  pub fn from_timestamp(timestamp: u64) -> Self {
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+      (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+      month_index + 3
+    } else {
+      month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    Self { year, month, day }
+  }
+
+  /// The date in the RFC 2822 format used by `dpkg`, at midnight UTC.
+  pub fn to_rfc2822(self) -> String {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+      "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if self.month <= 2 {
+      self.year - 1
+    } else {
+      self.year
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(self.month);
+    let day_of_year =
+      (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    // 1970-01-01 was a Thursday
+    let weekday = WEEKDAYS[(days + 3).rem_euclid(7) as usize];
+    format!(
+      "{}, {:02} {} {} 00:00:00 +0000",
+      weekday,
+      self.day,
+      MONTHS[self.month as usize - 1],
+      self.year
+    )
+  }
+}
+
+/// A release section of the changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+  pub version: String,
+  pub date: Option<Date>,
+  pub changes: Vec<String>,
+}
+
+/// Parses the releases of a Markdown changelog, most recent first.
+///
+/// Every heading starting with a version, e.g. `## \[1.2.0] - 2022-12-01` or `## v1.2.0`, starts a release
+/// and its list items are the changes. Other headings, like `## Unreleased` or `### Bug Fixes`, are skipped.
+pub fn parse(markdown: &str) -> Vec<Release> {
+  let mut releases: Vec<Release> = Vec::new();
+  let mut release_level = None;
+  for line in markdown.lines() {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+      let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+      if let Some(release) = parse_heading(trimmed) {
+        releases.push(release);
+        release_level = Some(level);
+      } else if release_level.map_or(false, |release_level| level <= release_level) {
+        // a heading of the same level ends the release without starting a new one
+        release_level = None;
+      }
+      continue;
+    }
+    if release_level.is_none() {
+      continue;
+    }
+    let release = releases.last_mut().unwrap();
+    if let Some(change) = trimmed
+      .strip_prefix("- ")
+      .or_else(|| trimmed.strip_prefix("* "))
+    {
+      release.changes.push(change.trim().to_string());
+    } else if !trimmed.is_empty() && line.starts_with(char::is_whitespace) {
+      // continuation of the previous list item
+      if let Some(change) = release.changes.last_mut() {
+        change.push(' ');
+        change.push_str(trimmed.trim_end());
+      }
+    }
+  }
+  releases
+}
+
+fn parse_heading(heading: &str) -> Option<Release> {
+  let title = heading.trim_start_matches('#').trim();
+  let mut words = title.split_whitespace();
+  let version = words
+    .next()?
+    .trim_start_matches('\\')
+    .trim_start_matches('[')
+    .trim_end_matches(']')
+    .trim_start_matches('v');
+  if !(version.starts_with(|c: char| c.is_ascii_digit()) && version.contains('.')) {
+    return None;
+  }
+  let date = words.find_map(|word| Date::parse(word.trim_matches(|c| c == '(' || c == ')')));
+  Some(Release {
+    version: version.to_string(),
+    date,
+    changes: Vec::new(),
+  })
+}
+
+/// Makes sure the most recent release is the given version, adding a release for it if it isn't.
+///
+/// Returns whether a release was added.
+pub fn ensure_release(releases: &mut Vec<Release>, version: &str) -> bool {
+  if releases.first().map(|release| release.version.as_str()) == Some(version) {
+    return false;
+  }
+  releases.insert(
+    0,
+    Release {
+      version: version.to_string(),
+      date: None,
+      changes: Vec::new(),
+    },
+  );
+  true
+}
+
+/// Formats the most recent releases as a Debian changelog.
+///
+/// Releases without a date use `default_date`.
+pub fn to_debian(
+  releases: &[Release],
+  package: &str,
+  maintainer: &str,
+  default_date: Date,
+) -> String {
+  let mut changelog = String::new();
+  for (i, release) in releases.iter().take(MAX_RELEASES).enumerate() {
+    if i > 0 {
+      changelog.push('\n');
+    }
+    writeln!(
+      changelog,
+      "{} ({}) unstable; urgency=medium\n",
+      package, release.version
+    )
+    .unwrap();
+    if release.changes.is_empty() {
+      changelog.push_str("  * New upstream release.\n");
+    }
+    for change in &release.changes {
+      wrap_change(&mut changelog, change);
+    }
+    writeln!(
+      changelog,
+      "\n -- {}  {}",
+      maintainer,
+      release.date.unwrap_or(default_date).to_rfc2822()
+    )
+    .unwrap();
+  }
+  changelog
+}
+
+fn wrap_change(changelog: &mut String, change: &str) {
+  let mut line = String::from("  *");
+  for word in change.split_whitespace() {
+    if line.len() > 4 && line.len() + 1 + word.len() > DEBIAN_LINE_WIDTH {
+      changelog.push_str(&line);
+      changelog.push('\n');
+      line = String::from("   ");
+    }
+    line.push(' ');
+    line.push_str(word);
+  }
+  changelog.push_str(&line);
+  changelog.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ensure_release, parse, to_debian, Date, Release};
+
+  const CHANGELOG: &str = r"# Changelog
+
+## Unreleased
+
+- Not released yet.
+
+## \[1.2.0] - 2022-12-01
+
+### New Features
+
+- Added the `tray` option.
+- A very long change that keeps going well past the width of a Debian changelog line so it has to be wrapped.
+
+### Bug Fixes
+
+* Fixed a crash
+  when closing the window.
+
+## v1.1.0 (2022-10-15)
+
+- Initial release.
+";
+
+  #[test]
+  fn parse_releases() {
+    let releases = parse(CHANGELOG);
+    assert_eq!(
+      releases,
+      vec![
+        Release {
+          version: "1.2.0".into(),
+          date: Some(Date {
+            year: 2022,
+            month: 12,
+            day: 1
+          }),
+          changes: vec![
+            "Added the `tray` option.".into(),
+            "A very long change that keeps going well past the width of a Debian changelog line so it has to be wrapped.".into(),
+            "Fixed a crash when closing the window.".into(),
+          ],
+        },
+        Release {
+          version: "1.1.0".into(),
+          date: Some(Date {
+            year: 2022,
+            month: 10,
+            day: 15
+          }),
+          changes: vec!["Initial release.".into()],
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn debian_changelog() {
+    let mut releases = parse(CHANGELOG);
+    assert!(!ensure_release(&mut releases, "1.2.0"));
+    let expected = "my-app (1.2.0) unstable; urgency=medium
+
+  * Added the `tray` option.
+  * A very long change that keeps going well past the width of a Debian
+    changelog line so it has to be wrapped.
+  * Fixed a crash when closing the window.
+
+ -- Jane Doe <jane@doe.com>  Thu, 01 Dec 2022 00:00:00 +0000
+
+my-app (1.1.0) unstable; urgency=medium
+
+  * Initial release.
+
+ -- Jane Doe <jane@doe.com>  Sat, 15 Oct 2022 00:00:00 +0000
+";
+    assert_eq!(
+      to_debian(
+        &releases,
+        "my-app",
+        "Jane Doe <jane@doe.com>",
+        Date::from_timestamp(0)
+      ),
+      expected
+    );
+  }
+
+  #[test]
+  fn mismatched_version() {
+    let mut releases = parse(CHANGELOG);
+    assert!(ensure_release(&mut releases, "1.3.0"));
+    let changelog = to_debian(
+      &releases,
+      "my-app",
+      "Jane Doe <jane@doe.com>",
+      Date::from_timestamp(1_672_531_200),
+    );
+    assert!(changelog.starts_with(
+      "my-app (1.3.0) unstable; urgency=medium
+
+  * New upstream release.
+
+ -- Jane Doe <jane@doe.com>  Sun, 01 Jan 2023 00:00:00 +0000
+
+my-app (1.2.0) unstable; urgency=medium
+"
+    ));
+  }
+
+  #[test]
+  fn dates() {
+    assert_eq!(
+      Date::from_timestamp(0),
+      Date {
+        year: 1970,
+        month: 1,
+        day: 1
+      }
+    );
+    assert_eq!(
+      Date::from_timestamp(951_782_400),
+      Date {
+        year: 2000,
+        month: 2,
+        day: 29
+      }
+    );
+    assert_eq!(
+      Date::parse("2000-02-29").unwrap().to_rfc2822(),
+      "Tue, 29 Feb 2000 00:00:00 +0000"
+    );
+    assert_eq!(
+      Date::from_timestamp(0).to_rfc2822(),
+      "Thu, 01 Jan 1970 00:00:00 +0000"
+    );
+    assert_eq!(Date::parse("2022-13-01"), None);
+    assert_eq!(Date::parse("22-12-01"), None);
+  }
+}
@@ -23,13 +23,13 @@
 // metadata, as well as generating the md5sums file.  Currently we do not
 // generate postinst or prerm files.
 
-use super::super::common;
-use crate::Settings;
+use super::{super::common, arch::LinuxArch, changelog};
+use crate::{bundle::settings::DesktopExecArg, DesktopEntrySettings, GpgSigner, Settings, Signer};
 use anyhow::Context;
 use heck::AsKebabCase;
-use image::{self, codecs::png::PngDecoder, ImageDecoder};
+use image::{self, codecs::png::PngDecoder, imageops::FilterType, ImageDecoder, ImageFormat};
 use libflate::gzip;
-use log::info;
+use log::{info, warn};
 use walkdir::WalkDir;
 
 use std::{
@@ -38,6 +38,7 @@ use std::{
   fs::{self, File},
   io::{self, Write},
   path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
 };
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -49,16 +50,26 @@ pub struct DebIcon {
 }
 
 /// Bundles the project.
-/// Returns a vector of PathBuf that shows where the DEB was created.
+/// Returns a vector of PathBuf that shows where the DEB and its signature were created.
 pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
-  let arch = match settings.binary_arch() {
-    "x86" => "i386",
-    "x86_64" => "amd64",
-    // ARM64 is detected differently, armel isn't supported, so armhf is the only reasonable choice here.
-    "arm" => "armhf",
-    "aarch64" => "arm64",
-    other => other,
-  };
+  let signer = settings
+    .linux_signing()
+    .filter(|signing| signing.deb)
+    .map(|signing| GpgSigner::new(signing.gpg_key.clone(), signing.gpg_home.clone()));
+  bundle_project_with_signer(
+    settings,
+    signer.as_ref().map(|signer| signer as &dyn Signer),
+    common::source_date_epoch(),
+  )
+}
+
+/// Bundles the project, with the `SOURCE_DATE_EPOCH` timestamp the package must not embed a later time than.
+fn bundle_project_with_signer(
+  settings: &Settings,
+  signer: Option<&dyn Signer>,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<Vec<PathBuf>> {
+  let arch = LinuxArch::from_settings(settings)?.debian();
   let package_base_name = format!(
     "{}_{}_{}",
     settings.main_binary_name(),
@@ -80,6 +91,8 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
   let (data_dir, _) = generate_data(settings, &package_dir)
     .with_context(|| "Failed to build data folders and files")?;
   copy_custom_files(settings, &data_dir).with_context(|| "Failed to copy custom files")?;
+  generate_changelog_file(settings, &data_dir, source_date_epoch)
+    .with_context(|| "Failed to create changelog file")?;
 
   // Generate control files.
   let control_dir = package_dir.join("control");
@@ -94,16 +107,25 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     .with_context(|| "Failed to create debian-binary file")?;
 
   // Apply tar/gzip/ar to create the final package file.
-  let control_tar_gz_path =
-    tar_and_gzip_dir(control_dir).with_context(|| "Failed to tar/gzip control directory")?;
-  let data_tar_gz_path =
-    tar_and_gzip_dir(data_dir).with_context(|| "Failed to tar/gzip data directory")?;
+  let control_tar_gz_path = tar_and_gzip_dir(control_dir, source_date_epoch)
+    .with_context(|| "Failed to tar/gzip control directory")?;
+  let data_tar_gz_path = tar_and_gzip_dir(data_dir, source_date_epoch)
+    .with_context(|| "Failed to tar/gzip data directory")?;
   create_archive(
     vec![debian_binary_path, control_tar_gz_path, data_tar_gz_path],
     &package_path,
+    source_date_epoch,
   )
   .with_context(|| "Failed to create package archive")?;
-  Ok(vec![package_path])
+
+  let mut paths = vec![package_path];
+  if let Some(signer) = signer {
+    let signature_path = signer
+      .sign(&paths[0])
+      .with_context(|| format!("Failed to sign {}", package_name))?;
+    paths.push(signature_path);
+  }
+  Ok(paths)
 }
 
 /// Generate the debian data folders and files.
@@ -141,23 +163,251 @@ fn generate_desktop_file(settings: &Settings, data_dir: &Path) -> crate::Result<
   let desktop_file_path = data_dir
     .join("usr/share/applications")
     .join(desktop_file_name);
+  let entries = desktop_entries(settings)?;
   let file = &mut common::create_file(&desktop_file_path)?;
   // For more information about the format of this file, see
-  // https://developer.gnome.org/integration-guide/stable/desktop-files.html.en
+  // https://specifications.freedesktop.org/desktop-entry-spec/latest/
   writeln!(file, "[Desktop Entry]")?;
-  if let Some(category) = settings.app_category() {
-    writeln!(file, "Categories={}", category.gnome_desktop_categories())?;
-  } else {
-    writeln!(file, "Categories=")?;
+  for (key, value) in entries {
+    writeln!(file, "{}={}", key, value)?;
   }
+  Ok(())
+}
+
+/// The escaped key/value pairs of the `[Desktop Entry]` group.
+fn desktop_entries(settings: &Settings) -> crate::Result<Vec<(String, String)>> {
+  let bin_name = settings.main_binary_name();
+  let config = &settings.deb().desktop_entry;
+
+  let categories = match &config.categories {
+    Some(categories) => escape_desktop_list(categories),
+    None => settings
+      .app_category()
+      .map(|category| category.gnome_desktop_categories().to_string())
+      .unwrap_or_default(),
+  };
+  let mut entries = vec![("Categories", categories)];
   if !settings.short_description().is_empty() {
-    writeln!(file, "Comment={}", settings.short_description())?;
+    entries.push((
+      "Comment",
+      escape_desktop_value(settings.short_description()),
+    ));
+  }
+  entries.push((
+    "Exec",
+    escape_desktop_value(&desktop_exec(bin_name, config)),
+  ));
+  entries.push(("Icon", bin_name.to_string()));
+  if !config.keywords.is_empty() {
+    entries.push(("Keywords", escape_desktop_list(&config.keywords)));
+  }
+  entries.push(("Name", escape_desktop_value(settings.product_name())));
+  if config.single_main_window {
+    entries.push(("SingleMainWindow", "true".into()));
+  }
+  entries.push((
+    "StartupWMClass",
+    escape_desktop_value(
+      config
+        .startup_wm_class
+        .as_deref()
+        .unwrap_or_else(|| settings.product_name()),
+    ),
+  ));
+  entries.push(("Terminal", config.terminal.to_string()));
+  entries.push(("Type", "Application".into()));
+
+  let mut entries: Vec<(String, String)> = entries
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect();
+  for (key, value) in &config.extra {
+    if !is_valid_desktop_key(key) {
+      return Err(crate::Error::GenericError(format!(
+        "invalid desktop entry key `{}`",
+        key
+      )));
+    }
+    if entries.iter().any(|(k, _)| k == key) {
+      return Err(crate::Error::GenericError(format!(
+        "desktop entry key `{}` is already generated by the bundler",
+        key
+      )));
+    }
+    entries.push((key.clone(), escape_desktop_value(value)));
+  }
+
+  // the localized keys follow the unlocalized ones
+  for (locale, translation) in &config.localized {
+    if !is_valid_locale(locale) {
+      return Err(crate::Error::GenericError(format!(
+        "invalid desktop entry locale `{}`",
+        locale
+      )));
+    }
+    for (key, value) in [
+      ("Name", &translation.name),
+      ("Comment", &translation.comment),
+    ] {
+      if let Some(value) = value {
+        let key = format!("{}[{}]", key, locale);
+        if entries.iter().any(|(k, _)| *k == key) {
+          return Err(crate::Error::GenericError(format!(
+            "desktop entry key `{}` is both localized and an extra entry",
+            key
+          )));
+        }
+        entries.push((key, escape_desktop_value(value)));
+      }
+    }
+  }
+  Ok(entries)
+}
+
+/// Loosely validates a locale, e.g. `de`, `pt_BR` or `sr@latin`.
+fn is_valid_locale(locale: &str) -> bool {
+  !locale.is_empty()
+    && locale
+      .chars()
+      .all(|c| c.is_ascii_alphabetic() || matches!(c, '_' | '-' | '@'))
+}
+
+/// The `Exec` command line, with the field code of the configured or inferred argument.
+fn desktop_exec(bin_name: &str, config: &DesktopEntrySettings) -> String {
+  let exec_arg = config
+    .exec_arg
+    .unwrap_or_else(|| match config.extra.get("MimeType") {
+      Some(mime_types)
+        if mime_types
+          .split(';')
+          .any(|mime_type| mime_type.starts_with("x-scheme-handler/")) =>
+      {
+        DesktopExecArg::MultipleUrls
+      }
+      Some(_) => DesktopExecArg::MultipleFiles,
+      None => DesktopExecArg::None,
+    });
+  let field_code = match exec_arg {
+    DesktopExecArg::None => return quote_exec_arg(bin_name),
+    DesktopExecArg::SingleFile => "%f",
+    DesktopExecArg::MultipleFiles => "%F",
+    DesktopExecArg::SingleUrl => "%u",
+    DesktopExecArg::MultipleUrls => "%U",
+  };
+  format!("{} {}", quote_exec_arg(bin_name), field_code)
+}
+
+/// Quotes an `Exec` argument, which must be double quoted when it contains reserved characters.
+fn quote_exec_arg(arg: &str) -> String {
+  let arg = arg.replace('%', "%%");
+  if !arg
+    .chars()
+    .any(|c| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c))
+  {
+    return arg;
+  }
+  let mut quoted = String::with_capacity(arg.len() + 2);
+  quoted.push('"');
+  for c in arg.chars() {
+    if matches!(c, '"' | '`' | '$' | '\\') {
+      quoted.push('\\');
+    }
+    quoted.push(c);
+  }
+  quoted.push('"');
+  quoted
+}
+
+/// Escapes a desktop entry string value, which must not contain raw control characters.
+fn escape_desktop_value(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      '\t' => escaped.push_str("\\t"),
+      '\r' => escaped.push_str("\\r"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Escapes a desktop entry list, terminating every element with a semicolon.
+fn escape_desktop_list(values: &[String]) -> String {
+  values
+    .iter()
+    .map(|value| format!("{};", escape_desktop_value(value).replace(';', "\\;")))
+    .collect()
+}
+
+/// Whether the key is a valid desktop entry key, optionally followed by a `[locale]`.
+fn is_valid_desktop_key(key: &str) -> bool {
+  let (name, locale) = match key.split_once('[') {
+    Some((name, locale)) => match locale.strip_suffix(']') {
+      Some(locale) => (name, Some(locale)),
+      None => return false,
+    },
+    None => (key, None),
+  };
+  !name.is_empty()
+    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    && locale.map_or(true, |locale| {
+      !locale.is_empty()
+        && locale
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '@' | '.' | '-'))
+    })
+}
+
+/// Converts the Markdown changelog to the Debian format and stores it gzipped under the `data_dir`.
+fn generate_changelog_file(
+  settings: &Settings,
+  data_dir: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<()> {
+  let changelog_path = match settings.changelog() {
+    Some(path) => path,
+    None => return Ok(()),
+  };
+  let markdown = fs::read_to_string(changelog_path)
+    .with_context(|| format!("Failed to read {}", changelog_path.display()))?;
+  let mut releases = changelog::parse(&markdown);
+  if changelog::ensure_release(&mut releases, settings.version_string()) {
+    warn!(
+      "{} has no release for version {}, adding an empty entry to the Debian changelog",
+      changelog_path.display(),
+      settings.version_string()
+    );
   }
-  writeln!(file, "Exec={}", bin_name)?;
-  writeln!(file, "Icon={}", bin_name)?;
-  writeln!(file, "Name={}", settings.product_name())?;
-  writeln!(file, "Terminal=false")?;
-  writeln!(file, "Type=Application")?;
+
+  // releases without a date use the build date, which `SOURCE_DATE_EPOCH` pins for reproducible builds
+  let timestamp = source_date_epoch.unwrap_or_else(|| {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_secs())
+      .unwrap_or_default()
+  });
+  let package = AsKebabCase(settings.product_name()).to_string();
+  let maintainer = settings
+    .author_names()
+    .first()
+    .map(String::as_str)
+    .unwrap_or("unknown");
+  let content = changelog::to_debian(
+    &releases,
+    &package,
+    maintainer,
+    changelog::Date::from_timestamp(timestamp),
+  );
+
+  let dest_path = data_dir
+    .join("usr/share/doc")
+    .join(&package)
+    .join("changelog.gz");
+  let mut encoder = gzip_encoder(common::create_file(&dest_path)?)?;
+  encoder.write_all(content.as_bytes())?;
+  encoder.finish().into_result()?.flush()?;
   Ok(())
 }
 
@@ -213,7 +463,7 @@ fn generate_control_file(
 fn generate_md5sums(control_dir: &Path, data_dir: &Path) -> crate::Result<()> {
   let md5sums_path = control_dir.join("md5sums");
   let mut md5sums_file = common::create_file(&md5sums_path)?;
-  for entry in WalkDir::new(data_dir) {
+  for entry in sorted_walk_dir(data_dir) {
     let entry = entry?;
     let path = entry.path();
     if path.is_dir() {
@@ -266,29 +516,41 @@ fn copy_custom_files(settings: &Settings, data_dir: &Path) -> crate::Result<()>
   Ok(())
 }
 
+/// The icon sizes generated when [`Settings::auto_resize_icons`] is enabled.
+const STANDARD_ICON_SIZES: [u32; 5] = [32, 64, 128, 256, 512];
+
 /// Generate the icon files and store them under the `data_dir`.
 fn generate_icon_files(settings: &Settings, data_dir: &Path) -> crate::Result<BTreeSet<DebIcon>> {
   let base_dir = data_dir.join("usr/share/icons/hicolor");
+  // HiDPI directories are named after the logical size, half of the actual icon size
   let get_dest_path = |width: u32, height: u32, is_high_density: bool| {
+    let scale = if is_high_density { 2 } else { 1 };
     base_dir.join(format!(
       "{}x{}{}/apps/{}.png",
-      width,
-      height,
+      width / scale,
+      height / scale,
       if is_high_density { "@2" } else { "" },
       settings.main_binary_name()
     ))
   };
+  let scalable_dest_path =
+    base_dir.join(format!("scalable/apps/{}.svg", settings.main_binary_name()));
   let mut icons = BTreeSet::new();
   for icon_path in settings.icon_files() {
     let icon_path = icon_path?;
+    if icon_path.extension() == Some(OsStr::new("svg")) {
+      check_svg_icon(&icon_path)?;
+      // only the first SVG is installed, the theme has a single scalable size
+      if !scalable_dest_path.exists() {
+        common::copy_file(&icon_path, &scalable_dest_path)?;
+      }
+      continue;
+    }
     if icon_path.extension() != Some(OsStr::new("png")) {
       continue;
     }
-    // Put file in scope so that it's closed when copying it
     let deb_icon = {
-      let decoder = PngDecoder::new(File::open(&icon_path)?)?;
-      let width = decoder.dimensions().0;
-      let height = decoder.dimensions().1;
+      let (width, height) = png_dimensions(&icon_path)?;
       let is_high_density = common::is_retina(&icon_path);
       let dest_path = get_dest_path(width, height, is_high_density);
       DebIcon {
@@ -303,9 +565,180 @@ fn generate_icon_files(settings: &Settings, data_dir: &Path) -> crate::Result<BT
       icons.insert(deb_icon);
     }
   }
+
+  if settings.auto_resize_icons() {
+    generate_resized_icons(settings, &mut icons, &get_dest_path)?;
+  }
+
+  // an icon twice the size of another one is also installed as the HiDPI variant of that size
+  let high_density_icons: Vec<(PathBuf, DebIcon)> = icons
+    .iter()
+    .filter(|icon| {
+      !icon.is_high_density
+        && icon.width % 2 == 0
+        && icon.height % 2 == 0
+        && icons.iter().any(|other| {
+          !other.is_high_density && other.width * 2 == icon.width && other.height * 2 == icon.height
+        })
+    })
+    .map(|icon| {
+      (
+        icon.path.clone(),
+        DebIcon {
+          width: icon.width,
+          height: icon.height,
+          is_high_density: true,
+          path: get_dest_path(icon.width, icon.height, true),
+        },
+      )
+    })
+    .collect();
+  for (source, deb_icon) in high_density_icons {
+    if !icons.iter().any(|icon| icon.path == deb_icon.path) {
+      common::copy_file(&source, &deb_icon.path)?;
+      icons.insert(deb_icon);
+    }
+  }
+
   Ok(icons)
 }
 
+/// Downscales the largest square icon to the standard sizes that weren't provided.
+fn generate_resized_icons(
+  settings: &Settings,
+  icons: &mut BTreeSet<DebIcon>,
+  get_dest_path: impl Fn(u32, u32, bool) -> PathBuf,
+) -> crate::Result<()> {
+  let (source_path, source_size) = match icons
+    .iter()
+    .filter(|icon| !icon.is_high_density && icon.width == icon.height)
+    .max_by_key(|icon| icon.width)
+  {
+    Some(icon) => (icon.path.clone(), icon.width),
+    None => {
+      warn!("no square PNG icon found, skipping the icon resizing");
+      return Ok(());
+    }
+  };
+
+  let mut too_large = Vec::new();
+  for size in STANDARD_ICON_SIZES {
+    if icons
+      .iter()
+      .any(|icon| !icon.is_high_density && icon.width == size && icon.height == size)
+    {
+      continue;
+    }
+    if size > source_size {
+      too_large.push(size.to_string());
+      continue;
+    }
+    let resized = resized_icon(settings, &source_path, size)?;
+    let deb_icon = DebIcon {
+      width: size,
+      height: size,
+      is_high_density: false,
+      path: get_dest_path(size, size, false),
+    };
+    common::copy_file(&resized, &deb_icon.path)?;
+    icons.insert(deb_icon);
+  }
+  if !too_large.is_empty() {
+    warn!(
+      "the largest icon is {0}x{0}, provide a larger one to generate the {1} sizes; icons are never upscaled",
+      source_size,
+      too_large.join(", ")
+    );
+  }
+  Ok(())
+}
+
+/// Downscales the icon to the given size, reusing the result of a previous build of the same icon.
+fn resized_icon(settings: &Settings, source: &Path, size: u32) -> crate::Result<PathBuf> {
+  let hash = md5::compute(fs::read(source)?);
+  let cache_path = settings
+    .project_out_directory()
+    .join("bundle/icons")
+    .join(format!("{:x}-{}x{}.png", hash, size, size));
+  if cache_path.exists() {
+    return Ok(cache_path);
+  }
+
+  let image = image::open(source)?.resize_exact(size, size, FilterType::Lanczos3);
+  fs::create_dir_all(cache_path.parent().unwrap())?;
+  // write to a temporary file first so an interrupted build doesn't leave a broken cache entry
+  let partial_path = cache_path.with_extension("png.part");
+  image.save_with_format(&partial_path, ImageFormat::Png)?;
+  fs::rename(&partial_path, &cache_path)?;
+  Ok(cache_path)
+}
+
+/// Reads the dimensions of a PNG file from its header.
+fn png_dimensions(path: &Path) -> crate::Result<(u32, u32)> {
+  let decoder = PngDecoder::new(File::open(path)?)?;
+  Ok(decoder.dimensions())
+}
+
+/// Sanity checks an SVG icon so a broken file doesn't end up in the icon theme.
+fn check_svg_icon(path: &Path) -> crate::Result<()> {
+  let content = fs::read_to_string(path)?;
+  let attributes = svg_root_attributes(&content).ok_or_else(|| {
+    crate::Error::GenericError(format!("{} is not a valid SVG document", path.display()))
+  })?;
+  let has_attribute = |name: &str| attributes.iter().any(|(n, _)| *n == name);
+  if has_attribute("viewBox") || (has_attribute("width") && has_attribute("height")) {
+    Ok(())
+  } else {
+    Err(crate::Error::GenericError(format!(
+      "SVG icon {} must have a `viewBox` or a `width` and `height`",
+      path.display()
+    )))
+  }
+}
+
+/// The attributes of the root `<svg>` element, or `None` if the document is malformed.
+fn svg_root_attributes(content: &str) -> Option<Vec<(&str, &str)>> {
+  // skip the XML declaration, comments and doctype before the root element
+  let mut rest = content.trim_start_matches('\u{feff}').trim_start();
+  loop {
+    let r = if let Some(r) = rest.strip_prefix("<?") {
+      r.split_once("?>")?.1
+    } else if let Some(r) = rest.strip_prefix("<!--") {
+      r.split_once("-->")?.1
+    } else if let Some(r) = rest.strip_prefix("<!") {
+      r.split_once('>')?.1
+    } else {
+      break;
+    };
+    rest = r.trim_start();
+  }
+
+  let (tag, _) = rest.strip_prefix("<svg")?.split_once('>')?;
+  if !(tag.is_empty() || tag.starts_with(|c: char| c.is_whitespace() || c == '/')) {
+    return None;
+  }
+  let self_closing = tag.ends_with('/');
+  if !self_closing && !content.contains("</svg>") {
+    return None;
+  }
+
+  let mut attributes = Vec::new();
+  let mut rest = tag.trim_end_matches('/').trim_start();
+  while !rest.is_empty() {
+    let (name, value) = rest.split_once('=')?;
+    let name = name.trim_end();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+      return None;
+    }
+    let value = value.trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let (value, r) = value[1..].split_once(quote)?;
+    attributes.push((name, value));
+    rest = r.trim_start();
+  }
+  Some(attributes)
+}
+
 /// Create an empty file at the given path, creating any parent directories as
 /// needed, then write `data` into the file.
 fn create_file_with_data<P: AsRef<Path>>(path: P, data: &str) -> crate::Result<()> {
@@ -325,18 +758,64 @@ fn total_dir_size(dir: &Path) -> crate::Result<u64> {
   Ok(total)
 }
 
+/// Walks the directory in file name order so the archives list their entries in a stable order.
+fn sorted_walk_dir(dir: &Path) -> WalkDir {
+  WalkDir::new(dir).sort_by(|a, b| a.file_name().cmp(b.file_name()))
+}
+
+/// The modification time of the file, clamped to `SOURCE_DATE_EPOCH` when it is set.
+fn clamped_mtime(metadata: &fs::Metadata, source_date_epoch: Option<u64>) -> u64 {
+  let mtime = metadata
+    .modified()
+    .ok()
+    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default();
+  match source_date_epoch {
+    Some(epoch) => mtime.min(epoch),
+    None => mtime,
+  }
+}
+
+/// Creates a gzip encoder that, like `gzip --no-name`, stores neither a file name nor a timestamp in the header.
+fn gzip_encoder<W: Write>(writer: W) -> io::Result<gzip::Encoder<W>> {
+  let header = gzip::HeaderBuilder::new().modification_time(0).finish();
+  gzip::Encoder::with_options(writer, gzip::EncodeOptions::new().header(header))
+}
+
 /// Writes a tar file to the given writer containing the given directory.
-fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> crate::Result<W> {
+///
+/// With `SOURCE_DATE_EPOCH` set, the entries are owned by root and their modification times are clamped to it.
+fn create_tar_from_dir<P: AsRef<Path>, W: Write>(
+  src_dir: P,
+  dest_file: W,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<W> {
   let src_dir = src_dir.as_ref();
   let mut tar_builder = tar::Builder::new(dest_file);
-  for entry in WalkDir::new(src_dir) {
+  for entry in sorted_walk_dir(src_dir) {
     let entry = entry?;
     let src_path = entry.path();
     if src_path == src_dir {
       continue;
     }
     let dest_path = src_path.strip_prefix(src_dir)?;
-    if entry.file_type().is_dir() {
+    if source_date_epoch.is_some() {
+      let metadata = entry.metadata()?;
+      let mut header = tar::Header::new_gnu();
+      header.set_metadata_in_mode(&metadata, tar::HeaderMode::Deterministic);
+      header.set_mtime(clamped_mtime(&metadata, source_date_epoch));
+      if entry.file_type().is_dir() {
+        tar_builder.append_data(&mut header, dest_path, io::empty())?;
+      } else if entry.file_type().is_symlink() {
+        // the metadata is the link's own, so the link is stored instead of its target
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        tar_builder.append_link(&mut header, dest_path, fs::read_link(src_path)?)?;
+      } else {
+        tar_builder.append_data(&mut header, dest_path, File::open(src_path)?)?;
+      }
+    } else if entry.file_type().is_dir() {
       tar_builder.append_dir(dest_path, src_path)?;
     } else {
       let mut src_file = fs::File::open(src_path)?;
@@ -350,12 +829,15 @@ fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> cr
 /// Creates a `.tar.gz` file from the given directory (placing the new file
 /// within the given directory's parent directory), then deletes the original
 /// directory and returns the path to the new file.
-fn tar_and_gzip_dir<P: AsRef<Path>>(src_dir: P) -> crate::Result<PathBuf> {
+fn tar_and_gzip_dir<P: AsRef<Path>>(
+  src_dir: P,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<PathBuf> {
   let src_dir = src_dir.as_ref();
   let dest_path = src_dir.with_extension("tar.gz");
   let dest_file = common::create_file(&dest_path)?;
-  let gzip_encoder = gzip::Encoder::new(dest_file)?;
-  let gzip_encoder = create_tar_from_dir(src_dir, gzip_encoder)?;
+  let gzip_encoder = gzip_encoder(dest_file)?;
+  let gzip_encoder = create_tar_from_dir(src_dir, gzip_encoder, source_date_epoch)?;
   let mut dest_file = gzip_encoder.finish().into_result()?;
   dest_file.flush()?;
   Ok(dest_path)
@@ -363,11 +845,573 @@ fn tar_and_gzip_dir<P: AsRef<Path>>(src_dir: P) -> crate::Result<PathBuf> {
 
 /// Creates an `ar` archive from the given source files and writes it to the
 /// given destination path.
-fn create_archive(srcs: Vec<PathBuf>, dest: &Path) -> crate::Result<()> {
+///
+/// With `SOURCE_DATE_EPOCH` set, the members are owned by root and their modification times are clamped to it.
+fn create_archive(
+  srcs: Vec<PathBuf>,
+  dest: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<()> {
   let mut builder = ar::Builder::new(common::create_file(dest)?);
   for path in &srcs {
-    builder.append_path(path)?;
+    if source_date_epoch.is_some() {
+      let file = File::open(path)?;
+      let metadata = file.metadata()?;
+      let identifier = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned().into_bytes())
+        .unwrap_or_default();
+      let mut header = ar::Header::from_metadata(identifier, &metadata);
+      header.set_mtime(clamped_mtime(&metadata, source_date_epoch));
+      header.set_uid(0);
+      header.set_gid(0);
+      builder.append(&header, file)?;
+    } else {
+      builder.append_path(path)?;
+    }
   }
   builder.into_inner()?.flush()?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    bundle_project_with_signer, create_tar_from_dir, desktop_exec, escape_desktop_value,
+    generate_desktop_file, generate_icon_files, png_dimensions, svg_root_attributes,
+  };
+  use crate::{
+    bundle::settings::{DesktopEntryLocale, DesktopExecArg},
+    AppCategory, BundleBinary, BundleSettings, DebianSettings, DesktopEntrySettings,
+    PackageSettings, Settings, SettingsBuilder, Signer,
+  };
+
+  fn settings(bundle_settings: BundleSettings) -> Settings {
+    settings_with_out_dir(bundle_settings, std::path::Path::new("target/release"))
+  }
+
+  fn settings_with_out_dir(bundle_settings: BundleSettings, out_dir: &std::path::Path) -> Settings {
+    SettingsBuilder::new()
+      .package_settings(PackageSettings {
+        product_name: "My App".into(),
+        version: "0.1.0".into(),
+        description: "An app".into(),
+        homepage: None,
+        authors: None,
+        default_run: None,
+      })
+      .bundle_settings(bundle_settings)
+      .binaries(vec![BundleBinary::new("my-app".into(), true)])
+      .project_out_directory(out_dir)
+      .target("x86_64-unknown-linux-gnu".into())
+      .build()
+      .unwrap()
+  }
+
+  fn desktop_file(desktop_entry: DesktopEntrySettings) -> crate::Result<String> {
+    let settings = settings(BundleSettings {
+      category: Some(AppCategory::DeveloperTool),
+      short_description: Some("Edits\nfiles".into()),
+      deb: DebianSettings {
+        desktop_entry,
+        ..Default::default()
+      },
+      ..Default::default()
+    });
+    let data_dir = tempfile::tempdir().unwrap();
+    generate_desktop_file(&settings, data_dir.path())?;
+    Ok(
+      std::fs::read_to_string(
+        data_dir
+          .path()
+          .join("usr/share/applications/my-app.desktop"),
+      )
+      .unwrap(),
+    )
+  }
+
+  #[test]
+  fn default_desktop_file() {
+    let expected = r"[Desktop Entry]
+Categories=Development;
+Comment=Edits\nfiles
+Exec=my-app
+Icon=my-app
+Name=My App
+StartupWMClass=My App
+Terminal=false
+Type=Application
+";
+    assert_eq!(desktop_file(Default::default()).unwrap(), expected);
+  }
+
+  #[test]
+  fn full_desktop_file() {
+    let desktop_entry = DesktopEntrySettings {
+      categories: Some(vec!["Development".into(), "IDE".into()]),
+      keywords: vec!["code".into(), "a;b".into(), "back\\slash".into()],
+      startup_wm_class: Some("my-app".into()),
+      terminal: true,
+      single_main_window: true,
+      extra: [
+        ("X-GNOME-UsesNotifications".to_string(), "true".to_string()),
+        (
+          "GenericName[de]".to_string(),
+          "Editor\tfür Code".to_string(),
+        ),
+      ]
+      .into_iter()
+      .collect(),
+      exec_arg: Some(DesktopExecArg::SingleFile),
+      localized: [
+        (
+          "fr",
+          DesktopEntryLocale {
+            name: Some("Mon App".into()),
+            comment: Some("Édite des fichiers".into()),
+          },
+        ),
+        (
+          "de",
+          DesktopEntryLocale {
+            name: Some("Meine Äpp".into()),
+            comment: None,
+          },
+        ),
+        (
+          "pt_BR",
+          DesktopEntryLocale {
+            name: None,
+            comment: Some("Edita arquivos\ncom estilo".into()),
+          },
+        ),
+      ]
+      .into_iter()
+      .map(|(locale, translation)| (locale.to_string(), translation))
+      .collect(),
+    };
+    let expected = r"[Desktop Entry]
+Categories=Development;IDE;
+Comment=Edits\nfiles
+Exec=my-app %f
+Icon=my-app
+Keywords=code;a\;b;back\\slash;
+Name=My App
+SingleMainWindow=true
+StartupWMClass=my-app
+Terminal=true
+Type=Application
+GenericName[de]=Editor\tfür Code
+X-GNOME-UsesNotifications=true
+Name[de]=Meine Äpp
+Name[fr]=Mon App
+Comment[fr]=Édite des fichiers
+Comment[pt_BR]=Edita arquivos\ncom estilo
+";
+    assert_eq!(desktop_file(desktop_entry).unwrap(), expected);
+  }
+
+  #[test]
+  fn invalid_locales() {
+    for locale in ["", "de DE", "de]", "de\n"] {
+      let desktop_entry = DesktopEntrySettings {
+        localized: [(
+          locale.to_string(),
+          DesktopEntryLocale {
+            name: Some("Name".into()),
+            comment: None,
+          },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+      };
+      assert!(
+        desktop_file(desktop_entry).is_err(),
+        "`{}` should be rejected",
+        locale
+      );
+    }
+  }
+
+  #[test]
+  fn invalid_extra_keys() {
+    for key in ["", "Name", "X Key", "Name[de", "Name[]", "Name=Other"] {
+      let desktop_entry = DesktopEntrySettings {
+        extra: [(key.to_string(), "value".to_string())]
+          .into_iter()
+          .collect(),
+        ..Default::default()
+      };
+      assert!(
+        desktop_file(desktop_entry).is_err(),
+        "`{}` should be rejected",
+        key
+      );
+    }
+  }
+
+  #[test]
+  fn exec_field_codes() {
+    for (exec_arg, expected) in [
+      (DesktopExecArg::None, "my-app"),
+      (DesktopExecArg::SingleFile, "my-app %f"),
+      (DesktopExecArg::MultipleFiles, "my-app %F"),
+      (DesktopExecArg::SingleUrl, "my-app %u"),
+      (DesktopExecArg::MultipleUrls, "my-app %U"),
+    ] {
+      let config = DesktopEntrySettings {
+        exec_arg: Some(exec_arg),
+        ..Default::default()
+      };
+      assert_eq!(desktop_exec("my-app", &config), expected);
+    }
+  }
+
+  #[test]
+  fn inferred_exec_field_code() {
+    let exec = |mime_type: Option<&str>| {
+      let config = DesktopEntrySettings {
+        extra: mime_type
+          .map(|mime_type| ("MimeType".to_string(), mime_type.to_string()))
+          .into_iter()
+          .collect(),
+        ..Default::default()
+      };
+      desktop_exec("my-app", &config)
+    };
+    assert_eq!(exec(None), "my-app");
+    assert_eq!(exec(Some("text/plain;image/png;")), "my-app %F");
+    assert_eq!(
+      exec(Some("text/plain;x-scheme-handler/my-app;")),
+      "my-app %U"
+    );
+  }
+
+  #[test]
+  fn exec_quoting() {
+    let config = DesktopEntrySettings {
+      exec_arg: Some(DesktopExecArg::MultipleUrls),
+      ..Default::default()
+    };
+    assert_eq!(desktop_exec("My App", &config), r#""My App" %U"#);
+    assert_eq!(desktop_exec("100%", &config), "100%% %U");
+    assert_eq!(desktop_exec("my $app", &config), r#""my \$app" %U"#);
+    // the string escape rule applies on top of the quoting rule
+    assert_eq!(
+      escape_desktop_value(&desktop_exec("my \"app\"", &config)),
+      r#""my \\"app\\"" %U"#
+    );
+  }
+
+  #[test]
+  fn auto_resized_icons() {
+    let icons_dir = tempfile::tempdir().unwrap();
+    let source = icons_dir.path().join("icon.png");
+    write_png(&source, 300);
+    // an explicitly provided size is never replaced
+    let explicit = icons_dir.path().join("64x64.png");
+    write_png(&explicit, 64);
+    let out_dir = tempfile::tempdir().unwrap();
+    let settings = settings_with_out_dir(
+      BundleSettings {
+        icon: Some(vec![
+          source.display().to_string(),
+          explicit.display().to_string(),
+        ]),
+        auto_resize_icons: true,
+        ..Default::default()
+      },
+      out_dir.path(),
+    );
+
+    let data_dir = tempfile::tempdir().unwrap();
+    generate_icon_files(&settings, data_dir.path()).unwrap();
+    let hicolor = data_dir.path().join("usr/share/icons/hicolor");
+    for size in [32, 64, 128, 256, 300] {
+      let path = hicolor.join(format!("{0}x{0}/apps/my-app.png", size));
+      assert_eq!(png_dimensions(&path).unwrap(), (size, size));
+    }
+    // the largest icon is smaller than 512 pixels, it isn't upscaled
+    assert!(!hicolor.join("512x512").exists());
+    // 128 is half of the generated 256 icon
+    assert!(hicolor.join("128x128@2/apps/my-app.png").is_file());
+    assert_eq!(
+      std::fs::read(hicolor.join("64x64/apps/my-app.png")).unwrap(),
+      std::fs::read(&explicit).unwrap()
+    );
+
+    // the resized icons are cached by the hash of the source icon
+    let cache_dir = out_dir.path().join("bundle/icons");
+    let mut cached: Vec<std::path::PathBuf> = std::fs::read_dir(&cache_dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .collect();
+    cached.sort();
+    assert_eq!(cached.len(), 3);
+    let hash = format!("{:x}", md5::compute(std::fs::read(&source).unwrap()));
+    assert_eq!(
+      cached[0].file_name().unwrap().to_string_lossy(),
+      format!("{}-128x128.png", hash)
+    );
+    // a second build reuses the cache instead of resizing again
+    write_png(&cached[0], 1);
+    let data_dir = tempfile::tempdir().unwrap();
+    generate_icon_files(&settings, data_dir.path()).unwrap();
+    assert_eq!(
+      png_dimensions(
+        &data_dir
+          .path()
+          .join("usr/share/icons/hicolor/128x128/apps/my-app.png")
+      )
+      .unwrap(),
+      (1, 1)
+    );
+  }
+
+  #[test]
+  fn reproducible_package() {
+    const SOURCE_DATE_EPOCH: u64 = 1_000_000_000;
+    let icons_dir = tempfile::tempdir().unwrap();
+    let icon = icons_dir.path().join("32x32.png");
+    write_png(&icon, 32);
+
+    let build = || {
+      let out_dir = tempfile::tempdir().unwrap();
+      std::fs::write(out_dir.path().join("my-app"), "#!/bin/sh\n").unwrap();
+      let settings = settings_with_out_dir(
+        BundleSettings {
+          icon: Some(vec![icon.display().to_string()]),
+          ..Default::default()
+        },
+        out_dir.path(),
+      );
+      let package = bundle_project_with_signer(&settings, None, Some(SOURCE_DATE_EPOCH))
+        .unwrap()
+        .remove(0);
+      (out_dir, std::fs::read(package).unwrap())
+    };
+    let (_first_dir, first) = build();
+    let (_second_dir, second) = build();
+    assert!(first == second, "the packages differ");
+
+    let mut archive = ar::Archive::new(first.as_slice());
+    let mut members = Vec::new();
+    while let Some(entry) = archive.next_entry() {
+      let entry = entry.unwrap();
+      assert_eq!(entry.header().mtime(), SOURCE_DATE_EPOCH);
+      assert_eq!(entry.header().uid(), 0);
+      members.push(String::from_utf8_lossy(entry.header().identifier()).into_owned());
+    }
+    assert_eq!(
+      members,
+      vec!["debian-binary", "control.tar.gz", "data.tar.gz"]
+    );
+  }
+
+  #[test]
+  fn reproducible_tar_symlink() {
+    const SOURCE_DATE_EPOCH: u64 = 1_000_000_000;
+    let src_dir = tempfile::tempdir().unwrap();
+    std::fs::write(src_dir.path().join("my-app"), "#!/bin/sh\n").unwrap();
+    std::os::unix::fs::symlink("my-app", src_dir.path().join("my-app-link")).unwrap();
+
+    let tar = create_tar_from_dir(src_dir.path(), Vec::new(), Some(SOURCE_DATE_EPOCH)).unwrap();
+    let mut archive = tar::Archive::new(tar.as_slice());
+    let entries: Vec<_> = archive
+      .entries()
+      .unwrap()
+      .map(|entry| {
+        let entry = entry.unwrap();
+        let header = entry.header();
+        assert_eq!(header.mtime().unwrap(), SOURCE_DATE_EPOCH);
+        assert_eq!(header.uid().unwrap(), 0);
+        (
+          entry.path().unwrap().display().to_string(),
+          header.entry_type(),
+          header.size().unwrap(),
+          entry
+            .link_name()
+            .unwrap()
+            .map(|target| target.display().to_string()),
+        )
+      })
+      .collect();
+    assert_eq!(
+      entries,
+      vec![
+        ("my-app".into(), tar::EntryType::Regular, 10, None),
+        (
+          "my-app-link".into(),
+          tar::EntryType::Symlink,
+          0,
+          Some("my-app".into())
+        ),
+      ]
+    );
+  }
+
+  /// Records the signed files instead of signing them.
+  #[derive(Default)]
+  struct FakeSigner(std::cell::RefCell<Vec<std::path::PathBuf>>);
+
+  impl Signer for FakeSigner {
+    fn sign(&self, path: &std::path::Path) -> crate::Result<std::path::PathBuf> {
+      self.0.borrow_mut().push(path.to_path_buf());
+      let signature_path = path.with_extension("deb.asc");
+      std::fs::write(&signature_path, "signature")?;
+      Ok(signature_path)
+    }
+  }
+
+  #[test]
+  fn signed_package() {
+    let out_dir = tempfile::tempdir().unwrap();
+    std::fs::write(out_dir.path().join("my-app"), "#!/bin/sh\n").unwrap();
+    let settings = settings_with_out_dir(Default::default(), out_dir.path());
+    let signer = FakeSigner::default();
+    let paths = bundle_project_with_signer(&settings, Some(&signer), None).unwrap();
+
+    let package = out_dir.path().join("bundle/deb/my-app_0.1.0_amd64.deb");
+    assert_eq!(*signer.0.borrow(), vec![package.clone()]);
+    assert_eq!(
+      paths,
+      vec![
+        package,
+        out_dir.path().join("bundle/deb/my-app_0.1.0_amd64.deb.asc")
+      ]
+    );
+  }
+
+  fn write_png(path: &std::path::Path, size: u32) {
+    image::RgbaImage::new(size, size).save(path).unwrap();
+  }
+
+  fn icon_settings(icons: &[&std::path::Path]) -> Settings {
+    settings(BundleSettings {
+      icon: Some(icons.iter().map(|p| p.display().to_string()).collect()),
+      ..Default::default()
+    })
+  }
+
+  #[test]
+  fn scalable_icon_layout() {
+    let icons_dir = tempfile::tempdir().unwrap();
+    let small = icons_dir.path().join("32x32.png");
+    let large = icons_dir.path().join("128x128.png");
+    let svg = icons_dir.path().join("icon.svg");
+    write_png(&small, 32);
+    write_png(&large, 128);
+    std::fs::write(
+      &svg,
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Created with Inkscape -->
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 128 128"><rect width="128" height="128"/></svg>
+"#,
+    )
+    .unwrap();
+
+    let data_dir = tempfile::tempdir().unwrap();
+    let icons =
+      generate_icon_files(&icon_settings(&[&small, &large, &svg]), data_dir.path()).unwrap();
+
+    let hicolor = data_dir.path().join("usr/share/icons/hicolor");
+    assert!(hicolor.join("32x32/apps/my-app.png").is_file());
+    assert!(hicolor.join("128x128/apps/my-app.png").is_file());
+    assert!(hicolor.join("scalable/apps/my-app.svg").is_file());
+    assert!(!hicolor.join("64x64@2").exists());
+    let sizes: Vec<u32> = icons.iter().map(|icon| icon.width).collect();
+    assert_eq!(sizes, vec![32, 128]);
+  }
+
+  #[test]
+  fn high_density_icon_layout() {
+    let icons_dir = tempfile::tempdir().unwrap();
+    let mut paths = Vec::new();
+    for size in [32, 64, 128, 256] {
+      let path = icons_dir.path().join(format!("{}x{}.png", size, size));
+      write_png(&path, size);
+      paths.push(path);
+    }
+    // the explicit naming convention wins over the inferred variant
+    let explicit = icons_dir.path().join("icon@2x.png");
+    write_png(&explicit, 64);
+    paths.insert(0, explicit);
+
+    let data_dir = tempfile::tempdir().unwrap();
+    let paths: Vec<&std::path::Path> = paths.iter().map(|p| p.as_path()).collect();
+    let icons = generate_icon_files(&icon_settings(&paths), data_dir.path()).unwrap();
+
+    let hicolor = data_dir.path().join("usr/share/icons/hicolor");
+    let mut dirs: Vec<String> = std::fs::read_dir(&hicolor)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+      .collect();
+    dirs.sort();
+    assert_eq!(
+      dirs,
+      vec![
+        "128x128",
+        "128x128@2",
+        "256x256",
+        "32x32",
+        "32x32@2",
+        "64x64",
+        "64x64@2"
+      ]
+    );
+    for dir in &dirs {
+      assert!(hicolor.join(dir).join("apps/my-app.png").is_file());
+    }
+    assert_eq!(icons.len(), 7);
+    assert_eq!(
+      icons
+        .iter()
+        .filter(|icon| icon.is_high_density && icon.width == 64)
+        .count(),
+      1
+    );
+  }
+
+  #[test]
+  fn png_only_icon_layout() {
+    let icons_dir = tempfile::tempdir().unwrap();
+    let icon = icons_dir.path().join("32x32.png");
+    write_png(&icon, 32);
+
+    let data_dir = tempfile::tempdir().unwrap();
+    generate_icon_files(&icon_settings(&[&icon]), data_dir.path()).unwrap();
+
+    let hicolor = data_dir.path().join("usr/share/icons/hicolor");
+    assert!(hicolor.join("32x32/apps/my-app.png").is_file());
+    assert!(!hicolor.join("scalable").exists());
+  }
+
+  #[test]
+  fn invalid_svg_icon() {
+    let icons_dir = tempfile::tempdir().unwrap();
+    let svg = icons_dir.path().join("icon.svg");
+    std::fs::write(&svg, r#"<svg xmlns="http://www.w3.org/2000/svg"></svg>"#).unwrap();
+
+    let data_dir = tempfile::tempdir().unwrap();
+    assert!(generate_icon_files(&icon_settings(&[&svg]), data_dir.path()).is_err());
+  }
+
+  #[test]
+  fn svg_attributes() {
+    assert_eq!(
+      svg_root_attributes(r#"<svg width="16" height='16'/>"#),
+      Some(vec![("width", "16"), ("height", "16")])
+    );
+    assert_eq!(
+      svg_root_attributes(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg viewBox=\"0 0 1 1\">\n</svg>"
+      ),
+      Some(vec![("viewBox", "0 0 1 1")])
+    );
+    assert_eq!(svg_root_attributes("<svg>"), None);
+    assert_eq!(svg_root_attributes("<svgx/>"), None);
+    assert_eq!(svg_root_attributes(r#"<svg width="16/>"#), None);
+    assert_eq!(svg_root_attributes("<html/>"), None);
+  }
+}
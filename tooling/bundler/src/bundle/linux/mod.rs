@@ -4,5 +4,7 @@
 // SPDX-License-Identifier: MIT
 
 pub mod appimage;
+mod arch;
+mod changelog;
 pub mod debian;
 pub mod rpm;
@@ -127,6 +127,12 @@ pub fn bundle_project(settings: &Settings, bundles: &[Bundle]) -> crate::Result<
     args.push(&license_path_ref);
   }
 
+  if settings.log_level() >= log::Level::Debug {
+    args.push("--hdiutil-verbose");
+  } else if settings.log_level() <= log::Level::Warn {
+    args.push("--hdiutil-quiet");
+  }
+
   // Issue #592 - Building MacOS dmg files on CI
   // https://github.com/tauri-apps/tauri/issues/592
   if let Some(value) = env::var_os("CI") {
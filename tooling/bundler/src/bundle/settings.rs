@@ -5,14 +5,14 @@
 
 use super::category::AppCategory;
 use crate::bundle::{common, platform::target_triple};
-pub use tauri_utils::config::WebviewInstallMode;
+pub use tauri_utils::config::{DesktopEntryLocale, DesktopExecArg, WebviewInstallMode};
 use tauri_utils::{
   config::BundleType,
   resources::{external_binaries, ResourcePaths},
 };
 
 use std::{
-  collections::HashMap,
+  collections::{BTreeMap, HashMap},
   path::{Path, PathBuf},
 };
 
@@ -149,6 +149,40 @@ pub struct DebianSettings {
   /// List of custom files to add to the deb package.
   /// Maps the path on the debian package to the path of the file to include (relative to the current working directory).
   pub files: HashMap<PathBuf, PathBuf>,
+  /// Additional entries for the generated `.desktop` file.
+  pub desktop_entry: DesktopEntrySettings,
+}
+
+/// The GPG signing settings of the Linux bundles.
+#[derive(Clone, Debug, Default)]
+pub struct LinuxSigningSettings {
+  /// The GPG key to sign with. Defaults to the default key of the keyring.
+  pub gpg_key: Option<String>,
+  /// The GPG home directory containing the keyring.
+  pub gpg_home: Option<PathBuf>,
+  /// Whether to create a detached `.asc` signature of the Debian package.
+  pub deb: bool,
+}
+
+/// The `[Desktop Entry]` settings of the `.desktop` file generated for the Linux bundles.
+#[derive(Clone, Debug, Default)]
+pub struct DesktopEntrySettings {
+  /// The menu categories. Defaults to the categories matching the app category.
+  pub categories: Option<Vec<String>>,
+  /// Keywords used by application launchers to find the application.
+  pub keywords: Vec<String>,
+  /// The `StartupWMClass` key. Defaults to the product name.
+  pub startup_wm_class: Option<String>,
+  /// Whether the application runs in a terminal.
+  pub terminal: bool,
+  /// Whether the application only has a single main window.
+  pub single_main_window: bool,
+  /// The `Exec` field code. Defaults to the one matching the `MimeType` extra entry.
+  pub exec_arg: Option<DesktopExecArg>,
+  /// Additional key/value pairs, written in key order after the generated entries.
+  pub extra: BTreeMap<String, String>,
+  /// Translations of the name and comment, written in locale order after the other entries.
+  pub localized: BTreeMap<String, DesktopEntryLocale>,
 }
 
 /// The macOS bundle settings.
@@ -300,6 +334,8 @@ pub struct BundleSettings {
   pub publisher: Option<String>,
   /// the app's icon list.
   pub icon: Option<Vec<String>>,
+  /// whether the missing standard icon sizes are generated from the largest PNG icon.
+  pub auto_resize_icons: bool,
   /// the app's resources to bundle.
   ///
   /// each item can be a path to a file or a path to a folder.
@@ -314,6 +350,10 @@ pub struct BundleSettings {
   pub short_description: Option<String>,
   /// the app's long description.
   pub long_description: Option<String>,
+  /// the path to the app's Markdown changelog.
+  pub changelog: Option<PathBuf>,
+  /// the signing settings of the Linux bundles.
+  pub linux_signing: Option<LinuxSigningSettings>,
   // Bundles for other binaries:
   /// Configuration map for the apps to bundle.
   pub bin: Option<HashMap<String, BundleSettings>>,
@@ -410,6 +450,8 @@ pub struct Settings {
   binaries: Vec<BundleBinary>,
   /// The target triple.
   target: String,
+  /// The log level the bundlers use to configure the output of the tools they run.
+  log_level: log::Level,
 }
 
 /// A builder for [`Settings`].
@@ -421,6 +463,7 @@ pub struct SettingsBuilder {
   bundle_settings: BundleSettings,
   binaries: Vec<BundleBinary>,
   target: Option<String>,
+  log_level: Option<log::Level>,
 }
 
 impl SettingsBuilder {
@@ -473,6 +516,13 @@ impl SettingsBuilder {
     self
   }
 
+  /// Sets the log level. Defaults to [`log::Level::Info`].
+  #[must_use]
+  pub fn log_level(mut self, level: log::Level) -> Self {
+    self.log_level.replace(level);
+    self
+  }
+
   /// Builds a Settings from the CLI args.
   ///
   /// Package settings will be read from Cargo.toml.
@@ -501,6 +551,7 @@ impl SettingsBuilder {
         ..self.bundle_settings
       },
       target,
+      log_level: self.log_level.unwrap_or(log::Level::Info),
     })
   }
 }
@@ -516,6 +567,11 @@ impl Settings {
     &self.target
   }
 
+  /// Returns the log level.
+  pub fn log_level(&self) -> log::Level {
+    self.log_level
+  }
+
   /// Returns the architecture for the binary being bundled (e.g. "arm", "x86" or "x86_64").
   pub fn binary_arch(&self) -> &str {
     if self.target.starts_with("x86_64") {
@@ -688,6 +744,16 @@ impl Settings {
     }
   }
 
+  /// Whether the missing standard icon sizes are generated from the largest PNG icon.
+  pub fn auto_resize_icons(&self) -> bool {
+    self.bundle_settings.auto_resize_icons
+  }
+
+  /// Returns the path to the Markdown changelog.
+  pub fn changelog(&self) -> Option<&Path> {
+    self.bundle_settings.changelog.as_deref()
+  }
+
   /// Returns the authors as a comma-separated string.
   pub fn authors_comma_separated(&self) -> Option<String> {
     let names = self.author_names();
@@ -727,6 +793,11 @@ impl Settings {
     &self.bundle_settings.deb
   }
 
+  /// Returns the signing settings of the Linux bundles.
+  pub fn linux_signing(&self) -> Option<&LinuxSigningSettings> {
+    self.bundle_settings.linux_signing.as_ref()
+  }
+
   /// Returns the MacOS settings.
   pub fn macos(&self) -> &MacOsSettings {
     &self.bundle_settings.macos
@@ -750,3 +821,36 @@ impl Settings {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{PackageSettings, SettingsBuilder};
+
+  fn builder() -> SettingsBuilder {
+    SettingsBuilder::new()
+      .package_settings(PackageSettings {
+        product_name: "app".into(),
+        version: "0.1.0".into(),
+        description: "An app".into(),
+        homepage: None,
+        authors: None,
+        default_run: None,
+      })
+      .project_out_directory("target/release")
+      .target("x86_64-unknown-linux-gnu".into())
+  }
+
+  #[test]
+  fn default_log_level() {
+    let settings = builder().build().unwrap();
+    assert_eq!(settings.log_level(), log::Level::Info);
+  }
+
+  #[test]
+  fn log_level_reaches_settings() {
+    for level in [log::Level::Warn, log::Level::Debug, log::Level::Trace] {
+      let settings = builder().log_level(level).build().unwrap();
+      assert_eq!(settings.log_level(), level);
+    }
+  }
+}
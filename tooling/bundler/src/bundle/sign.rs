@@ -0,0 +1,212 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  fmt,
+  io::{self, Write},
+  path::{Path, PathBuf},
+  process::{Command, Stdio},
+};
+
+use log::info;
+
+/// The environment variable holding the passphrase of the GPG key.
+const GPG_PASSPHRASE_VAR: &str = "TAURI_GPG_PASSPHRASE";
+
+/// Creates detached signatures of the bundled artifacts.
+pub trait Signer {
+  /// Signs the file, returning the path of the detached signature written next to it.
+  fn sign(&self, path: &Path) -> crate::Result<PathBuf>;
+}
+
+/// Signs files with `gpg`, writing ASCII armored `.asc` signatures.
+///
+/// The passphrase of the key is read from the `TAURI_GPG_PASSPHRASE` environment variable,
+/// otherwise `gpg` asks its agent for it.
+#[derive(Clone, Default)]
+pub struct GpgSigner {
+  key: Option<String>,
+  home: Option<PathBuf>,
+  passphrase: Option<String>,
+}
+
+impl fmt::Debug for GpgSigner {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // the passphrase must never end up in the logs
+    f.debug_struct("GpgSigner")
+      .field("key", &self.key)
+      .field("home", &self.home)
+      .field(
+        "passphrase",
+        &self.passphrase.as_ref().map(|_| "<redacted>"),
+      )
+      .finish()
+  }
+}
+
+impl GpgSigner {
+  /// Signs with the given key, a key id, fingerprint or user id, or the default key of the keyring.
+  ///
+  /// `home` overrides the GPG home directory containing the keyring.
+  pub fn new(key: Option<String>, home: Option<PathBuf>) -> Self {
+    Self {
+      key,
+      home,
+      passphrase: std::env::var(GPG_PASSPHRASE_VAR).ok(),
+    }
+  }
+
+  fn command(&self, path: &Path, signature_path: &Path) -> Command {
+    let mut command = Command::new("gpg");
+    command.args(["--batch", "--yes", "--armor", "--detach-sign"]);
+    if let Some(home) = &self.home {
+      command.arg("--homedir").arg(home);
+    }
+    if let Some(key) = &self.key {
+      command.args(["--local-user", key]);
+    }
+    if self.passphrase.is_some() {
+      // the passphrase is written to stdin so it doesn't show up in the process list
+      command.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+    }
+    command.arg("--output").arg(signature_path).arg(path);
+    command
+  }
+}
+
+impl Signer for GpgSigner {
+  fn sign(&self, path: &Path) -> crate::Result<PathBuf> {
+    let signature_path = signature_path(path, "asc");
+    info!(action = "Signing"; "{} with GPG", path.display());
+
+    run_gpg(
+      self.command(path, &signature_path),
+      self.passphrase.as_deref(),
+    )
+    .map_err(|e| {
+      crate::Error::GenericError(format!("failed to sign {} with gpg: {}", path.display(), e))
+    })?;
+    Ok(signature_path)
+  }
+}
+
+/// Runs `gpg`, writing the passphrase to its stdin, and fails with its stderr if it fails.
+fn run_gpg(mut command: Command, passphrase: Option<&str>) -> Result<(), String> {
+  let mut child = command
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| format!("failed to run gpg: {}", e))?;
+  // dropping stdin closes it, so gpg doesn't wait for a passphrase it won't get
+  let mut stdin = child.stdin.take().unwrap();
+  let written = passphrase.map_or(Ok(()), |passphrase| {
+    stdin
+      .write_all(passphrase.as_bytes())
+      .and_then(|_| stdin.write_all(b"\n"))
+  });
+  drop(stdin);
+
+  // gpg exiting before reading the passphrase breaks the pipe, its stderr tells why
+  let output = child.wait_with_output().map_err(|e| e.to_string())?;
+  if !output.status.success() {
+    return Err(format!(
+      "{}: {}",
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+  match written {
+    Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+      Err(format!("failed to write the passphrase: {}", e))
+    }
+    _ => Ok(()),
+  }
+}
+
+/// The path of the detached signature of the file, the file path with the extension appended.
+fn signature_path(path: &Path, extension: &str) -> PathBuf {
+  let mut signature_path = OsString::from(path.as_os_str());
+  signature_path.push(".");
+  signature_path.push(extension);
+  signature_path.into()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{run_gpg, signature_path, GpgSigner};
+  use std::{path::Path, process::Command};
+
+  #[test]
+  fn gpg_command() {
+    let signer = GpgSigner {
+      key: Some("0xDEADBEEF".into()),
+      home: Some("/keys".into()),
+      passphrase: Some("hunter2".into()),
+    };
+    let command = signer.command(Path::new("app.deb"), Path::new("app.deb.asc"));
+    let args: Vec<String> = command
+      .get_args()
+      .map(|arg| arg.to_string_lossy().into_owned())
+      .collect();
+    assert_eq!(
+      args,
+      vec![
+        "--batch",
+        "--yes",
+        "--armor",
+        "--detach-sign",
+        "--homedir",
+        "/keys",
+        "--local-user",
+        "0xDEADBEEF",
+        "--pinentry-mode",
+        "loopback",
+        "--passphrase-fd",
+        "0",
+        "--output",
+        "app.deb.asc",
+        "app.deb"
+      ]
+    );
+    assert!(!format!("{:?}", signer).contains("hunter2"));
+  }
+
+  #[test]
+  fn gpg_agent_command() {
+    let command = GpgSigner::default().command(Path::new("app.deb"), Path::new("app.deb.asc"));
+    assert!(!command.get_args().any(|arg| arg == "--passphrase-fd"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn gpg_exits_before_reading_the_passphrase() {
+    let mut command = Command::new("sh");
+    command.args([
+      "-c",
+      "exec 0<&-; echo 'gpg: signing failed: No secret key' >&2; exit 2",
+    ]);
+    // larger than the pipe buffer, so writing it fails once gpg closed stdin
+    let passphrase = "x".repeat(1 << 20);
+    let error = run_gpg(command, Some(&passphrase)).unwrap_err();
+    assert!(
+      error.ends_with("gpg: signing failed: No secret key"),
+      "{}",
+      error
+    );
+
+    let mut command = Command::new("sh");
+    command.args(["-c", "exec 0<&-"]);
+    assert_eq!(run_gpg(command, Some(&passphrase)), Ok(()));
+  }
+
+  #[test]
+  fn signature_paths() {
+    assert_eq!(
+      signature_path(Path::new("out/app_0.1.0_amd64.deb"), "asc"),
+      Path::new("out/app_0.1.0_amd64.deb.asc")
+    );
+  }
+}
@@ -338,6 +338,10 @@ fn run_candle(
     args.push("-fips".into());
   }
 
+  if settings.log_level() >= log::Level::Debug {
+    args.push("-v".into());
+  }
+
   let candle_exe = wix_toolset_path.join("candle.exe");
 
   info!(action = "Running"; "candle for {:?}", wxs_file_path);
@@ -841,7 +845,7 @@ pub fn build_wix_app_installer(
       fileout.write_all(locale_contents.as_bytes())?;
     }
 
-    let arguments = vec![
+    let mut arguments = vec![
       format!(
         "-cultures:{}",
         if language == "en-US" {
@@ -854,6 +858,9 @@ pub fn build_wix_app_installer(
       locale_path.display().to_string(),
       "*.wixobj".into(),
     ];
+    if settings.log_level() >= log::Level::Debug {
+      arguments.push("-v".into());
+    }
     let msi_output_path = output_path.join("output.msi");
     let msi_path = app_installer_output_path(settings, &language, updater)?;
     create_dir_all(msi_path.parent().unwrap())?;
@@ -6,8 +6,11 @@ use crate::{
   helpers::{
     app_paths::{app_dir, tauri_dir},
     command_env,
-    config::{get as get_config, AppUrl, HookCommand, WindowUrl, MERGE_CONFIG_EXTENSION_NAME},
-    updater_signature::{read_key_from_file, secret_key as updater_secret_key, sign_file},
+    config::{
+      get_with_targets as get_config_with_targets, AppUrl, HookCommand, WindowUrl,
+      MERGE_CONFIG_EXTENSION_NAME,
+    },
+    updater_signature::{read_key_from_file, secret_key as updater_secret_key, UpdaterSigner},
   },
   interface::{AppInterface, AppSettings, Interface},
   CommandExt, Result,
@@ -16,11 +19,12 @@ use anyhow::{bail, Context};
 use clap::{ArgAction, Parser};
 use log::{debug, error, info, warn};
 use std::{
+  collections::HashMap,
   env::{set_current_dir, var_os},
   path::{Path, PathBuf},
   process::Command,
 };
-use tauri_bundler::bundle::{bundle_project, Bundle, PackageType};
+use tauri_bundler::bundle::{bundle_project, Bundle, PackageType, Signer};
 
 #[derive(Debug, Clone, Parser)]
 #[clap(about = "Tauri build")]
@@ -47,16 +51,23 @@ pub struct Options {
   /// If `none` is specified, the bundler will be skipped.
   ///
   /// Note that the `updater` bundle is not automatically added so you must specify it if the updater is enabled.
+  ///
+  /// The `tauri.<bundle>.conf.json` configuration overlay of each bundle is merged with the configuration if it exists.
   #[clap(short, long, action = ArgAction::Append, num_args(0..))]
   pub bundles: Option<Vec<String>>,
   /// JSON string or path to JSON file to merge with tauri.conf.json
   #[clap(short, long)]
   pub config: Option<String>,
+  /// Skips the frontend and Rust builds and only bundles the binaries of a previous build.
+  ///
+  /// The `--debug` and `--target` flags must match the ones used to build the binaries.
+  #[clap(long)]
+  pub bundle_only: bool,
   /// Command line arguments passed to the runner
   pub args: Vec<String>,
 }
 
-pub fn command(mut options: Options) -> Result<()> {
+pub fn command(mut options: Options, log_level: log::Level) -> Result<()> {
   let (merge_config, merge_config_path) = if let Some(config) = &options.config {
     if config.starts_with('{') {
       (Some(config.to_string()), None)
@@ -76,7 +87,15 @@ pub fn command(mut options: Options) -> Result<()> {
   let tauri_path = tauri_dir();
   set_current_dir(&tauri_path).with_context(|| "failed to change current working directory")?;
 
-  let config = get_config(options.config.as_deref())?;
+  // the bundle targets must be known before the config is loaded so their overlays are merged
+  let bundle_targets: Vec<String> = options
+    .bundles
+    .iter()
+    .flatten()
+    .flat_map(|n| n.split(',').map(|s| s.to_string()).collect::<Vec<String>>())
+    .take_while(|name| name != "none")
+    .collect();
+  let config = get_config_with_targets(options.config.as_deref(), &bundle_targets)?;
 
   let config_guard = config.lock().unwrap();
   let config_ = config_guard.as_ref().unwrap();
@@ -110,46 +129,56 @@ pub fn command(mut options: Options) -> Result<()> {
     std::process::exit(1);
   }
 
+  if options.bundle_only && !config_.tauri.bundle.active {
+    bail!("The `--bundle-only` flag requires `tauri.conf.json > tauri > bundle > active` to be enabled.");
+  }
+
   let mut interface = AppInterface::new(config_, options.target.clone())?;
   let app_settings = interface.app_settings();
   let interface_options = options.clone().into();
 
-  if let Some(before_build) = config_.build.before_build_command.clone() {
-    run_hook(
-      "beforeBuildCommand",
-      before_build,
-      &interface,
-      options.debug,
-    )?;
+  if !options.bundle_only {
+    if let Some(before_build) = config_.build.before_build_command.clone() {
+      run_hook(
+        "beforeBuildCommand",
+        before_build,
+        &interface,
+        options.debug,
+        HashMap::new(),
+      )?;
+    }
   }
 
-  if let AppUrl::Url(WindowUrl::App(web_asset_path)) = &config_.build.dist_dir {
-    if !web_asset_path.exists() {
-      return Err(anyhow::anyhow!(
-          "Unable to find your web assets, did you forget to build your web app? Your distDir is set to \"{:?}\".",
-          web_asset_path
-        ));
-    }
-    if web_asset_path.canonicalize()?.file_name() == Some(std::ffi::OsStr::new("src-tauri")) {
-      return Err(anyhow::anyhow!(
-            "The configured distDir is the `src-tauri` folder.
-            Please isolate your web assets on a separate folder and update `tauri.conf.json > build > distDir`.",
+  // the web assets are already embedded in the binary when only bundling
+  if !options.bundle_only {
+    if let AppUrl::Url(WindowUrl::App(web_asset_path)) = &config_.build.dist_dir {
+      if !web_asset_path.exists() {
+        return Err(anyhow::anyhow!(
+            "Unable to find your web assets, did you forget to build your web app? Your distDir is set to \"{:?}\".",
+            web_asset_path
           ));
-    }
+      }
+      if web_asset_path.canonicalize()?.file_name() == Some(std::ffi::OsStr::new("src-tauri")) {
+        return Err(anyhow::anyhow!(
+              "The configured distDir is the `src-tauri` folder.
+            Please isolate your web assets on a separate folder and update `tauri.conf.json > build > distDir`.",
+            ));
+      }
 
-    let mut out_folders = Vec::new();
-    for folder in &["node_modules", "src-tauri", "target"] {
-      if web_asset_path.join(folder).is_dir() {
-        out_folders.push(folder.to_string());
+      let mut out_folders = Vec::new();
+      for folder in &["node_modules", "src-tauri", "target"] {
+        if web_asset_path.join(folder).is_dir() {
+          out_folders.push(folder.to_string());
+        }
+      }
+      if !out_folders.is_empty() {
+        return Err(anyhow::anyhow!(
+              "The configured distDir includes the `{:?}` {}. Please isolate your web assets on a separate folder and update `tauri.conf.json > build > distDir`.",
+              out_folders,
+              if out_folders.len() == 1 { "folder" }else { "folders" }
+            )
+          );
       }
-    }
-    if !out_folders.is_empty() {
-      return Err(anyhow::anyhow!(
-            "The configured distDir includes the `{:?}` {}. Please isolate your web assets on a separate folder and update `tauri.conf.json > build > distDir`.",
-            out_folders,
-            if out_folders.len() == 1 { "folder" }else { "folders" }
-          )
-        );
     }
   }
 
@@ -165,7 +194,9 @@ pub fn command(mut options: Options) -> Result<()> {
   let bin_path = app_settings.app_binary_path(&interface_options)?;
   let out_dir = bin_path.parent().unwrap();
 
-  interface.build(interface_options)?;
+  if !options.bundle_only {
+    interface.build(interface_options)?;
+  }
 
   let app_settings = interface.app_settings();
 
@@ -202,7 +233,11 @@ pub fn command(mut options: Options) -> Result<()> {
     };
 
     if let Some(types) = &package_types {
-      if config_.tauri.updater.active && !types.contains(&PackageType::Updater) {
+      // Flatpak installs the updates of the `flatpak` updater source
+      if config_.tauri.updater.active
+        && config_.tauri.updater.source == tauri_utils::config::UpdaterSource::Endpoints
+        && !types.contains(&PackageType::Updater)
+      {
         warn!("The updater is enabled but the bundle target list does not contain `updater`, so the updater artifacts won't be generated.");
       }
     }
@@ -210,19 +245,40 @@ pub fn command(mut options: Options) -> Result<()> {
     // if we have a package to bundle, let's run the `before_bundle_command`.
     if package_types.as_ref().map_or(true, |p| !p.is_empty()) {
       if let Some(before_bundle) = config_.build.before_bundle_command.clone() {
+        // let the hook find the build artifacts
+        let mut env = HashMap::new();
+        env.insert(
+          "TAURI_PROFILE",
+          if options.debug { "debug" } else { "release" }.to_string(),
+        );
+        env.insert("TAURI_OUT_DIR", out_dir.display().to_string());
         run_hook(
           "beforeBundleCommand",
           before_bundle,
           &interface,
           options.debug,
+          env,
         )?;
       }
     }
 
+    let bundle_only = options.bundle_only;
     let settings = app_settings
-      .get_bundler_settings(&options.into(), config_, out_dir, package_types)
+      .get_bundler_settings(&options.into(), config_, out_dir, package_types, log_level)
       .with_context(|| "failed to build bundler settings")?;
 
+    if bundle_only {
+      for binary in settings.binaries() {
+        let path = settings.binary_path(binary);
+        if !path.exists() {
+          bail!(
+            "Unable to find the binary at {}, did you forget to run `tauri build` with the same `--debug` and `--target` flags?",
+            path.display()
+          );
+        }
+      }
+    }
+
     // set env vars used by the bundler
     #[cfg(target_os = "linux")]
     {
@@ -293,6 +349,7 @@ pub fn command(mut options: Options) -> Result<()> {
       let pubkey = base64::decode(&config_.tauri.updater.pubkey)?;
       let pub_key_decoded = String::from_utf8_lossy(&pubkey);
       let public_key = minisign::PublicKeyBox::from_string(&pub_key_decoded)?.into_public_key()?;
+      let signer = UpdaterSigner::new(secret_key, public_key);
 
       // make sure we have our package built
       let mut signed_paths = Vec::new();
@@ -301,13 +358,7 @@ pub fn command(mut options: Options) -> Result<()> {
         // another type of updater package who require multiple file signature
         for path in elem.bundle_paths.iter() {
           // sign our path from environment variables
-          let (signature_path, signature) = sign_file(&secret_key, path)?;
-          if signature.keynum() != public_key.keynum() {
-            return Err(anyhow::anyhow!(
-              "The updater secret key from `TAURI_PRIVATE_KEY` does not match the public key defined in `tauri.conf.json > tauri > updater > pubkey`."
-            ));
-          }
-          signed_paths.append(&mut vec![signature_path]);
+          signed_paths.push(signer.sign(path)?);
         }
       }
 
@@ -318,7 +369,13 @@ pub fn command(mut options: Options) -> Result<()> {
   Ok(())
 }
 
-fn run_hook(name: &str, hook: HookCommand, interface: &AppInterface, debug: bool) -> Result<()> {
+fn run_hook(
+  name: &str,
+  hook: HookCommand,
+  interface: &AppInterface,
+  debug: bool,
+  extra_env: HashMap<&str, String>,
+) -> Result<()> {
   let (script, script_cwd) = match hook {
     HookCommand::Script(s) if s.is_empty() => (None, None),
     HookCommand::Script(s) => (Some(s), None),
@@ -330,6 +387,7 @@ fn run_hook(name: &str, hook: HookCommand, interface: &AppInterface, debug: bool
 
     let mut env = command_env(debug);
     env.extend(interface.env());
+    env.extend(extra_env);
 
     debug!("Setting environment for hook {:?}", env);
 
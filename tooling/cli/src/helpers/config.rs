@@ -103,7 +103,11 @@ fn config_handle() -> &'static ConfigHandle {
 }
 
 /// Gets the static parsed config from `tauri.conf.json`.
-fn get_internal(merge_config: Option<&str>, reload: bool) -> crate::Result<ConfigHandle> {
+fn get_internal(
+  merge_config: Option<&str>,
+  targets: &[String],
+  reload: bool,
+) -> crate::Result<ConfigHandle> {
   if !reload && config_handle().lock().unwrap().is_some() {
     return Ok(config_handle().clone());
   }
@@ -113,25 +117,53 @@ fn get_internal(merge_config: Option<&str>, reload: bool) -> crate::Result<Confi
     tauri_utils::config::parse::parse_value(tauri_dir.join("tauri.conf.json"))?;
   let config_file_name = config_path.file_name().unwrap().to_string_lossy();
   let mut extensions = HashMap::new();
+  // extension names in merge order, used to find out which file set an invalid value
+  let mut merge_order = Vec::new();
 
   if let Some((platform_config, config_path)) =
-    tauri_utils::config::parse::read_platform(tauri_dir)?
+    tauri_utils::config::parse::read_platform(tauri_dir.clone())?
   {
     merge(&mut config, &platform_config);
-    extensions.insert(
-      config_path.file_name().unwrap().to_str().unwrap().into(),
-      platform_config,
-    );
+    let name: String = config_path.file_name().unwrap().to_str().unwrap().into();
+    merge_order.push(name.clone());
+    extensions.insert(name, platform_config);
+  }
+
+  // the bundle target overlays and the --config value are not read by the tauri build script,
+  // so we forward them through the TAURI_CONFIG environment variable
+  let mut env_config: Option<JsonValue> = None;
+
+  for target in targets {
+    if let Some((target_config, config_path)) =
+      tauri_utils::config::parse::read_target(tauri_dir.clone(), target)?
+    {
+      merge(&mut config, &target_config);
+      merge(
+        env_config.get_or_insert_with(|| JsonValue::Object(Default::default())),
+        &target_config,
+      );
+      let name: String = config_path.file_name().unwrap().to_str().unwrap().into();
+      merge_order.push(name.clone());
+      extensions.insert(name, target_config);
+    }
   }
 
   if let Some(merge_config) = merge_config {
-    set_var("TAURI_CONFIG", merge_config);
     let merge_config: JsonValue =
       serde_json::from_str(merge_config).with_context(|| "failed to parse config to merge")?;
     merge(&mut config, &merge_config);
+    merge(
+      env_config.get_or_insert_with(|| JsonValue::Object(Default::default())),
+      &merge_config,
+    );
+    merge_order.push(MERGE_CONFIG_EXTENSION_NAME.into());
     extensions.insert(MERGE_CONFIG_EXTENSION_NAME.into(), merge_config);
   };
 
+  if let Some(env_config) = env_config {
+    set_var("TAURI_CONFIG", env_config.to_string());
+  }
+
   if config_path.extension() == Some(OsStr::new("json"))
     || config_path.extension() == Some(OsStr::new("json5"))
   {
@@ -140,11 +172,18 @@ fn get_internal(merge_config: Option<&str>, reload: bool) -> crate::Result<Confi
     let result = schema.validate(&config);
     if let Err(errors) = result {
       for error in errors {
-        let path = error.instance_path.clone().into_vec().join(" > ");
+        let instance_path = error.instance_path.clone().into_vec();
+        let source = merge_order
+          .iter()
+          .rev()
+          .find(|name| has_path(&extensions[*name], &instance_path))
+          .map(|name| name.as_str())
+          .unwrap_or(&*config_file_name);
+        let path = instance_path.join(" > ");
         if path.is_empty() {
-          error!("`{}` error: {}", config_file_name, error);
+          error!("`{}` error: {}", source, error);
         } else {
-          error!("`{}` error on `{}`: {}", config_file_name, path, error);
+          error!("`{}` error on `{}`: {}", source, path, error);
         }
       }
       if !reload {
@@ -163,10 +202,36 @@ fn get_internal(merge_config: Option<&str>, reload: bool) -> crate::Result<Confi
   Ok(config_handle().clone())
 }
 
+/// Checks if the given JSON value defines the given (JSON pointer like) path.
+fn has_path(value: &JsonValue, path: &[String]) -> bool {
+  let mut value = value;
+  for segment in path {
+    let next = match value {
+      JsonValue::Object(object) => object.get(segment),
+      JsonValue::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get(i)),
+      _ => None,
+    };
+    match next {
+      Some(next) => value = next,
+      None => return false,
+    }
+  }
+  true
+}
+
 pub fn get(merge_config: Option<&str>) -> crate::Result<ConfigHandle> {
-  get_internal(merge_config, false)
+  get_internal(merge_config, &[], false)
+}
+
+/// Gets the config merged with the overlays of the given bundle targets,
+/// e.g. `tauri.deb.conf.json` for the `deb` bundle.
+pub fn get_with_targets(
+  merge_config: Option<&str>,
+  targets: &[String],
+) -> crate::Result<ConfigHandle> {
+  get_internal(merge_config, targets, false)
 }
 
 pub fn reload(merge_config: Option<&str>) -> crate::Result<ConfigHandle> {
-  get_internal(merge_config, true)
+  get_internal(merge_config, &[], true)
 }
@@ -4,7 +4,7 @@
 
 use anyhow::Context;
 use base64::{decode, encode};
-use minisign::{sign, KeyPair as KP, SecretKey, SecretKeyBox, SignatureBox};
+use minisign::{sign, KeyPair as KP, PublicKey, SecretKey, SecretKeyBox, SignatureBox};
 use std::{
   fs::{self, File, OpenOptions},
   io::{BufReader, BufWriter, Write},
@@ -134,6 +134,34 @@ where
   Ok((fs::canonicalize(&signature_path)?, signature_box))
 }
 
+/// Signs the updater bundles with the updater secret key.
+pub struct UpdaterSigner {
+  secret_key: SecretKey,
+  public_key: PublicKey,
+}
+
+impl UpdaterSigner {
+  /// Signs with the secret key, checking that it matches the public key the updater verifies the signatures with.
+  pub fn new(secret_key: SecretKey, public_key: PublicKey) -> Self {
+    Self {
+      secret_key,
+      public_key,
+    }
+  }
+}
+
+impl tauri_bundler::Signer for UpdaterSigner {
+  fn sign(&self, path: &Path) -> tauri_bundler::Result<PathBuf> {
+    let (signature_path, signature) = sign_file(&self.secret_key, path)?;
+    if signature.keynum() != self.public_key.keynum() {
+      return Err(anyhow::anyhow!(
+        "The updater secret key from `TAURI_PRIVATE_KEY` does not match the public key defined in `tauri.conf.json > tauri > updater > pubkey`."
+      ).into());
+    }
+    Ok(signature_path)
+  }
+}
+
 /// Gets the updater secret key from the given private key and password.
 pub fn secret_key(private_key: String, password: Option<String>) -> crate::Result<SecretKey> {
   let decoded_secret = decode_key(private_key)?;
@@ -11,6 +11,9 @@ use clap::Parser;
 use colored::Colorize;
 use serde::Deserialize;
 
+#[cfg(target_os = "linux")]
+mod check;
+
 use std::{
   collections::HashMap,
   fmt::Write,
@@ -87,7 +90,17 @@ enum PackageManager {
 
 #[derive(Debug, Parser)]
 #[clap(about = "Shows information about Tauri dependencies and project configuration")]
-pub struct Options;
+pub struct Options {
+  /// Actively checks the Linux desktop integration of this machine instead of printing information.
+  ///
+  /// Probes the desktop portal, notification server, tray watcher, webkit2gtk, the dev server and
+  /// whether the CLI runs in a sandbox. Exits with an error if any check fails.
+  #[clap(long)]
+  pub check: bool,
+  /// Prints the `--check` results as JSON.
+  #[clap(long, requires = "check")]
+  pub json: bool,
+}
 
 fn version_metadata() -> Result<VersionMetadata> {
   serde_json::from_str::<VersionMetadata>(include_str!("../metadata.json")).map_err(Into::into)
@@ -515,6 +528,129 @@ fn crate_version(
   (crate_version_string, suffix)
 }
 
+/// Runs the command and returns its trimmed stdout, or `None` if it fails or takes longer than
+/// `timeout`.
+#[cfg(target_os = "linux")]
+fn probe_output(command: &mut Command, timeout: std::time::Duration) -> Option<String> {
+  use std::{io::Read, process::Stdio, time::Instant};
+
+  let mut child = command
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .ok()?;
+  let started = Instant::now();
+  loop {
+    match child.try_wait() {
+      Ok(Some(status)) if status.success() => break,
+      Ok(None) if started.elapsed() < timeout => {
+        std::thread::sleep(std::time::Duration::from_millis(10))
+      }
+      _ => {
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+      }
+    }
+  }
+  let mut stdout = String::new();
+  child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+  Some(stdout.trim().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn sandbox_section() {
+  const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+  const NOT_DETECTED: &str = "not detected";
+
+  Section("Linux sandbox").display();
+
+  match tauri_utils::FlatpakInfo::detect() {
+    Some(info) => {
+      InfoBlock::new(
+        "Flatpak sandbox",
+        format!(
+          "{} (runtime: {}, branch: {})",
+          info.id,
+          info.runtime.as_deref().unwrap_or("unknown"),
+          info.branch.as_deref().unwrap_or("unknown")
+        ),
+      )
+      .display();
+    }
+    None => InfoBlock::new("Flatpak sandbox", "not sandboxed").display(),
+  }
+
+  for (name, bin) in [
+    ("flatpak", "flatpak"),
+    ("flatpak-builder", "flatpak-builder"),
+  ] {
+    let version = probe_output(Command::new(bin).arg("--version"), PROBE_TIMEOUT)
+      .and_then(|v| v.split_whitespace().last().map(ToString::to_string))
+      .unwrap_or_default();
+    VersionBlock::new(name, version).display();
+  }
+
+  let portal = probe_output(
+    Command::new("dbus-send").args([
+      "--session",
+      "--print-reply",
+      "--reply-timeout=1000",
+      "--dest=org.freedesktop.DBus",
+      "/org/freedesktop/DBus",
+      "org.freedesktop.DBus.NameHasOwner",
+      "string:org.freedesktop.portal.Desktop",
+    ]),
+    PROBE_TIMEOUT,
+  );
+  InfoBlock::new(
+    "xdg-desktop-portal",
+    match portal {
+      Some(reply) if reply.contains("boolean true") => "running".into(),
+      Some(_) => "not running".into(),
+      None => NOT_DETECTED.to_string(),
+    },
+  )
+  .display();
+}
+
+#[cfg(target_os = "linux")]
+fn run_checks(json: bool) -> Result<()> {
+  use crate::helpers::config::{AppUrl, WindowUrl};
+
+  let hook = panic::take_hook();
+  panic::set_hook(Box::new(|_info| {
+    // do nothing
+  }));
+  let tauri_dir = panic::catch_unwind(crate::helpers::app_paths::tauri_dir)
+    .map(Some)
+    .unwrap_or_default();
+  panic::set_hook(hook);
+
+  let dev_url = tauri_dir
+    .and_then(|_| get_config(None).ok())
+    .and_then(|config| {
+      let config_guard = config.lock().unwrap();
+      // bound so the guard is dropped before the config handle
+      #[allow(clippy::let_and_return)]
+      let dev_url = match &config_guard.as_ref()?.build.dev_path {
+        AppUrl::Url(WindowUrl::External(url)) => Some(url.clone()),
+        _ => None,
+      };
+      dev_url
+    });
+
+  check::report(&check::run(dev_url.as_ref()), json)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run_checks(_json: bool) -> Result<()> {
+  Err(anyhow::anyhow!(
+    "`tauri info --check` is only available on Linux"
+  ))
+}
+
 fn indent(spaces: usize) {
   print!("{}", " ".repeat(spaces));
 }
@@ -602,7 +738,11 @@ impl InfoBlock {
   }
 }
 
-pub fn command(_options: Options) -> Result<()> {
+pub fn command(options: Options) -> Result<()> {
+  if options.check {
+    return run_checks(options.json);
+  }
+
   Section("Environment").display();
 
   let os_info = os_info::get();
@@ -726,6 +866,9 @@ pub fn command(_options: Options) -> Result<()> {
   )
   .display();
 
+  #[cfg(target_os = "linux")]
+  sandbox_section();
+
   Section("Packages").display();
 
   let mut package_manager = PackageManager::Npm;
@@ -0,0 +1,264 @@
+// Copyright 2019-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Active Linux desktop integration checks for `tauri info --check`.
+
+use crate::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::{process::Command, time::Duration};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+  Pass,
+  Warn,
+  Fail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+  name: &'static str,
+  status: Status,
+  message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  hint: Option<&'static str>,
+}
+
+impl Check {
+  fn pass(name: &'static str, message: impl Into<String>) -> Self {
+    Self {
+      name,
+      status: Status::Pass,
+      message: message.into(),
+      hint: None,
+    }
+  }
+
+  fn warn(name: &'static str, message: impl Into<String>, hint: &'static str) -> Self {
+    Self {
+      name,
+      status: Status::Warn,
+      message: message.into(),
+      hint: Some(hint),
+    }
+  }
+
+  fn fail(name: &'static str, message: impl Into<String>, hint: &'static str) -> Self {
+    Self {
+      name,
+      status: Status::Fail,
+      message: message.into(),
+      hint: Some(hint),
+    }
+  }
+
+  fn display(&self) {
+    let status = match self.status {
+      Status::Pass => "pass".green(),
+      Status::Warn => "warn".yellow(),
+      Status::Fail => "fail".red(),
+    };
+    println!(
+      "  {} {} {}: {}",
+      "›".cyan(),
+      status.bold(),
+      self.name.bold(),
+      self.message
+    );
+    if let Some(hint) = self.hint {
+      println!("         {}", hint.italic());
+    }
+  }
+}
+
+/// Runs all checks, bounded by a timeout per probe.
+pub fn run(dev_url: Option<&url::Url>) -> Vec<Check> {
+  let mut checks = vec![
+    sandbox(),
+    webkit2gtk(),
+    portal(),
+    notification_server(),
+    tray_watcher(),
+  ];
+  if let Some(url) = dev_url {
+    checks.push(dev_server(url));
+  }
+  checks
+}
+
+/// Prints the checks, as JSON if requested, and fails if any of them failed.
+pub fn report(checks: &[Check], json: bool) -> Result<()> {
+  if json {
+    println!("{}", serde_json::to_string_pretty(checks)?);
+  } else {
+    println!();
+    println!("{}", "Environment checks".yellow().bold());
+    for check in checks {
+      check.display();
+    }
+  }
+
+  let failed = checks
+    .iter()
+    .filter(|check| check.status == Status::Fail)
+    .count();
+  if failed > 0 {
+    return Err(anyhow::anyhow!(
+      "{} of {} environment checks failed",
+      failed,
+      checks.len()
+    ));
+  }
+  Ok(())
+}
+
+fn session_bus_call(method: &str, args: &[&str]) -> Option<String> {
+  super::probe_output(
+    Command::new("dbus-send")
+      .args([
+        "--session",
+        "--print-reply",
+        "--reply-timeout=1000",
+        "--dest=org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+      ])
+      .arg(format!("org.freedesktop.DBus.{}", method))
+      .args(args),
+    PROBE_TIMEOUT,
+  )
+}
+
+/// Whether the name is owned on the session bus, `None` if the bus could not be reached.
+fn name_has_owner(name: &str) -> Option<bool> {
+  session_bus_call("NameHasOwner", &[&format!("string:{}", name)])
+    .map(|reply| reply.contains("boolean true"))
+}
+
+/// The running and activatable session bus names starting with the prefix.
+fn bus_names(prefix: &str) -> Vec<String> {
+  let mut names: Vec<String> = ["ListNames", "ListActivatableNames"]
+    .iter()
+    .filter_map(|method| session_bus_call(method, &[]))
+    .flat_map(|reply| {
+      reply
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("string \""))
+        .filter_map(|name| name.strip_suffix('"'))
+        .filter(|name| name.starts_with(prefix))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+    })
+    .collect();
+  names.sort();
+  names.dedup();
+  names
+}
+
+fn sandbox() -> Check {
+  const NAME: &str = "sandbox";
+  match tauri_utils::FlatpakInfo::detect() {
+    Some(info) => Check::warn(
+      NAME,
+      format!("running inside the Flatpak sandbox of {}", info.id),
+      "Host tools and libraries are not visible in the sandbox, run the CLI on the host or through `flatpak-spawn --host`.",
+    ),
+    None => Check::pass(NAME, "not sandboxed"),
+  }
+}
+
+fn webkit2gtk() -> Check {
+  const NAME: &str = "webkit2gtk";
+  const HINT: &str =
+    "Install the webkit2gtk 4.0 development package, e.g. `libwebkit2gtk-4.0-dev` on Debian.";
+  match super::probe_output(
+    Command::new("pkg-config").args(["--modversion", "webkit2gtk-4.0"]),
+    PROBE_TIMEOUT,
+  ) {
+    Some(version) if !version.is_empty() => Check::pass(NAME, version),
+    _ => Check::fail(NAME, "webkit2gtk-4.0 not found by pkg-config", HINT),
+  }
+}
+
+fn portal() -> Check {
+  const NAME: &str = "xdg-desktop-portal";
+  const HINT: &str =
+    "Install xdg-desktop-portal and a backend matching your desktop, e.g. xdg-desktop-portal-gtk.";
+  let running = match name_has_owner("org.freedesktop.portal.Desktop") {
+    Some(running) => running,
+    None => {
+      return Check::fail(
+        NAME,
+        "the session bus could not be reached",
+        "Make sure `dbus-send` is installed and `DBUS_SESSION_BUS_ADDRESS` is set.",
+      )
+    }
+  };
+  let backends: Vec<String> = bus_names("org.freedesktop.impl.portal.desktop.")
+    .into_iter()
+    .map(|name| {
+      name
+        .trim_start_matches("org.freedesktop.impl.portal.desktop.")
+        .to_string()
+    })
+    .collect();
+  match (running, backends.is_empty()) {
+    (true, false) => Check::pass(NAME, format!("running, backends: {}", backends.join(", "))),
+    (true, true) => Check::warn(NAME, "running without a backend", HINT),
+    (false, false) => Check::warn(
+      NAME,
+      format!("not running, backends: {}", backends.join(", ")),
+      "The portal is started on demand, make sure the xdg-desktop-portal service can be activated.",
+    ),
+    (false, true) => Check::fail(NAME, "not running and no backend installed", HINT),
+  }
+}
+
+fn notification_server() -> Check {
+  const NAME: &str = "notification server";
+  const BUS_NAME: &str = "org.freedesktop.Notifications";
+  let running = name_has_owner(BUS_NAME).unwrap_or_default();
+  if running {
+    Check::pass(NAME, "running")
+  } else if !bus_names(BUS_NAME).is_empty() {
+    Check::pass(NAME, "activatable")
+  } else {
+    Check::warn(
+      NAME,
+      "not found",
+      "Notifications will not be shown, install a notification daemon such as dunst or use a desktop that ships one.",
+    )
+  }
+}
+
+fn tray_watcher() -> Check {
+  const NAME: &str = "tray watcher";
+  if name_has_owner("org.kde.StatusNotifierWatcher").unwrap_or_default() {
+    Check::pass(NAME, "running")
+  } else {
+    Check::warn(
+      NAME,
+      "no StatusNotifierWatcher found",
+      "System tray icons will not be shown, on GNOME install the AppIndicator and KStatusNotifierItem extension.",
+    )
+  }
+}
+
+fn dev_server(url: &url::Url) -> Check {
+  use std::net::TcpStream;
+
+  const NAME: &str = "dev server";
+  const HINT: &str = "Start the dev server first; when the CLI runs in a sandbox, make sure it shares the host network, e.g. with `--share=network`.";
+  let addrs = url.socket_addrs(|| None).unwrap_or_default();
+  if addrs
+    .iter()
+    .any(|addr| TcpStream::connect_timeout(addr, PROBE_TIMEOUT).is_ok())
+  {
+    Check::pass(NAME, format!("{} is reachable", url))
+  } else {
+    Check::warn(NAME, format!("{} is not reachable", url), HINT)
+  }
+}
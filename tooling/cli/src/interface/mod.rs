@@ -35,6 +35,7 @@ pub trait AppSettings {
     config: &Config,
     out_dir: &Path,
     package_types: Option<Vec<PackageType>>,
+    log_level: log::Level,
   ) -> crate::Result<Settings> {
     let no_default_features = options.args.contains(&"--no-default-features".into());
     let mut enabled_features = options.features.clone().unwrap_or_default();
@@ -53,7 +54,8 @@ pub trait AppSettings {
       .bundle_settings(self.get_bundle_settings(config, &enabled_features)?)
       .binaries(self.get_binaries(config, &target)?)
       .project_out_directory(out_dir)
-      .target(target);
+      .target(target)
+      .log_level(log_level);
 
     if let Some(types) = package_types {
       settings_builder = settings_builder.package_types(types);
@@ -28,8 +28,8 @@ use notify_debouncer_mini::new_debouncer;
 use serde::Deserialize;
 use shared_child::SharedChild;
 use tauri_bundler::{
-  AppCategory, BundleBinary, BundleSettings, DebianSettings, MacOsSettings, PackageSettings,
-  UpdaterSettings, WindowsSettings,
+  AppCategory, BundleBinary, BundleSettings, DebianSettings, DesktopEntrySettings,
+  LinuxSigningSettings, MacOsSettings, PackageSettings, UpdaterSettings, WindowsSettings,
 };
 use tauri_utils::config::parse::is_configuration_file;
 
@@ -920,6 +920,7 @@ fn tauri_config_to_bundle_settings(
     identifier: Some(config.identifier),
     publisher: config.publisher,
     icon: Some(config.icon),
+    auto_resize_icons: config.auto_resize_icons,
     resources: if resources.is_empty() {
       None
     } else {
@@ -935,6 +936,12 @@ fn tauri_config_to_bundle_settings(
     },
     short_description: config.short_description,
     long_description: config.long_description,
+    changelog: config.changelog,
+    linux_signing: config.linux_signing.map(|signing| LinuxSigningSettings {
+      gpg_key: signing.gpg_key,
+      gpg_home: signing.gpg_home,
+      deb: signing.deb,
+    }),
     external_bin: config.external_bin,
     deb: DebianSettings {
       depends: if depends.is_empty() {
@@ -943,6 +950,20 @@ fn tauri_config_to_bundle_settings(
         Some(depends)
       },
       files: config.deb.files,
+      desktop_entry: config
+        .deb
+        .desktop_entry
+        .map(|entry| DesktopEntrySettings {
+          categories: entry.categories,
+          keywords: entry.keywords,
+          startup_wm_class: entry.startup_wm_class,
+          terminal: entry.terminal,
+          single_main_window: entry.single_main_window,
+          exec_arg: entry.exec_arg,
+          extra: entry.extra.into_iter().collect(),
+          localized: entry.localized.into_iter().collect(),
+        })
+        .unwrap_or_default(),
     },
     macos: MacOsSettings {
       frameworks: config.macos.frameworks,
@@ -55,6 +55,9 @@ struct Cli {
   /// Enables verbose logging
   #[clap(short, long, global = true, action = ArgAction::Count)]
   verbose: u8,
+  /// Only logs warnings and errors
+  #[clap(long, global = true, conflicts_with = "verbose")]
+  quiet: bool,
   #[clap(subcommand)]
   command: Commands,
 }
@@ -118,10 +121,16 @@ where
     Err(e) => e.exit(),
   };
 
+  let log_level = if cli.quiet {
+    Level::Warn
+  } else {
+    verbosity_level(cli.verbose)
+  };
+
   let mut builder = Builder::from_default_env();
   let init_res = builder
     .format_indent(Some(12))
-    .filter(None, verbosity_level(cli.verbose).to_level_filter())
+    .filter(None, log_level.to_level_filter())
     .format(|f, record| {
       let mut is_command_output = false;
       if let Some(action) = record.key_values().get("action".into()) {
@@ -160,7 +169,7 @@ where
   }
 
   match cli.command {
-    Commands::Build(options) => build::command(options)?,
+    Commands::Build(options) => build::command(options, log_level)?,
     Commands::Dev(options) => dev::command(options)?,
     Commands::Icon(options) => icon::command(options)?,
     Commands::Info(options) => info::command(options)?,